@@ -0,0 +1,130 @@
+//! Benchmarks for Scuttle's hot paths.
+//!
+//! Run with `cargo bench`. Covers port specification parsing and expansion,
+//! scan record serialization, the overhead of `run_scan` itself using a
+//! scanner that answers instantly, and connect scans of every port on
+//! loopback, where nearly every probe is refused at once.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use scuttle::scanner::{run_scan, MockScanner, ScanJobConfig, TcpConnectScanner};
+use scuttle::storage::ScanRecord;
+use scuttle::{Port, PortResult, PortSpec, PortStatus, ScanType, Scanner};
+use std::hint::black_box;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Fragmented, overlapping spec that exercises parsing and normalization.
+fn large_spec() -> String {
+    (0..2000)
+        .map(|i| format!("{}-{}", i * 30 + 1, i * 30 + 40))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn bench_port_spec(c: &mut Criterion) {
+    let spec = large_spec();
+    let mut group = c.benchmark_group("port_spec");
+
+    group.bench_function("from_str", |b| {
+        b.iter(|| black_box(&spec).parse::<PortSpec>().unwrap())
+    });
+
+    let full: PortSpec = "1-65535".parse().unwrap();
+    group.throughput(Throughput::Elements(65535));
+    group.bench_function("to_ports_full", |b| b.iter(|| black_box(&full).to_ports()));
+
+    group.finish();
+}
+
+fn bench_record_serialization(c: &mut Criterion) {
+    let results = (1..=1000)
+        .filter_map(Port::new)
+        .map(|port| {
+            PortResult::new(port, PortStatus::Open, "http")
+                .with_banner(Some("HTTP/1.1 200 OK\nServer: nginx/1.24.0".to_string()))
+                .with_response_time(3)
+        })
+        .collect();
+    let record =
+        ScanRecord::new("127.0.0.1", "127.0.0.1", ScanType::Connect).finalize(results, 1234);
+
+    c.bench_function("scan_record_to_json", |b| {
+        b.iter(|| serde_json::to_string(black_box(&record)).unwrap())
+    });
+}
+
+fn bench_run_scan(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let ports: Vec<Port> = (1..=10_000).filter_map(Port::new).collect();
+    let scanner: Arc<dyn Scanner> =
+        Arc::new(MockScanner::default().with_default_status(PortStatus::Open));
+
+    let mut group = c.benchmark_group("run_scan");
+    group.throughput(Throughput::Elements(ports.len() as u64));
+    for concurrency in [1, 100, 1000] {
+        group.bench_with_input(
+            BenchmarkId::from_parameter(concurrency),
+            &concurrency,
+            |b, &concurrency| {
+                b.to_async(&runtime).iter(|| {
+                    let config = ScanJobConfig::new(ports.clone()).with_concurrency(concurrency);
+                    run_scan(Arc::clone(&scanner), config)
+                })
+            },
+        );
+    }
+    group.bench_function("1000_batch_16", |b| {
+        b.to_async(&runtime).iter(|| {
+            let config = ScanJobConfig::new(ports.clone())
+                .with_concurrency(1000)
+                .with_batch_size(16);
+            run_scan(Arc::clone(&scanner), config)
+        })
+    });
+    group.finish();
+}
+
+fn bench_connect_scan(c: &mut Criterion) {
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    let ports: Vec<Port> = (1..=u16::MAX).filter_map(Port::new).collect();
+    let scanner = |closed_services| -> Arc<dyn Scanner> {
+        Arc::new(
+            TcpConnectScanner::new(
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                Duration::from_millis(500),
+                false,
+            )
+            .with_closed_services(closed_services),
+        )
+    };
+
+    let mut group = c.benchmark_group("connect_scan");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(ports.len() as u64));
+    for (name, closed_services, batch_size) in [
+        ("per_port", true, 1),
+        ("open_only", false, 1),
+        ("open_only_batch_16", false, 16),
+    ] {
+        let scanner = scanner(closed_services);
+        group.bench_function(name, |b| {
+            b.to_async(&runtime).iter(|| {
+                let config = ScanJobConfig::new(ports.clone())
+                    .with_concurrency(500)
+                    .with_batch_size(batch_size);
+                run_scan(Arc::clone(&scanner), config)
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_port_spec,
+    bench_record_serialization,
+    bench_run_scan,
+    bench_connect_scan
+);
+criterion_main!(benches);
@@ -0,0 +1,329 @@
+//! Scripted send/expect exchanges with an open TCP service.
+//!
+//! Used by `scan --interact`. A script is a list of statements, one per
+//! line or separated by `;`, with `#` starting a comment:
+//!
+//! ```text
+//! expect "220"            # wait for the greeting
+//! send "EHLO scuttle\r\n"
+//! expect "250 "
+//! send "QUIT\r\n"
+//! ```
+//!
+//! `send` writes its string; `expect` reads until the string has been seen.
+//! Strings accept the escapes `\r`, `\n`, `\t`, `\0`, `\\`, and `\"`. The
+//! exchange stops at the first failed step, and the transcript records what
+//! was sent (`>`), what came back (`<`), and why it stopped (`!`).
+
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::time::timeout;
+
+/// Errors from loading or parsing an interaction script.
+#[derive(Error, Debug)]
+pub enum ScriptError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+
+    #[error("script has no statements")]
+    Empty,
+
+    #[error("failed to read script: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+/// One statement of an interaction script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Write these bytes to the service.
+    Send(String),
+    /// Read until these bytes have been received.
+    Expect(String),
+}
+
+impl fmt::Display for Step {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Send(data) => write!(f, "send {:?}", data),
+            Self::Expect(data) => write!(f, "expect {:?}", data),
+        }
+    }
+}
+
+/// A parsed send/expect script.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Script {
+    steps: Vec<Step>,
+}
+
+impl Script {
+    /// Read and parse a script file.
+    pub fn load(path: &Path) -> Result<Self, ScriptError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+
+    /// Statements in the order they run.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+}
+
+impl FromStr for Script {
+    type Err = ScriptError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            chars: s.chars().peekable(),
+            line: 1,
+        };
+        let mut steps = Vec::new();
+        while let Some(step) = parser.statement()? {
+            steps.push(step);
+        }
+
+        if steps.is_empty() {
+            return Err(ScriptError::Empty);
+        }
+        Ok(Self { steps })
+    }
+}
+
+/// Hand-rolled scanner over the script text, tracking the line for errors.
+struct Parser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl Parser<'_> {
+    /// Parse the next statement, or `None` at the end of the script.
+    fn statement(&mut self) -> Result<Option<Step>, ScriptError> {
+        self.skip_separators();
+        if self.chars.peek().is_none() {
+            return Ok(None);
+        }
+
+        let mut keyword = String::new();
+        while let Some(&c) = self.chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+            keyword.push(c);
+            self.chars.next();
+        }
+        let make: fn(String) -> Step = match keyword.as_str() {
+            "send" => Step::Send,
+            "expect" => Step::Expect,
+            "" => return Err(self.error("expected `send` or `expect`")),
+            other => return Err(self.error(&format!("unknown statement `{}`", other))),
+        };
+
+        while self.chars.next_if(|&c| c == ' ' || c == '\t').is_some() {}
+        if self.chars.next() != Some('"') {
+            return Err(self.error(&format!("`{}` needs a quoted string", keyword)));
+        }
+        let data = self.string()?;
+        if data.is_empty() {
+            return Err(self.error(&format!("`{}` needs a non-empty string", keyword)));
+        }
+        Ok(Some(make(data)))
+    }
+
+    /// Read the rest of a quoted string, resolving escapes.
+    fn string(&mut self) -> Result<String, ScriptError> {
+        let mut data = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(data),
+                Some('\\') => data.push(match self.chars.next() {
+                    Some('r') => '\r',
+                    Some('n') => '\n',
+                    Some('t') => '\t',
+                    Some('0') => '\0',
+                    Some(c @ ('\\' | '"')) => c,
+                    Some(c) => return Err(self.error(&format!("unknown escape `\\{}`", c))),
+                    None => break,
+                }),
+                Some('\n') | None => break,
+                Some(c) => data.push(c),
+            }
+        }
+        Err(self.error("unterminated string"))
+    }
+
+    /// Skip whitespace, `;` separators, and comments.
+    fn skip_separators(&mut self) {
+        while let Some(&c) = self.chars.peek() {
+            match c {
+                '\n' => self.line += 1,
+                '#' => {
+                    while self.chars.next_if(|&c| c != '\n').is_some() {}
+                    continue;
+                }
+                c if c.is_whitespace() || c == ';' => {}
+                _ => return,
+            }
+            self.chars.next();
+        }
+    }
+
+    fn error(&self, message: &str) -> ScriptError {
+        ScriptError::Syntax {
+            line: self.line,
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Run `script` over `stream` and return the transcript.
+///
+/// Each `expect` waits at most `read_timeout` per read and gives up after
+/// `max_size` bytes without a match.
+pub async fn run_script<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    script: &Script,
+    read_timeout: Duration,
+    max_size: usize,
+) -> String {
+    let mut transcript = Vec::new();
+    let mut pending: Vec<u8> = Vec::new();
+    let mut buffer = vec![0u8; max_size.clamp(1, super::MAX_BANNER_SIZE)];
+
+    for step in script.steps() {
+        match step {
+            Step::Send(data) => {
+                if let Err(e) = stream.write_all(data.as_bytes()).await {
+                    transcript.push(format!("! send failed: {}", e));
+                    break;
+                }
+                transcript.push(format!("> {}", printable(data.as_bytes())));
+            }
+            Step::Expect(pattern) => {
+                let outcome = loop {
+                    if let Some(end) = find_line(&pending, pattern.as_bytes()) {
+                        let rest = pending.split_off(end);
+                        push_received(&mut transcript, &pending);
+                        pending = rest;
+                        break None;
+                    }
+                    if pending.len() >= buffer.len() {
+                        break Some(format!("{} bytes without a match", pending.len()));
+                    }
+                    match timeout(read_timeout, stream.read(&mut buffer)).await {
+                        Ok(Ok(0)) => break Some("connection closed".to_string()),
+                        Ok(Ok(n)) => pending.extend_from_slice(&buffer[..n]),
+                        Ok(Err(e)) => break Some(e.to_string()),
+                        Err(_) => break Some("timed out".to_string()),
+                    }
+                };
+
+                if let Some(reason) = outcome {
+                    push_received(&mut transcript, &pending);
+                    transcript.push(format!("! {} not seen: {}", step, reason));
+                    break;
+                }
+            }
+        }
+    }
+
+    transcript.join("\n")
+}
+
+/// End of the first occurrence of `needle` in `haystack`, extended to the
+/// end of its line if that has been received, so lines stay whole.
+fn find_line(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    let end = haystack
+        .windows(needle.len())
+        .position(|window| window == needle)?
+        + needle.len();
+    Some(
+        haystack[end..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(end, |newline| end + newline + 1),
+    )
+}
+
+/// Add received bytes to the transcript, one `<` line per line of data.
+fn push_received(transcript: &mut Vec<String>, data: &[u8]) {
+    transcript.extend(
+        printable(data)
+            .split('\n')
+            .filter(|line| !line.is_empty())
+            .map(|line| format!("< {}", line)),
+    );
+}
+
+/// Render bytes as text, keeping line breaks and masking other controls.
+fn printable(data: &[u8]) -> String {
+    data.iter()
+        .filter(|&&b| b != b'\r')
+        .map(|&b| match b {
+            b'\n' => '\n',
+            b'\t' => ' ',
+            b if b.is_ascii_graphic() || b == b' ' => b as char,
+            _ => '.',
+        })
+        .collect::<String>()
+        .trim_end_matches('\n')
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_script() {
+        let script: Script = "# SMTP\nexpect \"220\"\nsend \"EHLO x\\r\\n\"; expect \"250\"\n"
+            .parse()
+            .unwrap();
+        assert_eq!(
+            script.steps(),
+            [
+                Step::Expect("220".to_string()),
+                Step::Send("EHLO x\r\n".to_string()),
+                Step::Expect("250".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "send \"a\"\nwait \"b\"".parse::<Script>().unwrap_err();
+        assert_eq!(err.to_string(), "line 2: unknown statement `wait`");
+        assert!(matches!(
+            "send \"open".parse::<Script>(),
+            Err(ScriptError::Syntax { .. })
+        ));
+        assert!(matches!(
+            "send hello".parse::<Script>(),
+            Err(ScriptError::Syntax { .. })
+        ));
+        assert!(matches!(
+            "# nothing\n".parse::<Script>(),
+            Err(ScriptError::Empty)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_run_script() {
+        let (client, mut server) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            server.write_all(b"220 ready\r\n").await.unwrap();
+            let mut buf = [0u8; 64];
+            let n = server.read(&mut buf).await.unwrap();
+            assert_eq!(&buf[..n], b"HELO x\r\n");
+            server.write_all(b"250 hello\r\n").await.unwrap();
+        });
+
+        let script = "expect \"220\"; send \"HELO x\\r\\n\"; expect \"250\"; expect \"354\""
+            .parse()
+            .unwrap();
+        let transcript = run_script(client, &script, Duration::from_millis(200), 1024).await;
+        assert_eq!(
+            transcript,
+            "< 220 ready\n> HELO x\n< 250 hello\n! expect \"354\" not seen: connection closed"
+        );
+    }
+}
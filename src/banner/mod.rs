@@ -0,0 +1,182 @@
+//! Banner grabbing functionality for TCP connections.
+//!
+//! Attempts to retrieve service banners by reading initial data
+//! sent by services after connection establishment. For services that need
+//! a conversation first, [`interact`] runs a scripted send/expect exchange.
+
+pub mod interact;
+#[cfg(feature = "tls")]
+pub mod tls;
+
+use std::net::SocketAddr;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// Default number of bytes to read for a banner.
+pub const DEFAULT_BANNER_SIZE: usize = 1024;
+
+/// Largest banner read size that may be configured.
+pub const MAX_BANNER_SIZE: usize = 64 * 1024;
+
+/// Probes to send to elicit responses from certain services.
+const HTTP_PROBE: &[u8] = b"HEAD / HTTP/1.0\r\n\r\n";
+
+/// Grab a banner from an open TCP port.
+///
+/// This function attempts to:
+/// 1. Read any data the service sends immediately upon connection
+/// 2. If no immediate data, send a probe and wait for response
+///
+/// Returns `None` if no banner could be retrieved. Reads share the
+/// connect timeout.
+#[allow(dead_code)]
+pub async fn grab_banner(addr: SocketAddr, connect_timeout: Duration) -> Option<String> {
+    // Connect with timeout
+    let stream = timeout(connect_timeout, TcpStream::connect(addr))
+        .await
+        .ok()?
+        .ok()?;
+
+    grab_banner_from_stream(stream, addr.port(), connect_timeout, DEFAULT_BANNER_SIZE).await
+}
+
+/// Grab banner from an existing stream.
+///
+/// Each read (the initial greeting and the probe response) waits at most
+/// `read_timeout` and captures up to `max_size` bytes. Streams that are not
+/// TCP, such as Unix sockets, pass port 0 to skip the HTTP probe.
+pub async fn grab_banner_from_stream<S: AsyncRead + AsyncWrite + Unpin>(
+    mut stream: S,
+    port: u16,
+    read_timeout: Duration,
+    max_size: usize,
+) -> Option<String> {
+    let mut buffer = vec![0u8; max_size.clamp(1, MAX_BANNER_SIZE)];
+
+    // Try reading immediate banner
+    match timeout(read_timeout, stream.read(&mut buffer)).await {
+        Ok(Ok(n)) if n > 0 => {
+            return Some(sanitize_banner(&buffer[..n]));
+        }
+        _ => {}
+    }
+
+    // For HTTP ports, send a probe
+    if is_http_port(port) && stream.write_all(HTTP_PROBE).await.is_ok() {
+        if let Ok(Ok(n)) = timeout(read_timeout, stream.read(&mut buffer)).await {
+            if n > 0 {
+                return Some(sanitize_banner(&buffer[..n]));
+            }
+        }
+    }
+
+    None
+}
+
+/// Check if a port is commonly used for HTTP services.
+fn is_http_port(port: u16) -> bool {
+    matches!(
+        port,
+        80 | 443 | 8000 | 8008 | 8080 | 8081 | 8082 | 8083 | 8443 | 8888 | 9000 | 9090
+    )
+}
+
+/// Header lines kept from an HTTP response banner.
+const HTTP_BANNER_LINES: usize = 8;
+
+/// Sanitize banner by removing non-printable characters.
+///
+/// HTTP responses keep their first few lines so headers such as `Server:`
+/// stay readable; other greetings (SSH, FTP, SMTP) collapse onto one line.
+/// The full capture is kept; output formats truncate for display.
+fn sanitize_banner(data: &[u8]) -> String {
+    let text: String = data
+        .iter()
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' || b == b'\r' || b == b'\n' {
+                b as char
+            } else if b == b'\t' {
+                ' '
+            } else {
+                '.'
+            }
+        })
+        .collect();
+
+    if looks_like_http(&text) {
+        text.lines()
+            .map(collapse_spaces)
+            .filter(|line| !line.is_empty())
+            .take(HTTP_BANNER_LINES)
+            .collect::<Vec<_>>()
+            .join("\n")
+    } else {
+        collapse_spaces(&text.replace(['\r', '\n'], " "))
+    }
+}
+
+/// Check if a response starts with an HTTP status line.
+fn looks_like_http(text: &str) -> bool {
+    text.starts_with("HTTP/")
+}
+
+/// Collapse runs of spaces and trim the ends.
+fn collapse_spaces(s: &str) -> String {
+    s.split(' ')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_banner() {
+        let data = b"SSH-2.0-OpenSSH_8.9\r\n";
+        assert_eq!(sanitize_banner(data), "SSH-2.0-OpenSSH_8.9");
+    }
+
+    #[test]
+    fn test_sanitize_binary_data() {
+        let data = b"\x00\x01Hello\x02World\x03";
+        assert_eq!(sanitize_banner(data), "..Hello.World.");
+    }
+
+    #[test]
+    fn test_sanitize_keeps_long_banner() {
+        let data = "220 ".to_string() + &"x".repeat(600);
+        assert_eq!(sanitize_banner(data.as_bytes()).len(), 604);
+    }
+
+    #[test]
+    fn test_sanitize_http_keeps_lines() {
+        let data = b"HTTP/1.1 200 OK\r\nServer: nginx/1.24.0\r\nContent-Type:  text/html\r\n\r\n<html>";
+        assert_eq!(
+            sanitize_banner(data),
+            "HTTP/1.1 200 OK\nServer: nginx/1.24.0\nContent-Type: text/html\n<html>"
+        );
+    }
+
+    #[test]
+    fn test_sanitize_http_limits_lines() {
+        let data = "HTTP/1.0 200 OK\r\n".to_string() + &"X-Header: 1\r\n".repeat(20);
+        assert_eq!(sanitize_banner(data.as_bytes()).lines().count(), HTTP_BANNER_LINES);
+    }
+
+    #[test]
+    fn test_sanitize_multiline_greeting_collapses() {
+        let data = b"220-mail.example.com ESMTP\r\n220 ready\r\n";
+        assert_eq!(sanitize_banner(data), "220-mail.example.com ESMTP 220 ready");
+    }
+
+    #[test]
+    fn test_is_http_port() {
+        assert!(is_http_port(80));
+        assert!(is_http_port(8080));
+        assert!(!is_http_port(22));
+    }
+}
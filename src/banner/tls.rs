@@ -0,0 +1,188 @@
+//! ALPN negotiation with TLS services.
+//!
+//! A handshake offering `h2` and `http/1.1` reveals which application
+//! protocol a TLS endpoint selects, telling an HTTP/2-capable server (or a
+//! gRPC one, which requires HTTP/2) from plain HTTPS. Certificates are not
+//! verified: the handshake only asks what the server would speak, and the
+//! connection is closed without sending any data.
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{ring, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, SignatureScheme};
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+use tokio_rustls::TlsConnector;
+
+/// Protocols offered in the handshake, most preferred first.
+pub const ALPN_PROTOCOLS: [&str; 2] = ["h2", "http/1.1"];
+
+/// Offers [`ALPN_PROTOCOLS`] to TLS services and reports the one selected.
+#[derive(Clone)]
+pub struct AlpnProbe {
+    connector: TlsConnector,
+    server_name: ServerName<'static>,
+}
+
+impl std::fmt::Debug for AlpnProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlpnProbe")
+            .field("server_name", &self.server_name)
+            .finish_non_exhaustive()
+    }
+}
+
+impl AlpnProbe {
+    /// Create a probe for `target`, sending `hostname` as the SNI name when
+    /// it is a DNS name rather than an address.
+    pub fn new(target: IpAddr, hostname: &str) -> Self {
+        let provider = Arc::new(ring::default_provider());
+        let mut config = ClientConfig::builder_with_provider(Arc::clone(&provider))
+            .with_safe_default_protocol_versions()
+            .expect("the ring provider supports the default TLS versions")
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+            .with_no_client_auth();
+        config.alpn_protocols = ALPN_PROTOCOLS
+            .iter()
+            .map(|p| p.as_bytes().to_vec())
+            .collect();
+
+        let server_name = ServerName::try_from(hostname.to_string())
+            .unwrap_or_else(|_| ServerName::IpAddress(target.into()));
+
+        Self {
+            connector: TlsConnector::from(Arc::new(config)),
+            server_name,
+        }
+    }
+
+    /// Complete a TLS handshake over `stream` within `handshake_timeout`.
+    ///
+    /// Returns the protocol the server selected, or `None` if the handshake
+    /// failed or the server chose none.
+    pub async fn negotiate(
+        &self,
+        stream: TcpStream,
+        handshake_timeout: Duration,
+    ) -> Option<String> {
+        let handshake = self.connector.connect(self.server_name.clone(), stream);
+        let tls = match timeout(handshake_timeout, handshake).await {
+            Ok(Ok(tls)) => tls,
+            Ok(Err(e)) => {
+                tracing::trace!(error = %e, "TLS handshake failed");
+                return None;
+            }
+            Err(_) => {
+                tracing::trace!("TLS handshake timed out");
+                return None;
+            }
+        };
+        let protocol = tls.get_ref().1.alpn_protocol()?;
+        Some(String::from_utf8_lossy(protocol).into_owned())
+    }
+}
+
+/// Accepts every certificate; only the negotiated protocol is of interest.
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        _message: &[u8],
+        _cert: &CertificateDer<'_>,
+        _dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        Ok(HandshakeSignatureValid::assertion())
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rustls::pki_types::PrivateKeyDer;
+    use rustls::ServerConfig;
+    use std::net::Ipv4Addr;
+    use tokio::net::TcpListener;
+    use tokio_rustls::TlsAcceptor;
+
+    /// A TLS server on localhost offering `protocols`, for one connection.
+    async fn tls_server(protocols: &[&str]) -> u16 {
+        let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+        let key = PrivateKeyDer::Pkcs8(cert.signing_key.serialize_der().into());
+        let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_no_client_auth()
+            .with_single_cert(vec![cert.cert.der().clone()], key)
+            .unwrap();
+        config.alpn_protocols = protocols.iter().map(|p| p.as_bytes().to_vec()).collect();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let acceptor = TlsAcceptor::from(Arc::new(config));
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            let _ = acceptor.accept(stream).await;
+        });
+        port
+    }
+
+    async fn negotiate(port: u16) -> Option<String> {
+        let probe = AlpnProbe::new(IpAddr::V4(Ipv4Addr::LOCALHOST), "localhost");
+        let stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        probe.negotiate(stream, Duration::from_secs(2)).await
+    }
+
+    #[tokio::test]
+    async fn test_negotiates_alpn() {
+        let port = tls_server(&["h2", "http/1.1"]).await;
+        assert_eq!(negotiate(port).await.as_deref(), Some("h2"));
+
+        let port = tls_server(&["http/1.1"]).await;
+        assert_eq!(negotiate(port).await.as_deref(), Some("http/1.1"));
+
+        // A TLS server that takes part in no ALPN
+        let port = tls_server(&[]).await;
+        assert_eq!(negotiate(port).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_plain_tcp_has_no_alpn() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (stream, _) = listener.accept().await.unwrap();
+            drop(stream);
+        });
+        assert_eq!(negotiate(port).await, None);
+    }
+}
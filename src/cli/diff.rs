@@ -0,0 +1,123 @@
+//! Diff subcommand implementation.
+//!
+//! Handles the `scuttle diff <baseline> <current>` command, which compares
+//! two saved scans host by host. Either ID may name a single scan or the
+//! session of a range scan.
+
+use crate::cli::OutputFormat;
+use crate::error::{CliError, CliResult, StorageError};
+use crate::output;
+use crate::storage::{ScanRecord, ScanStore, SessionDiff};
+use crate::types::ScanId;
+use chrono::{DateTime, Utc};
+use clap::Parser;
+use serde::Serialize;
+
+/// Compare two saved scans, such as two scans of a range, host by host.
+#[derive(Parser, Debug)]
+pub struct DiffCommand {
+    /// Scan or session ID, or prefix, of the earlier scan
+    #[arg(value_name = "BASELINE")]
+    pub baseline: String,
+
+    /// Scan or session ID, or prefix, of the later scan
+    #[arg(value_name = "CURRENT")]
+    pub current: String,
+
+    /// Output format (plain, json, json-compact, or jsonl)
+    #[arg(short, long, value_enum, default_value = "plain")]
+    pub output: OutputFormat,
+}
+
+/// A saved scan or range scan, as the hosts it covered.
+struct Saved {
+    id: ScanId,
+    kind: &'static str,
+    started_at: DateTime<Utc>,
+    scan_type: String,
+    records: Vec<ScanRecord>,
+}
+
+/// JSON form of a diff, naming the scans compared.
+#[derive(Serialize)]
+struct Report<'a> {
+    baseline: ScanId,
+    current: ScanId,
+    #[serde(flatten)]
+    diff: &'a SessionDiff,
+}
+
+impl DiffCommand {
+    /// Execute the diff command.
+    pub fn execute(&self, _verbose: u8, quiet: bool) -> CliResult<()> {
+        if self.output == OutputFormat::Csv {
+            return Err(CliError::InvalidArgument(
+                "diff output is plain, json, json-compact, or jsonl".to_string(),
+            ));
+        }
+
+        let store = ScanStore::new()?;
+        let baseline = load(&store, &self.baseline)?;
+        let current = load(&store, &self.current)?;
+
+        if baseline.scan_type != current.scan_type && !quiet {
+            output::print_warning(&format!(
+                "comparing a {} scan with a {} scan",
+                baseline.scan_type, current.scan_type
+            ));
+        }
+
+        let diff = SessionDiff::between(&baseline.records, &current.records);
+        let report = Report {
+            baseline: baseline.id,
+            current: current.id,
+            diff: &diff,
+        };
+
+        match self.output {
+            OutputFormat::Json => output::write_json(&report, false)?,
+            OutputFormat::JsonCompact => output::write_json(&report, true)?,
+            OutputFormat::Jsonl => println!(
+                "{}",
+                serde_json::to_string(&report).map_err(|e| CliError::Other(e.to_string()))?
+            ),
+            _ => {
+                let since = format!(
+                    "{} {} ({})",
+                    baseline.kind,
+                    baseline.id.short(),
+                    baseline.started_at.format("%Y-%m-%d %H:%M")
+                );
+                output::print_session_diff(&diff, &current.scan_type, &since)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Load a scan, or failing that a range scan session, by ID or prefix.
+fn load(store: &ScanStore, id: &str) -> CliResult<Saved> {
+    match store.resolve(id) {
+        Err(StorageError::ScanNotFound(_)) => {
+            let session = store.load_session(&store.resolve_session(id)?)?;
+            Ok(Saved {
+                id: session.id,
+                kind: "session",
+                started_at: session.started_at,
+                scan_type: session.scan_type,
+                records: session.records,
+            })
+        }
+        id => {
+            let record = store.load(&id?)?;
+            Ok(Saved {
+                id: record.id,
+                kind: "scan",
+                started_at: record.started_at,
+                scan_type: record.scan_type.clone(),
+                records: vec![record],
+            })
+        }
+    }
+}
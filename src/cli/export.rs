@@ -1,20 +1,35 @@
 //! Export subcommand implementation.
 //!
 //! Handles the `scuttle export <scan-id>` command for exporting scan results.
+//! The ID may also name the session of a range scan, which exports every host.
 
-use crate::cli::OutputFormat;
-use crate::error::CliResult;
+use crate::error::{CliError, CliResult, StorageError};
 use crate::output;
-use crate::storage::ScanStore;
-use crate::types::ScanId;
+use crate::storage::{ScanRecord, ScanSession, ScanStore, StorageFormat};
 use clap::Parser;
-use std::fs;
-use std::path::PathBuf;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+
+/// Export file format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ExportFormat {
+    /// Human-readable plain text
+    Plain,
+    /// JSON structured output
+    Json,
+    /// JSON Lines, one compact JSON object per record
+    Jsonl,
+    /// CSV format for data analysis
+    Csv,
+    /// MessagePack, a compact binary encoding of the JSON fields
+    Msgpack,
+}
 
 /// Export scan results.
 #[derive(Parser, Debug)]
 pub struct ExportCommand {
-    /// Scan ID or prefix to export
+    /// Scan or session ID, or prefix, to export
     ///
     /// Can be a full UUID or the first few characters (short ID).
     #[arg(value_name = "SCAN_ID")]
@@ -22,12 +37,18 @@ pub struct ExportCommand {
 
     /// Output format
     #[arg(short, long, value_enum, default_value = "json")]
-    pub format: OutputFormat,
+    pub format: ExportFormat,
 
     /// Output file path (prints to stdout if not specified)
     #[arg(short = 'o', long = "output")]
     pub output_file: Option<PathBuf>,
 
+    /// Add to the output file instead of replacing it (CSV, JSON Lines, plain)
+    ///
+    /// CSV rows are appended without repeating the header.
+    #[arg(long, requires = "output_file")]
+    pub append: bool,
+
     /// Include closed ports in export
     #[arg(long)]
     pub include_closed: bool,
@@ -35,52 +56,122 @@ pub struct ExportCommand {
     /// Export only open ports
     #[arg(long)]
     pub open_only: bool,
+
+    /// Export only ports whose service is in this list (case-insensitive)
+    #[arg(long, value_name = "SERVICES", value_delimiter = ',')]
+    pub service: Vec<String>,
 }
 
 impl ExportCommand {
     /// Execute the export command.
-    pub fn execute(&self, _verbose: bool, quiet: bool) -> CliResult<()> {
+    pub fn execute(&self, _verbose: u8, quiet: bool) -> CliResult<()> {
+        if self.append && self.format == ExportFormat::Json {
+            return Err(CliError::InvalidArgument(
+                "--append cannot extend a pretty-printed JSON document; \
+                 use --format jsonl for one record per line"
+                    .to_string(),
+            ));
+        }
+        if self.format == ExportFormat::Msgpack {
+            if self.append {
+                return Err(CliError::InvalidArgument(
+                    "--append cannot extend a MessagePack document".to_string(),
+                ));
+            }
+            if self.output_file.is_none() && io::stdout().is_terminal() {
+                return Err(CliError::InvalidArgument(
+                    "MessagePack is binary; write it to a file with -o or pipe it".to_string(),
+                ));
+            }
+        }
+
         let store = ScanStore::new()?;
 
-        // Find the scan by ID or prefix
-        let record = if self.scan_id.len() < 36 {
-            // Short ID - find by prefix
-            store.find_by_prefix(&self.scan_id)?
-        } else {
-            // Full ID
-            let id: ScanId = self.scan_id.parse()?;
-            store.load(&id)?
+        // Resolve the ID or prefix, falling back to range scan sessions
+        let record = match store.resolve(&self.scan_id) {
+            Err(StorageError::ScanNotFound(_)) => {
+                let session = store.load_session(&store.resolve_session(&self.scan_id)?)?;
+                return self.export_session(session, quiet);
+            }
+            id => store.load(&id?)?,
         };
 
         // Filter results if requested
         let mut record = record;
-        if self.open_only {
-            record.results.retain(|r| r.is_open());
-        } else if !self.include_closed {
-            record.results.retain(|r| {
-                !matches!(r.status, crate::scanner::PortStatus::Closed)
-            });
-        }
+        self.filter_results(&mut record);
 
         // Generate output
         let content = match self.format {
-            OutputFormat::Json => serde_json::to_string_pretty(&record)
+            ExportFormat::Json => serde_json::to_string_pretty(&record)
+                .map_err(|e| crate::error::CliError::Other(e.to_string()))?,
+            ExportFormat::Jsonl => serde_json::to_string(&record)
+                .map_err(|e| crate::error::CliError::Other(e.to_string()))?,
+            ExportFormat::Csv => generate_csv(&record)?,
+            ExportFormat::Plain => generate_plain(&record),
+            ExportFormat::Msgpack => {
+                let content = StorageFormat::Msgpack.encode(&record)?;
+                return self.write_binary(&content, &record.id.short(), quiet);
+            }
+        };
+
+        self.write_output(&content, &record.id.short(), quiet)
+    }
+
+    /// Export every host of a range scan session.
+    fn export_session(&self, mut session: ScanSession, quiet: bool) -> CliResult<()> {
+        for record in &mut session.records {
+            self.filter_results(record);
+        }
+        // Totals describe the records exported, not any pruned since
+        session.update_totals();
+
+        let content = match self.format {
+            ExportFormat::Json => serde_json::to_string_pretty(&session)
                 .map_err(|e| crate::error::CliError::Other(e.to_string()))?,
-            OutputFormat::Csv => generate_csv(&record)?,
-            OutputFormat::Plain => generate_plain(&record),
+            ExportFormat::Jsonl => session
+                .records
+                .iter()
+                .map(serde_json::to_string)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| crate::error::CliError::Other(e.to_string()))?
+                .join("\n"),
+            ExportFormat::Csv => {
+                let mut csv = Vec::new();
+                output::write_session_csv(&session, output::SortKey::Port, &mut csv)?;
+                String::from_utf8(csv).map_err(|e| crate::error::CliError::Other(e.to_string()))?
+            }
+            ExportFormat::Plain => {
+                let mut report = format!("Session: {}\n{}\n\n", session.id, session.summary());
+                for record in &session.records {
+                    report.push_str(&generate_plain(record));
+                    report.push('\n');
+                }
+                report
+            }
+            ExportFormat::Msgpack => {
+                let content = StorageFormat::Msgpack.encode(&session)?;
+                return self.write_binary(&content, &session.id.short(), quiet);
+            }
         };
 
+        self.write_output(&content, &session.id.short(), quiet)
+    }
+
+    /// Write the export to the output file, or stdout if none was given.
+    fn write_output(&self, content: &str, id: &str, quiet: bool) -> CliResult<()> {
         // Write to file or stdout
         if let Some(ref path) = self.output_file {
-            fs::write(path, &content)
-                .map_err(|e| crate::error::CliError::Other(format!("failed to write file: {}", e)))?;
+            if self.append {
+                append_to(path, content, self.format)?;
+            } else {
+                fs::write(path, content).map_err(|e| {
+                    crate::error::CliError::Other(format!("failed to write file: {}", e))
+                })?;
+            }
 
             if !quiet {
-                output::print_success(&format!(
-                    "Exported scan {} to {}",
-                    record.id.short(),
-                    path.display()
-                ));
+                let verb = if self.append { "Appended" } else { "Exported" };
+                output::print_success(&format!("{} scan {} to {}", verb, id, path.display()));
             }
         } else {
             println!("{}", content);
@@ -88,6 +179,93 @@ impl ExportCommand {
 
         Ok(())
     }
+
+    /// Write a binary export to the output file, or stdout if none was given.
+    fn write_binary(&self, content: &[u8], id: &str, quiet: bool) -> CliResult<()> {
+        let write_error = |e: io::Error| CliError::Other(format!("failed to write file: {}", e));
+
+        match self.output_file {
+            Some(ref path) => {
+                fs::write(path, content).map_err(write_error)?;
+                if !quiet {
+                    output::print_success(&format!("Exported scan {} to {}", id, path.display()));
+                }
+            }
+            None => io::stdout()
+                .lock()
+                .write_all(content)
+                .map_err(write_error)?,
+        }
+        Ok(())
+    }
+
+    /// Drop the results excluded by the status and service filters.
+    fn filter_results(&self, record: &mut ScanRecord) {
+        if self.open_only {
+            record.results.retain(|r| r.is_open());
+        } else if !self.include_closed {
+            record.results.retain(|r| {
+                !matches!(r.status, crate::scanner::PortStatus::Closed)
+            });
+        }
+
+        if !self.service.is_empty() {
+            record.results.retain(|r| {
+                self.service
+                    .iter()
+                    .any(|service| service.trim().eq_ignore_ascii_case(&r.service))
+            });
+        }
+    }
+}
+
+/// Append `content` to the file at `path`, creating it if needed.
+///
+/// CSV content loses its header line when the file already starts with the
+/// same header, and is refused when the file has a different one.
+fn append_to(path: &Path, content: &str, format: ExportFormat) -> CliResult<()> {
+    let write_error = |e: io::Error| CliError::Other(format!("failed to write file: {}", e));
+
+    let mut content = content;
+    if format == ExportFormat::Csv {
+        if let Some(existing) = first_line(path).map_err(write_error)? {
+            let (header, rows) = content.split_once('\n').unwrap_or((content, ""));
+            if existing != header {
+                return Err(CliError::InvalidArgument(format!(
+                    "{} has a different CSV header ({}); cannot append rows of '{}'",
+                    path.display(),
+                    existing,
+                    header
+                )));
+            }
+            content = rows;
+        }
+    }
+
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map_err(write_error)?;
+    file.write_all(content.as_bytes()).map_err(write_error)?;
+    if !content.is_empty() && !content.ends_with('\n') {
+        file.write_all(b"\n").map_err(write_error)?;
+    }
+    Ok(())
+}
+
+/// First line of the file at `path`, or `None` if it is missing or empty.
+fn first_line(path: &Path) -> io::Result<Option<String>> {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e),
+    };
+
+    let mut line = String::new();
+    BufReader::new(file).read_line(&mut line)?;
+    let line = line.trim_end_matches(['\r', '\n']);
+    Ok((!line.is_empty()).then(|| line.to_string()))
 }
 
 /// Generate CSV output.
@@ -123,10 +301,25 @@ fn generate_plain(record: &crate::storage::ScanRecord) -> String {
 
     output.push_str(&format!("Target:       {}\n", record.target));
     output.push_str(&format!("IP Address:   {}\n", record.ip_address));
+    if let Some(ref source_ip) = record.source_ip {
+        match record.interface {
+            Some(ref iface) => {
+                output.push_str(&format!("Source:       {} ({})\n", source_ip, iface))
+            }
+            None => output.push_str(&format!("Source:       {}\n", source_ip)),
+        }
+    }
     output.push_str(&format!("Scan Type:    {}\n", record.scan_type));
+    if !record.port_spec.is_empty() {
+        output.push_str(&format!("Ports:        {}\n", record.port_spec));
+    }
     output.push_str(&format!("Started:      {}\n", record.started_at));
     output.push_str(&format!("Completed:    {}\n", record.completed_at));
-    output.push_str(&format!("Duration:     {} ms\n\n", record.duration_ms));
+    output.push_str(&format!("Duration:     {} ms\n", record.duration_ms));
+    if !record.host_state.is_unknown() {
+        output.push_str(&format!("Host State:   {}\n", record.host_state));
+    }
+    output.push('\n');
 
     output.push_str(&format!(
         "Summary: {} ports scanned, {} open, {} closed, {} filtered\n\n",
@@ -143,7 +336,11 @@ fn generate_plain(record: &crate::storage::ScanRecord) -> String {
         output.push_str(&format!("{}\n", "-".repeat(60)));
 
         for result in &record.results {
-            let banner = result.banner.as_deref().unwrap_or("");
+            let banner = result
+                .banner
+                .as_deref()
+                .unwrap_or("")
+                .replace('\n', " | ");
             let banner_display = if banner.len() > 30 {
                 format!("{}...", &banner[..30])
             } else {
@@ -159,3 +356,98 @@ fn generate_plain(record: &crate::storage::ScanRecord) -> String {
 
     output
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{PortResult, PortStatus, ScanType};
+    use crate::types::Port;
+
+    #[test]
+    fn test_filter_by_service() {
+        let result =
+            |port, status, service| PortResult::new(Port::new(port).unwrap(), status, service);
+        let record = ScanRecord::new("10.0.0.1", "10.0.0.1", ScanType::Connect).finalize(
+            vec![
+                result(22, PortStatus::Open, "ssh"),
+                result(80, PortStatus::Open, "http"),
+                result(443, PortStatus::Open, "https"),
+                result(8080, PortStatus::Closed, "HTTP"),
+                result(8443, PortStatus::Filtered, "https"),
+            ],
+            0,
+        );
+        let ports = |args: &[&str]| {
+            let cmd = ExportCommand::parse_from([&["export", "abc"], args].concat());
+            let mut record = record.clone();
+            cmd.filter_results(&mut record);
+            record
+                .results
+                .iter()
+                .map(|r| r.port.as_u16())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(ports(&["--service", "HTTP,https"]), vec![80, 443, 8443]);
+        assert_eq!(
+            ports(&["--service", "http", "--include-closed"]),
+            vec![80, 8080]
+        );
+        assert_eq!(ports(&["--service", "https", "--open-only"]), vec![443]);
+        assert_eq!(ports(&[]), vec![22, 80, 443, 8443]);
+    }
+
+    #[test]
+    fn test_export_session_totals() {
+        let host = |ip: &str, open: bool| {
+            let status = if open {
+                PortStatus::Open
+            } else {
+                PortStatus::Filtered
+            };
+            ScanRecord::new(ip, ip, ScanType::Connect).finalize(
+                vec![PortResult::new(Port::new(22).unwrap(), status, "ssh")],
+                0,
+            )
+        };
+        let mut session = ScanSession::new("10.0.0.0/30", ScanType::Connect)
+            .finalize(vec![host("10.0.0.1", true), host("10.0.0.2", true)]);
+        // As loaded after one host's record was deleted from history
+        session.records.pop();
+        session.records.push(host("10.0.0.3", false));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let cmd = ExportCommand::parse_from(["export", "abc", "-o", path.to_str().unwrap()]);
+        cmd.export_session(session, true).unwrap();
+
+        let exported: ScanSession =
+            serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(exported.open_ports, 1);
+        assert_eq!(exported.ports_scanned, 2);
+        assert_eq!(exported.hosts_up, 2);
+    }
+
+    #[test]
+    fn test_append_to() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let csv = dir.path().join("findings.csv");
+        append_to(&csv, "port,status\n22,open\n", ExportFormat::Csv).unwrap();
+        append_to(&csv, "port,status\n80,open\n", ExportFormat::Csv).unwrap();
+        assert_eq!(
+            fs::read_to_string(&csv).unwrap(),
+            "port,status\n22,open\n80,open\n"
+        );
+        let err = append_to(&csv, "host,port\n10.0.0.1,22\n", ExportFormat::Csv);
+        assert!(matches!(err, Err(CliError::InvalidArgument(_))));
+
+        let jsonl = dir.path().join("findings.jsonl");
+        append_to(&jsonl, "{\"a\":1}", ExportFormat::Jsonl).unwrap();
+        append_to(&jsonl, "{\"a\":2}", ExportFormat::Jsonl).unwrap();
+        assert_eq!(
+            fs::read_to_string(&jsonl).unwrap(),
+            "{\"a\":1}\n{\"a\":2}\n"
+        );
+    }
+}
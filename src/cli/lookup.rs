@@ -0,0 +1,50 @@
+//! Lookup subcommand implementation.
+//!
+//! Handles the `scuttle lookup` command, which answers questions from the
+//! service database without scanning: the well-known service on a port, or
+//! the ports mapped to a service name.
+
+use crate::config::Paths;
+use crate::error::{CliError, CliResult};
+use crate::services;
+use clap::Parser;
+
+/// Look up well-known services and ports.
+#[derive(Parser, Debug)]
+pub struct LookupCommand {
+    /// Port to look up
+    #[arg(value_name = "PORT", required_unless_present = "service")]
+    pub port: Option<u16>,
+
+    /// List the ports mapped to this service instead (case-insensitive)
+    #[arg(short, long, value_name = "NAME", conflicts_with = "port")]
+    pub service: Option<String>,
+}
+
+impl LookupCommand {
+    /// Execute the lookup command.
+    pub fn execute(&self, _verbose: u8, _quiet: bool) -> CliResult<()> {
+        // Answer with the user's own names too, as a scan would
+        let path = Paths::get().services_file();
+        if path.exists() {
+            services::install_user_services(services::load_service_file(&path)?);
+        }
+
+        if let Some(ref name) = self.service {
+            let ports = services::ports_for_service(name);
+            if ports.is_empty() {
+                return Err(CliError::InvalidArgument(format!(
+                    "no ports are mapped to service '{}'",
+                    name
+                )));
+            }
+            for port in ports {
+                println!("{:<7}{}", port, services::get_service_description(port));
+            }
+        } else if let Some(port) = self.port {
+            println!("{:<7}{}", port, services::get_service_description(port));
+        }
+
+        Ok(())
+    }
+}
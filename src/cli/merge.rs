@@ -0,0 +1,50 @@
+//! Merge subcommand implementation.
+//!
+//! Handles the `scuttle merge <scan-id>...` command, which combines saved
+//! scans of the same host, such as a TCP and a UDP scan, into one record.
+
+use crate::cli::OutputFormat;
+use crate::error::CliResult;
+use crate::output::{self, PlainOptions};
+use crate::storage::ScanStore;
+use clap::Parser;
+
+/// Combine saved scans of one host into a single record.
+#[derive(Parser, Debug)]
+pub struct MergeCommand {
+    /// IDs or prefixes of the scans to merge (two or more)
+    #[arg(value_name = "SCAN_ID", num_args = 2.., required = true)]
+    pub scan_ids: Vec<String>,
+
+    /// Output format for the merged record
+    #[arg(short, long, value_enum, default_value = "plain")]
+    pub output: OutputFormat,
+
+    /// Print the merged record without saving it
+    #[arg(long)]
+    pub no_save: bool,
+}
+
+impl MergeCommand {
+    /// Execute the merge command.
+    pub fn execute(&self, _verbose: u8, quiet: bool) -> CliResult<()> {
+        let store = ScanStore::new()?;
+
+        let mut records = self.scan_ids.iter().map(|id| store.find(id));
+        let mut merged = records.next().expect("clap requires two scan IDs")?;
+        for record in records {
+            merged = merged.merge(record?)?;
+        }
+
+        output::print_results(&merged, self.output, &PlainOptions::default())?;
+
+        if !self.no_save {
+            store.save(&merged)?;
+            if !quiet && self.output == OutputFormat::Plain {
+                output::print_success(&format!("Merged scan saved as {}", merged.id.short()));
+            }
+        }
+
+        Ok(())
+    }
+}
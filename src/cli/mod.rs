@@ -4,15 +4,40 @@
 //! - `scuttle scan <target>` - Scan a target
 //! - `scuttle profiles list|create|delete` - Manage scan profiles
 //! - `scuttle export <scan-id>` - Export scan results
+//! - `scuttle rescan <scan-id>` - Re-run a saved scan
+//! - `scuttle merge <scan-id>...` - Combine saved scans of one host
+//! - `scuttle diff <baseline> <current>` - Compare two saved scans host by host
 //! - `scuttle history` - View scan history
+//! - `scuttle watch` - Re-run a scan on a schedule
+//! - `scuttle lookup <port>` - Look up well-known services
+//! - `scuttle resolve <targets>` - Resolve targets to IPs without scanning
+//! - `scuttle serve` - Run the HTTP API (`serve` feature)
 
+mod diff;
 mod export;
+mod lookup;
+mod merge;
+mod outcome;
 mod profiles;
+mod rescan;
+mod resolve;
 mod scan;
-
-pub use export::ExportCommand;
+#[cfg(feature = "serve")]
+mod serve;
+mod watch;
+
+pub use diff::DiffCommand;
+pub use export::{ExportCommand, ExportFormat};
+pub use lookup::LookupCommand;
+pub use merge::MergeCommand;
+pub use outcome::Outcome;
 pub use profiles::ProfilesCommand;
+pub use rescan::RescanCommand;
+pub use resolve::ResolveCommand;
 pub use scan::ScanCommand;
+#[cfg(feature = "serve")]
+pub use serve::ServeCommand;
+pub use watch::WatchCommand;
 
 use clap::{Parser, Subcommand};
 use std::path::PathBuf;
@@ -33,14 +58,18 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Option<Commands>,
 
-    /// Enable verbose output
-    #[arg(short, long, global = true)]
-    pub verbose: bool,
+    /// Increase verbosity (-v progress and probe errors, -vv open ports as found, -vvv every probe)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    pub verbose: u8,
 
     /// Suppress non-essential output
     #[arg(short, long, global = true)]
     pub quiet: bool,
 
+    /// Log every probe (connects, refusals, timeouts, banners) to stderr
+    #[arg(long, global = true)]
+    pub trace: bool,
+
     /// Path to custom configuration file
     #[arg(long, global = true, value_name = "PATH")]
     pub config: Option<PathBuf>,
@@ -60,7 +89,7 @@ pub struct Cli {
 pub enum Commands {
     /// Scan a target for open ports
     #[command(alias = "s")]
-    Scan(ScanCommand),
+    Scan(Box<ScanCommand>),
 
     /// Manage scan profiles
     #[command(alias = "p")]
@@ -70,9 +99,35 @@ pub enum Commands {
     #[command(alias = "e")]
     Export(ExportCommand),
 
+    /// Re-run a saved scan against the same host and ports
+    #[command(alias = "r")]
+    Rescan(RescanCommand),
+
+    /// Combine saved scans of the same host, such as TCP and UDP, into one
+    Merge(MergeCommand),
+
+    /// Compare two saved scans or range scans, host by host
+    #[command(alias = "d")]
+    Diff(DiffCommand),
+
     /// View scan history
     #[command(alias = "h")]
     History(HistoryCommand),
+
+    /// Re-run a scan at a fixed interval
+    #[command(alias = "w")]
+    Watch(WatchCommand),
+
+    /// Look up the well-known service for a port, or the ports for a service
+    #[command(alias = "l")]
+    Lookup(LookupCommand),
+
+    /// Resolve targets to the IP addresses a scan would probe, then exit
+    Resolve(ResolveCommand),
+
+    /// Start and fetch scans over an HTTP API
+    #[cfg(feature = "serve")]
+    Serve(ServeCommand),
 }
 
 /// View and manage scan history.
@@ -93,22 +148,49 @@ pub struct HistoryCommand {
     /// Delete scans older than N days
     #[arg(long, value_name = "DAYS")]
     pub prune: Option<u32>,
+
+    #[command(subcommand)]
+    pub action: Option<HistoryAction>,
+}
+
+/// Maintenance actions on the scan history.
+#[derive(Subcommand, Debug)]
+pub enum HistoryAction {
+    /// Report and remove saved scans that can no longer be read
+    Repair {
+        /// Only report the unreadable files, keep them on disk
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Show how much history is stored and what it found
+    Stats {
+        /// Output format (plain, json, json-compact, or jsonl)
+        #[arg(short, long, value_enum, default_value = "plain")]
+        output: OutputFormat,
+    },
 }
 
 /// Output format for results.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
 pub enum OutputFormat {
     /// Human-readable plain text
+    #[default]
     Plain,
     /// JSON structured output
     Json,
+    /// JSON on a single line, without whitespace, for machine pipelines
+    JsonCompact,
+    /// JSON Lines, one compact JSON object per record
+    Jsonl,
     /// CSV format for data analysis
     Csv,
 }
 
-impl Default for OutputFormat {
-    fn default() -> Self {
-        Self::Plain
+impl OutputFormat {
+    /// Check if this is one of the JSON formats.
+    pub fn is_json(self) -> bool {
+        matches!(self, Self::Json | Self::JsonCompact | Self::Jsonl)
     }
 }
 
@@ -117,6 +199,8 @@ impl std::fmt::Display for OutputFormat {
         match self {
             Self::Plain => write!(f, "plain"),
             Self::Json => write!(f, "json"),
+            Self::JsonCompact => write!(f, "json-compact"),
+            Self::Jsonl => write!(f, "jsonl"),
             Self::Csv => write!(f, "csv"),
         }
     }
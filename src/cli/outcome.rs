@@ -0,0 +1,185 @@
+//! Process exit codes.
+//!
+//! Scripts can branch on what a scan found without parsing its output:
+//!
+//! | Code | Meaning |
+//! |------|---------|
+//! | 0 | Success; a scan found at least one open port |
+//! | 1 | Error not covered below |
+//! | 2 | Invalid arguments or options |
+//! | 3 | Scan completed without finding an open port |
+//! | 4 | Target could not be resolved, or every host was down |
+//! | 5 | Raw socket privileges required |
+//! | 6 | Scan completed but may have missed ports, e.g. a connect-scan fallback or sampled ports |
+//!
+//! Warnings that lose nothing, such as an ignored option or a clamped
+//! concurrency, leave the code at 0 or 3.
+
+use crate::error::{CliError, ScanError};
+use crate::storage::ScanSession;
+use crate::types::TargetError;
+use std::process::ExitCode;
+
+/// How a command ended, as reported in the exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// Success; for a scan, at least one open port was found.
+    Success = 0,
+    /// An error without a more specific code.
+    Failure = 1,
+    /// Invalid arguments or options (as for argument parsing errors).
+    Usage = 2,
+    /// The scan completed without finding an open port.
+    NoOpenPorts = 3,
+    /// The target could not be resolved, or every host was down.
+    Unreachable = 4,
+    /// The scan needs raw socket privileges that are missing.
+    Privileges = 5,
+    /// The scan completed, but may have missed ports: it fell back to a
+    /// connect scan, ran without privileges under `--force`, sampled ports,
+    /// was cancelled, or lost many probes.
+    Partial = 6,
+}
+
+impl Outcome {
+    /// Outcome of a finished scan.
+    pub fn of_session(session: &ScanSession) -> Self {
+        let records = &session.records;
+        if !records.is_empty() && records.iter().all(|r| r.is_down()) {
+            Self::Unreachable
+        } else if records
+            .iter()
+            .any(|r| r.partial || r.cancelled || r.sampled.is_some())
+        {
+            Self::Partial
+        } else if session.open_ports > 0 {
+            Self::Success
+        } else {
+            Self::NoOpenPorts
+        }
+    }
+}
+
+impl From<&CliError> for Outcome {
+    fn from(error: &CliError) -> Self {
+        match error {
+            CliError::InvalidArgument(_) | CliError::Port(_) | CliError::ScanId(_) => Self::Usage,
+            CliError::Target(e) => match e {
+                TargetError::DnsResolutionFailed(..)
+                | TargetError::NoAddressesFound(_)
+                | TargetError::DnsDisabled(_) => Self::Unreachable,
+                _ => Self::Usage,
+            },
+            CliError::Unreachable(_) => Self::Unreachable,
+            CliError::Privileges(_) | CliError::Scan(ScanError::PermissionDenied(_)) => {
+                Self::Privileges
+            }
+            _ => Self::Failure,
+        }
+    }
+}
+
+impl From<Outcome> for ExitCode {
+    fn from(outcome: Outcome) -> Self {
+        ExitCode::from(outcome as u8)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{PortResult, PortStatus, ScanType};
+    use crate::storage::ScanRecord;
+    use crate::types::Port;
+
+    fn session(hosts: Vec<ScanRecord>) -> ScanSession {
+        ScanSession::new("10.0.0.0/30", ScanType::Connect).finalize(hosts)
+    }
+
+    fn host(open: bool) -> ScanRecord {
+        let results = if open {
+            vec![PortResult::new(
+                Port::new(22).unwrap(),
+                PortStatus::Open,
+                "ssh",
+            )]
+        } else {
+            Vec::new()
+        };
+        ScanRecord::new("10.0.0.1", "10.0.0.1", ScanType::Connect).finalize(results, 0)
+    }
+
+    #[test]
+    fn test_scan_outcome() {
+        let down = || ScanRecord::host_down("10.0.0.2", "10.0.0.2", ScanType::Connect);
+
+        assert_eq!(
+            Outcome::of_session(&session(vec![host(true), down()])),
+            Outcome::Success
+        );
+        assert_eq!(
+            Outcome::of_session(&session(vec![host(false)])),
+            Outcome::NoOpenPorts
+        );
+        assert_eq!(
+            Outcome::of_session(&session(vec![down(), down()])),
+            Outcome::Unreachable
+        );
+
+        let mut degraded = host(true);
+        degraded
+            .warnings
+            .push("falling back to a TCP connect scan.".to_string());
+        degraded.partial = true;
+        assert_eq!(
+            Outcome::of_session(&session(vec![degraded])),
+            Outcome::Partial
+        );
+
+        let mut cancelled = host(false);
+        cancelled.cancelled = true;
+        assert_eq!(
+            Outcome::of_session(&session(vec![cancelled])),
+            Outcome::Partial
+        );
+    }
+
+    #[test]
+    fn test_informational_warnings_keep_outcome() {
+        let mut open = host(true);
+        open.warnings
+            .push("concurrency 5000 was clamped to 1000".to_string());
+        assert_eq!(Outcome::of_session(&session(vec![open])), Outcome::Success);
+
+        let mut closed = host(false);
+        closed
+            .warnings
+            .push("--traceroute requires root/sudo privileges and will be skipped.".to_string());
+        assert_eq!(
+            Outcome::of_session(&session(vec![closed])),
+            Outcome::NoOpenPorts
+        );
+    }
+
+    #[test]
+    fn test_error_outcome() {
+        let outcome = |e: CliError| Outcome::from(&e);
+        assert_eq!(
+            outcome(CliError::InvalidArgument("x".into())),
+            Outcome::Usage
+        );
+        assert_eq!(
+            outcome(TargetError::InvalidCidr("10.0.0.0/33".into()).into()),
+            Outcome::Usage
+        );
+        assert_eq!(
+            outcome(TargetError::NoAddressesFound("example.invalid".into()).into()),
+            Outcome::Unreachable
+        );
+        assert_eq!(
+            outcome(ScanError::PermissionDenied("raw socket".into()).into()),
+            Outcome::Privileges
+        );
+        assert_eq!(outcome(CliError::Other("x".into())), Outcome::Failure);
+    }
+}
@@ -73,7 +73,7 @@ pub enum ProfilesAction {
 
 impl ProfilesCommand {
     /// Execute the profiles command.
-    pub fn execute(&self, _verbose: bool, quiet: bool) -> CliResult<()> {
+    pub fn execute(&self, _verbose: u8, quiet: bool) -> CliResult<()> {
         match &self.action {
             ProfilesAction::List => self.list_profiles(quiet),
             ProfilesAction::Show { name } => self.show_profile(name, quiet),
@@ -113,7 +113,7 @@ impl ProfilesCommand {
         }
 
         if !quiet {
-            println!("\n{:<15} {:<12} {:<20} {}", "NAME", "SCAN TYPE", "PORTS", "DESCRIPTION");
+            println!("\n{:<15} {:<12} {:<20} DESCRIPTION", "NAME", "SCAN TYPE", "PORTS");
             println!("{}", "-".repeat(70));
         }
 
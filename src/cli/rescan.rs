@@ -0,0 +1,191 @@
+//! Rescan subcommand implementation.
+//!
+//! Handles the `scuttle rescan <scan-id>` command, which re-runs a saved scan
+//! against the same resolved IP with the same scan type and ports. Timeout,
+//! concurrency, rate, and banner grabbing come from the settings saved with
+//! the scan, when it has them; options given on the command line win.
+
+use crate::cli::{Outcome, OutputFormat, ScanCommand};
+use crate::error::{CliError, CliResult};
+use crate::output;
+use crate::scanner::ScanType;
+use crate::storage::{ScanRecord, ScanStore};
+use clap::Parser;
+
+/// Re-run a previous scan.
+#[derive(Parser, Debug)]
+pub struct RescanCommand {
+    /// Scan ID or prefix of the scan to repeat
+    #[arg(value_name = "SCAN_ID")]
+    pub scan_id: String,
+
+    /// Override the ports to scan (required for records saved without a port spec)
+    #[arg(short, long)]
+    pub ports: Option<String>,
+
+    /// Maximum number of concurrent scanning tasks [default: saved, else per scan type]
+    #[arg(
+        short = 'c',
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub concurrency: Option<usize>,
+
+    /// Maximum probes per second, 0 for unlimited [default: saved, else 0]
+    #[arg(short = 'r', long = "rate", value_name = "PPS")]
+    pub rate_limit: Option<u32>,
+
+    /// Output format for results
+    #[arg(short, long, value_enum, default_value = "plain")]
+    pub output: OutputFormat,
+
+    /// Connection timeout in milliseconds [default: saved, else 3000]
+    #[arg(short = 't', long, value_name = "MS")]
+    pub timeout: Option<u64>,
+
+    /// Enable banner grabbing (connect scans only) [default: saved]
+    #[arg(short = 'b', long)]
+    pub banner: bool,
+
+    /// Don't save scan results
+    #[arg(long)]
+    pub no_save: bool,
+}
+
+impl RescanCommand {
+    /// Execute the rescan command.
+    pub async fn execute(&self, verbose: u8, quiet: bool) -> CliResult<Outcome> {
+        let store = ScanStore::new()?;
+
+        let record = store.find(&self.scan_id)?;
+        let scan_cmd = self.scan_command(&record)?;
+
+        if !quiet && self.output == OutputFormat::Plain {
+            output::print_info(&format!(
+                "Rescanning {} ({}, {} scan, ports {})",
+                record.id.short(),
+                record.ip_address,
+                record.scan_type,
+                scan_cmd.ports.as_deref().unwrap_or_default()
+            ));
+        }
+
+        scan_cmd.execute(verbose, quiet).await
+    }
+
+    /// The scan that repeats `record`, with this command's options applied.
+    fn scan_command(&self, record: &ScanRecord) -> CliResult<ScanCommand> {
+        let scan_type: ScanType = record.scan_type.parse().map_err(CliError::Other)?;
+        let config = record.config.as_ref();
+
+        let ports = match (&self.ports, record.port_spec.is_empty()) {
+            (Some(ports), _) => ports.clone(),
+            (None, false) => record.port_spec.clone(),
+            (None, true) => {
+                return Err(CliError::Other(format!(
+                    "scan {} does not record which ports were scanned; pass --ports",
+                    record.id.short()
+                )))
+            }
+        };
+
+        let defaults = ScanCommand::with_defaults(&record.ip_address);
+
+        // Reuse the resolved IP so the rescan hits the same host even if DNS changed
+        Ok(ScanCommand {
+            ports: Some(ports),
+            scan_type: Some(scan_type),
+            concurrency: self.concurrency.or(config.map(|c| c.concurrency)),
+            output: self.output,
+            timeout: self
+                .timeout
+                .or(config.map(|c| c.timeout_ms))
+                .unwrap_or(defaults.timeout),
+            rate_limit: self
+                .rate_limit
+                .or(config.map(|c| c.rate_limit))
+                .unwrap_or(defaults.rate_limit),
+            min_rate: config.map_or(defaults.min_rate, |c| c.min_rate),
+            banner: self.banner || config.is_some_and(|c| c.banner_grab),
+            syn_retries: config
+                .and_then(|c| c.syn_retries)
+                .unwrap_or(defaults.syn_retries),
+            no_save: self.no_save,
+            skip_host_discovery: true,
+            ..defaults
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::ScanSettings;
+
+    fn saved_record() -> ScanRecord {
+        let mut record = ScanRecord::new("example.com", "192.0.2.7", ScanType::Connect);
+        record.port_spec = "22,80,443".to_string();
+        record.config = Some(ScanSettings {
+            scan_type: record.scan_type.clone(),
+            port_spec: record.port_spec.clone(),
+            timeout_ms: 750,
+            concurrency: 64,
+            rate_limit: 200,
+            min_rate: 50,
+            banner_grab: true,
+            syn_retries: None,
+        });
+        record
+    }
+
+    #[test]
+    fn test_scan_defaults_match_cli() {
+        // with_defaults is built by hand; it must agree with clap's defaults
+        assert_eq!(
+            format!("{:?}", ScanCommand::with_defaults("192.0.2.7")),
+            format!("{:?}", ScanCommand::parse_from(["scan", "192.0.2.7"]))
+        );
+    }
+
+    #[test]
+    fn test_rescan_uses_saved_config() {
+        let rescan = RescanCommand::try_parse_from(["rescan", "abc"]).unwrap();
+        let scan = rescan.scan_command(&saved_record()).unwrap();
+
+        assert_eq!(scan.target.as_deref(), Some("192.0.2.7"));
+        assert_eq!(scan.ports.as_deref(), Some("22,80,443"));
+        assert_eq!(scan.timeout, 750);
+        assert_eq!(scan.concurrency, Some(64));
+        assert_eq!(scan.rate_limit, 200);
+        assert_eq!(scan.min_rate, 50);
+        assert!(scan.banner);
+    }
+
+    #[test]
+    fn test_rescan_flags_override_saved_config() {
+        let rescan = RescanCommand::try_parse_from([
+            "rescan", "abc", "-t", "2000", "-c", "8", "--rate", "0", "-p", "80",
+        ])
+        .unwrap();
+        let scan = rescan.scan_command(&saved_record()).unwrap();
+
+        assert_eq!(scan.ports.as_deref(), Some("80"));
+        assert_eq!(scan.timeout, 2000);
+        assert_eq!(scan.concurrency, Some(8));
+        assert_eq!(scan.rate_limit, 0);
+    }
+
+    #[test]
+    fn test_rescan_without_saved_config() {
+        let mut record = saved_record();
+        record.config = None;
+        let rescan = RescanCommand::try_parse_from(["rescan", "abc"]).unwrap();
+        let scan = rescan.scan_command(&record).unwrap();
+
+        assert_eq!(scan.timeout, 3000);
+        assert_eq!(scan.concurrency, None);
+        assert_eq!(scan.rate_limit, 0);
+        assert!(!scan.banner);
+    }
+}
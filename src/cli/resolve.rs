@@ -0,0 +1,151 @@
+//! Resolve subcommand implementation.
+//!
+//! Handles the `scuttle resolve` command, which expands targets (hostnames,
+//! IPs, CIDR ranges) to the addresses a scan would probe, without scanning
+//! or saving anything. Useful for building target lists for other tools.
+
+use super::scan::resolve_options;
+use crate::cli::OutputFormat;
+use crate::error::{CliError, CliResult};
+use crate::output;
+use crate::types::{ResolveOptions, ScanTarget, TargetSpec};
+use clap::Parser;
+use std::io;
+use std::net::IpAddr;
+
+/// Resolve targets to IP addresses without scanning.
+#[derive(Parser, Debug)]
+pub struct ResolveCommand {
+    /// Targets to resolve (IP, hostname, or CIDR notation)
+    #[arg(value_name = "TARGET", required = true)]
+    pub targets: Vec<String>,
+
+    /// Output format
+    #[arg(short, long, value_enum, default_value = "plain")]
+    pub output: OutputFormat,
+
+    /// Keep every address a hostname resolves to, not only the first
+    #[arg(long)]
+    pub all_ips: bool,
+
+    /// Name server to query instead of the system resolvers
+    #[arg(long, value_name = "IP", conflicts_with = "no_dns")]
+    pub dns_server: Option<IpAddr>,
+
+    /// Never send DNS queries; hostname targets are refused
+    #[arg(long)]
+    pub no_dns: bool,
+
+    /// Retries for transient DNS failures (timeouts, SERVFAIL)
+    #[arg(long, default_value_t = TargetSpec::DEFAULT_DNS_RETRIES, value_name = "N")]
+    pub dns_retries: u32,
+}
+
+impl ResolveCommand {
+    /// Execute the resolve command.
+    pub async fn execute(&self, _verbose: u8, _quiet: bool) -> CliResult<()> {
+        let options = self.resolve_options();
+
+        let mut targets = Vec::new();
+        for target in &self.targets {
+            targets.extend(TargetSpec::parse(target)?.resolve_with(&options).await?);
+        }
+
+        match self.output {
+            OutputFormat::Plain => {
+                for target in &targets {
+                    println!("{:<39}  {}", target.ip, target.original);
+                }
+            }
+            OutputFormat::Json => output::write_json(&targets, false)?,
+            OutputFormat::JsonCompact => output::write_json(&targets, true)?,
+            OutputFormat::Jsonl => {
+                for target in &targets {
+                    let json = serde_json::to_string(target)
+                        .map_err(|e| CliError::Other(e.to_string()))?;
+                    println!("{}", json);
+                }
+            }
+            OutputFormat::Csv => write_csv(&targets, io::stdout().lock())?,
+        }
+
+        Ok(())
+    }
+
+    /// Hostname resolution settings from the DNS flags.
+    fn resolve_options(&self) -> ResolveOptions {
+        resolve_options(self.dns_retries, self.no_dns, self.all_ips, self.dns_server)
+    }
+}
+
+/// Write resolved targets as CSV with an `original,ip` header.
+fn write_csv(targets: &[ScanTarget], writer: impl io::Write) -> io::Result<()> {
+    let mut wtr = csv::Writer::from_writer(writer);
+    wtr.write_record(["original", "ip"])?;
+    for target in targets {
+        wtr.write_record([target.original.as_str(), &target.ip.to_string()])?;
+    }
+    wtr.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_options_from_flags() {
+        let resolve = ResolveCommand::try_parse_from(["resolve", "example.com"]).unwrap();
+        let options = resolve.resolve_options();
+        assert!(options.allow_dns);
+        assert!(!options.all_ips);
+        assert_eq!(options.dns_server, None);
+        assert_eq!(options.dns_retries, TargetSpec::DEFAULT_DNS_RETRIES);
+
+        let resolve = ResolveCommand::try_parse_from([
+            "resolve",
+            "example.com",
+            "--all-ips",
+            "--dns-server",
+            "192.0.2.53",
+            "--dns-retries",
+            "0",
+        ])
+        .unwrap();
+        let options = resolve.resolve_options();
+        assert!(options.all_ips);
+        assert_eq!(options.dns_server, Some("192.0.2.53".parse().unwrap()));
+        assert_eq!(options.dns_retries, 0);
+
+        let resolve = ResolveCommand::try_parse_from(["resolve", "example.com", "--no-dns"]);
+        assert!(!resolve.unwrap().resolve_options().allow_dns);
+    }
+
+    #[test]
+    fn test_resolve_rejects_bad_flags() {
+        assert!(ResolveCommand::try_parse_from(["resolve"]).is_err());
+        assert!(ResolveCommand::try_parse_from(["resolve", "x", "--dns-server", "ns1"]).is_err());
+        assert!(ResolveCommand::try_parse_from([
+            "resolve",
+            "x",
+            "--no-dns",
+            "--dns-server",
+            "192.0.2.53"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let targets = vec![
+            ScanTarget::new("localhost", "127.0.0.1".parse().unwrap()),
+            ScanTarget::new("::1", "::1".parse().unwrap()),
+        ];
+        let mut out = Vec::new();
+        write_csv(&targets, &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "original,ip\nlocalhost,127.0.0.1\n::1,::1\n"
+        );
+    }
+}
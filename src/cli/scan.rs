@@ -2,17 +2,39 @@
 //!
 //! Handles the `scuttle scan <target>` command for port scanning.
 
-use crate::cli::OutputFormat;
-use crate::config::ProfileManager;
-use crate::error::CliResult;
-use crate::output;
+use crate::banner::interact::Script;
+use crate::banner::{DEFAULT_BANNER_SIZE, MAX_BANNER_SIZE};
+use crate::cli::{Outcome, OutputFormat};
+use crate::config::{AppSettings, Paths, ProfileManager};
+use crate::enrich::Enricher;
+use crate::error::{CliResult, ScanError};
+use crate::oui::OuiTable;
+use crate::output::{self, CsvStream, PlainOptions, SortKey, Template};
+use crate::scanner::adaptive::{DEFAULT_BACKOFF_THRESHOLD, DEFAULT_RECOVERY_THRESHOLD};
 use crate::scanner::{
-    create_scanner, run_scan, ScanConfig, ScanJobConfig, ScanType,
+    create_scanner, has_raw_socket_access, run_scan, sample_size, syn::DEFAULT_SYN_RETRIES,
+    DiscoveryMode, HostDiscovery, HostState, IcmpProbe, KeyedRateLimiter, OsProfile, PortResult,
+    RateLimiter, ScanConfig, ScanJobConfig, ScanProgress, ScanType, ScannerInfo, SynScanner,
+    Traceroute,
+};
+#[cfg(unix)]
+use crate::scanner::UnixSocketScanner;
+use crate::services;
+use crate::storage::{ScanDiff, ScanRecord, ScanSession, ScanStore};
+use crate::types::{
+    shuffle_targets, Port, PortRange, PortSpec, ResolveOptions, ScanId, ScanTarget, TargetSpec,
 };
-use crate::storage::ScanStore;
-use crate::types::{Port, PortSpec, ScanTarget, TargetSpec};
 use clap::Parser;
+use futures::stream::{self, StreamExt};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Ports scanned when neither `--ports` nor a profile or `--like` scan
+/// names any.
+const DEFAULT_PORTS: &str = "1-1000";
 
 /// Scan a target for open ports.
 #[derive(Parser, Debug)]
@@ -23,45 +45,235 @@ pub struct ScanCommand {
     ///   192.168.1.1        Single IP address
     ///   example.com        Hostname
     ///   192.168.1.0/24     CIDR range
-    #[arg(value_name = "TARGET")]
-    pub target: String,
+    #[arg(
+        value_name = "TARGET",
+        required_unless_present_any = ["list_scan_types", "unix_sockets", "like"]
+    )]
+    pub target: Option<String>,
 
     /// Ports to scan (e.g., "80", "80,443", "1-1000", "22,80,443,8000-9000")
-    #[arg(short, long, default_value = "1-1000")]
-    pub ports: String,
+    /// [default: 1-1000]
+    #[arg(short, long)]
+    pub ports: Option<String>,
 
-    /// Scan type to use
-    #[arg(short = 's', long = "scan-type", value_enum, default_value = "connect")]
-    pub scan_type: ScanType,
+    /// Scan only the ports a saved scan found open (ID or prefix)
+    #[arg(long, value_name = "SCAN_ID", conflicts_with = "ports")]
+    pub ports_from_scan: Option<String>,
+
+    /// Scan type to use [default: connect]
+    #[arg(short = 's', long = "scan-type", value_enum)]
+    pub scan_type: Option<ScanType>,
+
+    /// Default the target, ports, and scan type to a saved scan's (ID or prefix)
+    ///
+    /// Any of them given on the command line wins, and other options are
+    /// set as usual, e.g. `--like 3f2a9c1e -b` repeats a scan with banners.
+    #[arg(long, value_name = "SCAN_ID", conflicts_with = "profile")]
+    pub like: Option<String>,
 
     /// Maximum number of concurrent scanning tasks
-    #[arg(short = 'c', long, default_value = "500")]
-    pub concurrency: usize,
+    /// [default: 500 for connect, 200 for SYN, 20 for UDP]
+    #[arg(
+        short = 'c',
+        long,
+        value_name = "N",
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub concurrency: Option<usize>,
+
+    /// Hand the scanner N ports at a time instead of one
+    ///
+    /// Each batch is dispatched and probed together, trimming per-port
+    /// overhead on large scans of fast hosts. Batches are capped at the
+    /// fewest probes the scan may back off to.
+    #[arg(long, default_value = "1", value_name = "N")]
+    pub batch_size: usize,
 
     /// Output format for results
     #[arg(short, long, value_enum, default_value = "plain")]
     pub output: OutputFormat,
 
+    /// Print one line per port from a template instead of formatted results
+    ///
+    /// Fields: {ip}, {addr} (ip:port, bracketed for IPv6), {target},
+    /// {hostname}, {port}, {protocol}, {status}, {service}, {banner}, {rtt},
+    /// {scan_id}, {scan_type}. Write {{ and }} for literal braces, and \n or
+    /// \t for a newline or tab. Other output is suppressed as with --quiet.
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        conflicts_with_all = ["output", "unix_sockets"]
+    )]
+    pub template: Option<Template>,
+
     /// Connection timeout in milliseconds
     #[arg(short = 't', long, default_value = "3000")]
     pub timeout: u64,
 
-    /// Enable banner grabbing (TCP only)
+    /// Randomize each connect's timeout by up to MS either way (0 = off)
+    ///
+    /// Keeps probes started together from all timing out at once on large
+    /// scans. Connect scans only.
+    #[arg(long, default_value = "0", value_name = "MS")]
+    pub timeout_jitter: u64,
+
+    /// Close connections with a RST so they skip TIME_WAIT (connect scans only)
+    ///
+    /// Frees local ports at once, so back-to-back large scans do not fail
+    /// with "cannot assign requested address". Targets see an abortive close
+    /// instead of a normal one, which is more conspicuous.
+    #[arg(long)]
+    pub fast_close: bool,
+
+    /// Bind connections to source ports from START-END, used in turn
+    /// (connect scans only)
+    ///
+    /// Works around a small ephemeral port range on large scans, and tests
+    /// firewall rules that match on source port. By default the OS picks.
+    #[arg(long, value_name = "START-END")]
+    pub source_port_range: Option<PortRange>,
+
+    /// Flag refused ports that answered within MS milliseconds as fast
+    /// resets (connect scans only)
+    ///
+    /// Load balancers that shed ports often reset them far quicker than a
+    /// host with nothing listening. Set MS just under the target's usual
+    /// round trip time.
+    #[arg(long, value_name = "MS")]
+    pub fast_reset_threshold: Option<u64>,
+
+    /// Retry refused ports up to N times before calling them closed
+    /// (connect scans only)
+    ///
+    /// Catches stateful firewalls that reset the first connection and let
+    /// the next one through. Only refusals faster than
+    /// --fast-reset-threshold, or than 10ms without it, are retried, and
+    /// only a retry that connects changes the result. Closed ports take
+    /// longer to settle.
+    #[arg(long, default_value = "0", value_name = "N")]
+    pub max_retries_on_rst: u32,
+
+    /// Record which of h2 and http/1.1 TLS services on open ports select
+    /// (connect scans only)
+    ///
+    /// Opens a second connection to each open port for a TLS handshake,
+    /// telling HTTP/2 (and gRPC) servers from plain HTTPS. Certificates are
+    /// not checked. Needs scuttle built with the `tls` feature.
+    #[arg(long)]
+    pub alpn: bool,
+
+    /// Enable banner grabbing (connect scans only)
     #[arg(short = 'b', long)]
     pub banner: bool,
 
+    /// Banner read timeout in milliseconds (defaults to --timeout)
+    #[arg(long, value_name = "MS")]
+    pub banner_timeout: Option<u64>,
+
+    /// Maximum bytes to capture per banner
+    #[arg(long, default_value_t = DEFAULT_BANNER_SIZE, value_name = "BYTES")]
+    pub banner_size: usize,
+
+    /// Run a send/expect script on the open port and record the transcript
+    /// as its banner (one host and one port, connect scans only)
+    #[arg(long, value_name = "SCRIPT", conflicts_with = "unix_sockets")]
+    pub interact: Option<PathBuf>,
+
+    /// Service name file (JSON or nmap-services) merged over the built-in map
+    #[arg(long, value_name = "PATH")]
+    pub services_file: Option<PathBuf>,
+
     /// Show closed ports in output
     #[arg(long)]
     pub show_closed: bool,
 
-    /// Network interface to use (for SYN scan)
+    /// Show at most N ports in plain output, open ports first
+    #[arg(long, value_name = "N")]
+    pub limit: Option<usize>,
+
+    /// Order of port rows in plain and CSV output
+    #[arg(long, value_enum, default_value_t = SortKey::Port, value_name = "KEY")]
+    pub sort: SortKey,
+
+    /// Write open ports as CSV rows while scanning, to a file or "-" for stdout
+    #[arg(long, value_name = "PATH")]
+    pub stream_csv: Option<PathBuf>,
+
+    /// After the scan, write open ports one per line (ip:port for several
+    /// hosts) to a file or "-" for stdout
+    #[arg(long, value_name = "PATH")]
+    pub open_ports_file: Option<PathBuf>,
+
+    /// List each open port once in --open-ports-file, whichever hosts had it
+    #[arg(long, requires = "open_ports_file")]
+    pub unique_ports: bool,
+
+    /// Network interface to send probes from (connect and SYN scans)
     #[arg(short = 'i', long)]
     pub interface: Option<String>,
 
+    /// Run despite safety checks (missing privileges, --max-probes cap)
+    #[arg(long)]
+    pub force: bool,
+
+    /// Refuse to scan if hosts x ports exceeds N (overrides max_probes in settings)
+    #[arg(long, value_name = "N")]
+    pub max_probes: Option<u64>,
+
+    /// Probe a random PERCENT of each host's ports instead of all of them
+    ///
+    /// Records note the sampled percentage so partial coverage is not
+    /// mistaken for a complete scan.
+    #[arg(long, value_name = "PERCENT")]
+    pub sample: Option<f64>,
+
+    /// Fall back to a TCP connect scan if a SYN scan lacks raw socket access
+    #[arg(long)]
+    pub fallback_connect: bool,
+
+    /// Split SYN probes into tiny IP fragments (IDS reassembly testing)
+    #[arg(short = 'f', long)]
+    pub fragment: bool,
+
+    /// Times to resend an unanswered SYN before calling a port filtered
+    #[arg(long, default_value_t = DEFAULT_SYN_RETRIES, value_name = "N")]
+    pub syn_retries: u32,
+
+    /// Operating system whose TCP SYN options probes imitate
+    #[arg(long, value_enum, default_value_t = OsProfile::default(), value_name = "OS")]
+    pub os_profile: OsProfile,
+
     /// Rate limit in packets per second (0 = unlimited)
+    ///
+    /// Caps all hosts together when several are scanned at once.
     #[arg(short = 'r', long = "rate", default_value = "0")]
     pub rate_limit: u32,
 
+    /// Rate limit for each host in packets per second (0 = unlimited)
+    ///
+    /// Keeps any one host from being flooded when several are scanned at
+    /// once, under the overall --rate.
+    #[arg(long, default_value = "0", value_name = "PPS")]
+    pub per_host_rate: u32,
+
+    /// Minimum sustained rate in probes per second (0 = disabled)
+    ///
+    /// Concurrency is raised above --concurrency while throughput lags this
+    /// floor, up to the open-file limit.
+    #[arg(long = "min-rate", default_value = "0", value_name = "PPS")]
+    pub min_rate: u32,
+
+    /// Timeout ratio that makes a host's scan back off (0 = never back off)
+    ///
+    /// When more than this share of recent probes time out on a responsive
+    /// host, its concurrency is halved until the ratio recovers.
+    #[arg(long, default_value_t = DEFAULT_BACKOFF_THRESHOLD, value_name = "RATIO")]
+    pub backoff_threshold: f64,
+
+    /// Timeout ratio under which a backed-off scan ramps back up
+    #[arg(long, default_value_t = DEFAULT_RECOVERY_THRESHOLD, value_name = "RATIO")]
+    pub recovery_threshold: f64,
+
     /// Use a saved scan profile
     #[arg(long = "profile", short = 'P')]
     pub profile: Option<String>,
@@ -69,11 +281,209 @@ pub struct ScanCommand {
     /// Don't save scan results
     #[arg(long)]
     pub no_save: bool,
+
+    /// Derive scan IDs from the target, ports, scan type, and SEED
+    ///
+    /// The same inputs always give the same ID, so scripts can know it up
+    /// front; re-running with the same seed replaces the saved scan. Hosts of
+    /// a range get IDs from their IP address.
+    #[arg(long, value_name = "SEED")]
+    pub deterministic_id: Option<String>,
+
+    /// Host discovery probe to run before port scanning
+    ///
+    /// Discovery runs automatically with a TCP SYN ping for multi-host
+    /// targets such as CIDR ranges; setting this enables it for single hosts.
+    #[arg(long = "ping", value_enum, value_name = "MODE")]
+    pub ping: Option<DiscoveryMode>,
+
+    /// ICMP requests sent by host discovery, from echo, timestamp, and mask
+    /// (implies --ping icmp)
+    ///
+    /// A host answering any of them is up. Some hosts that filter echo
+    /// still answer timestamp or address mask requests. Those need
+    /// root/sudo; without it, a TCP SYN ping stands in for them.
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "PROBES")]
+    pub discovery_probes: Vec<IcmpProbe>,
+
+    /// Skip host discovery and port-scan every target
+    #[arg(long, conflicts_with_all = ["ping", "discovery_probes"])]
+    pub skip_host_discovery: bool,
+
+    /// Trace the route to each host before scanning it (requires root/sudo)
+    ///
+    /// Records the routers that answer, up to 30 hops out. When every port
+    /// comes back filtered, the last hop to answer tells a block upstream
+    /// from filtering on the host itself. IPv4 hosts only.
+    #[arg(long)]
+    pub traceroute: bool,
+
+    /// Scan the hosts of a range in random order instead of address order
+    #[arg(long)]
+    pub randomize_hosts: bool,
+
+    /// Retries for transient DNS failures (timeouts, SERVFAIL)
+    #[arg(long, default_value_t = TargetSpec::DEFAULT_DNS_RETRIES, value_name = "N")]
+    pub dns_retries: u32,
+
+    /// Never send DNS queries; hostname targets are refused
+    #[arg(long)]
+    pub no_dns: bool,
+
+    /// Scan every address a hostname resolves to, not only the first
+    #[arg(long)]
+    pub all_ips: bool,
+
+    /// Name server to query instead of the system resolvers
+    #[arg(long, value_name = "IP", conflicts_with = "no_dns")]
+    pub dns_server: Option<IpAddr>,
+
+    /// Look up the reverse DNS name of each scanned host (ignored with --no-dns)
+    #[arg(long)]
+    pub resolve_ptr: bool,
+
+    /// MaxMind ASN database (e.g. GeoLite2-ASN.mmdb) to look up each host's
+    /// ASN and organization in (needs the `asn` feature)
+    #[arg(long, value_name = "PATH")]
+    pub asn_db: Option<PathBuf>,
+
+    /// OUI vendor list (IEEE oui.txt or nmap-mac-prefixes) merged over the
+    /// built-in one used to name the vendor of on-link hosts' MACs
+    #[arg(long, value_name = "PATH")]
+    pub oui_file: Option<PathBuf>,
+
+    /// Number of hosts to scan at the same time (for CIDR targets)
+    ///
+    /// Each host still uses up to --concurrency port probes.
+    #[arg(long, default_value = "1", value_name = "N")]
+    pub host_concurrency: usize,
+
+    /// List the available scan types and their requirements, then exit
+    #[arg(long)]
+    pub list_scan_types: bool,
+
+    /// Probe the Unix domain sockets under DIR instead of a network target (Unix only)
+    #[arg(long, value_name = "DIR", conflicts_with = "target")]
+    pub unix_sockets: Option<PathBuf>,
+
+    /// After scanning, show what changed since a saved scan (ID or prefix)
+    ///
+    /// For range scans only the host matching the saved scan's IP is compared.
+    #[arg(long, value_name = "SCAN_ID")]
+    pub compare_with: Option<String>,
+
+    /// Print the number of probes (hosts x ports) and exit without scanning
+    ///
+    /// Hostnames are not resolved and count as one host.
+    #[arg(long)]
+    pub count_only: bool,
 }
 
 impl ScanCommand {
+    /// Create a scan command for `target` with every option at its CLI default.
+    ///
+    /// Built field by field rather than by parsing an argument list, so it
+    /// keeps working when a flag is renamed. Keep the values in step with
+    /// the `#[arg]` defaults above.
+    pub fn with_defaults(target: impl Into<String>) -> Self {
+        Self {
+            target: Some(target.into()),
+            ports: None,
+            ports_from_scan: None,
+            scan_type: None,
+            like: None,
+            concurrency: None,
+            batch_size: 1,
+            output: OutputFormat::Plain,
+            template: None,
+            timeout: 3000,
+            timeout_jitter: 0,
+            fast_close: false,
+            source_port_range: None,
+            fast_reset_threshold: None,
+            max_retries_on_rst: 0,
+            alpn: false,
+            banner: false,
+            banner_timeout: None,
+            banner_size: DEFAULT_BANNER_SIZE,
+            interact: None,
+            services_file: None,
+            show_closed: false,
+            limit: None,
+            sort: SortKey::Port,
+            stream_csv: None,
+            open_ports_file: None,
+            unique_ports: false,
+            interface: None,
+            force: false,
+            max_probes: None,
+            sample: None,
+            fallback_connect: false,
+            fragment: false,
+            syn_retries: DEFAULT_SYN_RETRIES,
+            os_profile: OsProfile::default(),
+            rate_limit: 0,
+            per_host_rate: 0,
+            min_rate: 0,
+            backoff_threshold: DEFAULT_BACKOFF_THRESHOLD,
+            recovery_threshold: DEFAULT_RECOVERY_THRESHOLD,
+            profile: None,
+            no_save: false,
+            deterministic_id: None,
+            ping: None,
+            discovery_probes: Vec::new(),
+            skip_host_discovery: false,
+            traceroute: false,
+            randomize_hosts: false,
+            dns_retries: TargetSpec::DEFAULT_DNS_RETRIES,
+            no_dns: false,
+            all_ips: false,
+            dns_server: None,
+            resolve_ptr: false,
+            asn_db: None,
+            oui_file: None,
+            host_concurrency: 1,
+            list_scan_types: false,
+            unix_sockets: None,
+            compare_with: None,
+            count_only: false,
+        }
+    }
+
     /// Execute the scan command.
-    pub async fn execute(&self, verbose: bool, quiet: bool) -> CliResult<()> {
+    ///
+    /// The outcome reflects what the scan found; modes that do not scan
+    /// succeed.
+    pub async fn execute(&self, verbose: u8, quiet: bool) -> CliResult<Outcome> {
+        let session = self.run(verbose, quiet).await?;
+        Ok(session
+            .as_ref()
+            .map_or(Outcome::Success, Outcome::of_session))
+    }
+
+    /// Execute the scan command and return a session holding the record of
+    /// every host, including hosts found down. Modes that do not scan
+    /// return `None`.
+    pub async fn run(&self, verbose: u8, quiet: bool) -> CliResult<Option<ScanSession>> {
+        if self.list_scan_types {
+            return self.list_scan_types().map(|()| None);
+        }
+
+        if let Some(ref dir) = self.unix_sockets {
+            return self.scan_unix_sockets(dir).await.map(|()| None);
+        }
+
+        self.load_services()?;
+
+        // Template lines are the only output, so keep the rest off stdout
+        let quiet = quiet || self.template.is_some();
+
+        // A --like scan fills in the target, ports, and scan type left unset
+        let like = match self.like {
+            Some(ref id) => Some(ScanStore::new()?.find(id)?),
+            None => None,
+        };
+
         // Apply profile if specified
         let (ports_str, scan_type, concurrency, timeout_ms, banner, rate_limit) =
             if let Some(profile_name) = &self.profile {
@@ -88,15 +498,19 @@ impl ScanCommand {
                 (
                     profile.ports.clone(),
                     profile.scan_type.parse().unwrap_or(ScanType::Connect),
-                    profile.concurrency,
+                    Some(profile.concurrency),
                     profile.timeout_ms,
                     profile.banner,
                     profile.rate_limit,
                 )
             } else {
+                let (ports, scan_type) = match like {
+                    Some(ref record) => like_defaults(record, self)?,
+                    None => (self.ports.clone(), self.scan_type),
+                };
                 (
-                    self.ports.clone(),
-                    self.scan_type,
+                    ports.unwrap_or_else(|| DEFAULT_PORTS.to_string()),
+                    scan_type.unwrap_or(ScanType::Connect),
                     self.concurrency,
                     self.timeout,
                     self.banner,
@@ -104,8 +518,65 @@ impl ScanCommand {
                 )
             };
 
-        // Parse ports
-        let port_spec: PortSpec = ports_str.parse()?;
+        if rate_limit > 0 && self.min_rate > rate_limit {
+            return Err(crate::error::CliError::InvalidArgument(format!(
+                "--min-rate ({}) cannot exceed --rate ({})",
+                self.min_rate, rate_limit
+            )));
+        }
+
+        if self.per_host_rate > 0 && self.min_rate > self.per_host_rate {
+            return Err(crate::error::CliError::InvalidArgument(format!(
+                "--min-rate ({}) cannot exceed --per-host-rate ({})",
+                self.min_rate, self.per_host_rate
+            )));
+        }
+
+        for (flag, ratio) in [
+            ("--backoff-threshold", self.backoff_threshold),
+            ("--recovery-threshold", self.recovery_threshold),
+        ] {
+            if !(0.0..=1.0).contains(&ratio) {
+                return Err(crate::error::CliError::InvalidArgument(format!(
+                    "{} must be between 0 and 1, got {}",
+                    flag, ratio
+                )));
+            }
+        }
+
+        if let Some(percent) = self.sample {
+            if !(percent > 0.0 && percent <= 100.0) {
+                return Err(crate::error::CliError::InvalidArgument(format!(
+                    "--sample must be above 0 and at most 100, got {}",
+                    percent
+                )));
+            }
+        }
+
+        if !(1..=MAX_BANNER_SIZE).contains(&self.banner_size) {
+            return Err(crate::error::CliError::InvalidArgument(format!(
+                "--banner-size must be between 1 and {}, got {}",
+                MAX_BANNER_SIZE, self.banner_size
+            )));
+        }
+
+        let stdout_writers = [
+            self.streams_to_stdout(),
+            self.lists_ports_to_stdout(),
+            self.template.is_some(),
+        ];
+        if stdout_writers.iter().filter(|&&writes| writes).count() > 1 {
+            return Err(crate::error::CliError::InvalidArgument(
+                "only one of --stream-csv -, --open-ports-file -, and --template can write to stdout"
+                    .to_string(),
+            ));
+        }
+
+        // Parse ports, or take them from an earlier scan's hits
+        let port_spec: PortSpec = match self.ports_from_scan {
+            Some(ref id) => open_ports_of(&ScanStore::new()?.find(id)?)?,
+            None => ports_str.parse()?,
+        };
         let ports = port_spec.to_ports();
 
         if ports.is_empty() {
@@ -113,71 +584,561 @@ impl ScanCommand {
         }
 
         // Parse and resolve target
-        let target_spec = TargetSpec::parse(&self.target)?;
-        let targets = target_spec.resolve().await?;
+        let target = self.target.as_deref();
+        let target = target.or(like.as_ref().map(|r| r.target.as_str()));
+        let target = target.ok_or_else(|| {
+            crate::error::CliError::InvalidArgument("a scan target is required".to_string())
+        })?;
+        let target_spec = TargetSpec::parse(target)?;
+
+        if self.count_only {
+            return self
+                .print_probe_count(&target_spec, &port_spec)
+                .map(|()| None);
+        }
+
+        // Load the baseline up front so a bad ID fails before scanning
+        let baseline = match self.compare_with {
+            Some(ref id) => Some(ScanStore::new()?.find(id)?),
+            None => None,
+        };
+
+        let mut targets = target_spec.resolve_with(&self.resolve_options()).await?;
+        if self.randomize_hosts {
+            shuffle_targets(&mut targets);
+        }
 
         if targets.is_empty() {
-            return Err(crate::error::CliError::Other(
+            return Err(crate::error::CliError::Unreachable(
                 "No valid targets resolved".to_string(),
             ));
         }
 
-        // Check for privileged scan types
-        if matches!(scan_type, ScanType::Syn | ScanType::Udp) {
-            if !is_root() {
-                output::print_warning(&format!(
-                    "{} scan requires root/sudo privileges for raw socket access.",
-                    scan_type
+        // Non-fatal problems are printed as they occur and kept on each record
+        let mut warnings = Vec::new();
+        let mut records = Vec::with_capacity(targets.len());
+
+        self.check_probe_cap(targets.len(), self.probed_ports(ports.len()), &mut warnings)?;
+
+        let resolved = self.resolve_scan_type(scan_type, targets[0].ip, timeout_ms, &mut warnings);
+        // A fallback, or a privileged scan forced without privileges, may miss ports
+        let mut partial =
+            resolved != scan_type || (resolved.requires_privileges() && !has_raw_socket_access());
+        let scan_type = resolved;
+
+        if self.fragment && scan_type != ScanType::Syn {
+            warn(
+                &mut warnings,
+                "--fragment only applies to SYN scans and will be ignored.".to_string(),
+            );
+        }
+
+        if banner && !scan_type.supports_banner() {
+            warn(
+                &mut warnings,
+                format!(
+                    "--banner is not supported by {} scans and will be ignored.",
+                    scan_type.as_str()
+                ),
+            );
+        }
+
+        if self.timeout_jitter > 0 && scan_type != ScanType::Connect {
+            warn(
+                &mut warnings,
+                "--timeout-jitter only applies to connect scans and will be ignored.".to_string(),
+            );
+        }
+
+        if self.fast_close && scan_type != ScanType::Connect {
+            warn(
+                &mut warnings,
+                "--fast-close only applies to connect scans and will be ignored.".to_string(),
+            );
+        }
+
+        if self.source_port_range.is_some() && scan_type != ScanType::Connect {
+            warn(
+                &mut warnings,
+                "--source-port-range only applies to connect scans and will be ignored."
+                    .to_string(),
+            );
+        }
+
+        if self.fast_reset_threshold.is_some() && scan_type != ScanType::Connect {
+            warn(
+                &mut warnings,
+                "--fast-reset-threshold only applies to connect scans and will be ignored."
+                    .to_string(),
+            );
+        }
+
+        if self.max_retries_on_rst > 0 && scan_type != ScanType::Connect {
+            warn(
+                &mut warnings,
+                "--max-retries-on-rst only applies to connect scans and will be ignored."
+                    .to_string(),
+            );
+        }
+
+        if self.alpn {
+            #[cfg(not(feature = "tls"))]
+            return Err(crate::error::CliError::InvalidArgument(
+                "--alpn needs scuttle built with the `tls` feature".to_string(),
+            ));
+            #[cfg(feature = "tls")]
+            if scan_type != ScanType::Connect {
+                warn(
+                    &mut warnings,
+                    "--alpn only applies to connect scans and will be ignored.".to_string(),
+                );
+            }
+        }
+
+        self.check_privileges(scan_type, &mut warnings)?;
+        let enricher = self.enricher(&mut warnings)?;
+        let traceroute = self.traceroute(timeout_ms, &mut warnings);
+        let interact = self.interact_script(scan_type, targets.len(), ports.len())?;
+
+        // Hosts of a range share a session so they are saved and listed together
+        let mut session = ScanSession::new(target, scan_type);
+        session.port_spec = port_spec.to_string();
+        if let Some(id) = self.derived_id(target, &port_spec, scan_type) {
+            session.id = id;
+        }
+        let session_id = (targets.len() > 1).then_some(session.id);
+
+        // Probe hosts first so dead addresses are not port-scanned
+        let discovery_enabled = self.host_discovery_enabled(targets.len());
+        let targets = if discovery_enabled {
+            let mode = self.discovery_mode(&mut warnings);
+            if mode.requires_privileges() && !has_raw_socket_access() {
+                partial = true;
+                warn(
+                    &mut warnings,
+                    format!(
+                        "{} requires root/sudo privileges; falling back to TCP SYN ping.",
+                        mode
+                    ),
+                );
+            }
+
+            let discovery = HostDiscovery::new(mode, Duration::from_millis(timeout_ms))
+                .with_icmp_probes(self.discovery_probes.clone());
+            let (up, down) = discovery
+                .discover(
+                    targets,
+                    concurrency.unwrap_or(HostDiscovery::DEFAULT_CONCURRENCY),
+                )
+                .await;
+
+            if !quiet && self.output == OutputFormat::Plain {
+                output::print_info(&format!(
+                    "Host discovery ({}): {} up, {} down",
+                    discovery.describe(),
+                    up.len(),
+                    down.len()
                 ));
-                output::print_warning("Results may be incomplete or scanning may fail.");
             }
+
+            for host in &down {
+                records.push(self.record_down_host(
+                    host, scan_type, &port_spec, &warnings, partial, session_id, quiet,
+                )?);
+            }
+
+            up
+        } else {
+            targets
+        };
+
+        // Write open ports as they are found when --stream-csv is set
+        let (stream, stream_writer) = match self.stream_csv {
+            Some(ref path) => {
+                let sink = if self.streams_to_stdout() {
+                    CsvStream::stdout()
+                } else {
+                    CsvStream::create(path).map_err(|e| {
+                        crate::error::CliError::Other(format!(
+                            "cannot create {}: {}",
+                            path.display(),
+                            e
+                        ))
+                    })?
+                };
+                let (sender, receiver) = mpsc::unbounded_channel();
+                // Rows are flushed one at a time, so keep the writes off the
+                // runtime's worker threads
+                (
+                    Some(sender),
+                    Some(tokio::task::spawn_blocking(move || {
+                        write_csv_stream(sink, receiver)
+                    })),
+                )
+            }
+            None => (None, None),
+        };
+
+        // One combined progress display across all hosts of a range scan
+        let progress = (verbose > 0 && targets.len() > 1)
+            .then(|| ScanProgress::new(targets.len(), self.probed_ports(ports.len())));
+
+        // One overall bucket and one per host, shared by every host's scan
+        let rate_limiter = RateLimiter::from_limit(rate_limit);
+        let host_limiter = KeyedRateLimiter::from_limit(self.per_host_rate);
+
+        // Scan resolved targets, several at a time if requested;
+        // `buffered` keeps records in target order for output
+        let mut scans = stream::iter(&targets)
+            .map(|scan_target| async {
+                let mut record = self
+                    .scan_target(
+                        scan_target,
+                        &port_spec,
+                        &ports,
+                        scan_type,
+                        concurrency,
+                        timeout_ms,
+                        banner,
+                        interact.as_ref(),
+                        traceroute.as_ref(),
+                        rate_limit,
+                        &rate_limiter,
+                        &host_limiter,
+                        verbose,
+                        quiet,
+                        &warnings,
+                        stream.as_ref(),
+                        progress.as_ref(),
+                    )
+                    .await?;
+                enricher.enrich(&mut record).await;
+                Ok::<_, crate::error::CliError>(record)
+            })
+            .buffered(self.host_concurrency.max(1));
+
+        let single_target = targets.len() == 1;
+        while let Some(record) = scans.next().await {
+            let mut record = record?;
+            record.session_id = session_id;
+            record.partial |= partial;
+            // Hosts that reached port scanning answered discovery, if it ran
+            if discovery_enabled {
+                record.host_state = HostState::Up;
+            }
+            if let Some(id) = self.derived_id(record_key(&record), &port_spec, scan_type) {
+                record.id = id;
+            }
+            let baseline = baseline
+                .as_ref()
+                .filter(|b| single_target || b.ip_address == record.ip_address);
+            suspend_progress(progress.as_ref(), || {
+                self.finish_target(&record, baseline, verbose, quiet)
+            })?;
+            records.push(record);
+        }
+
+        drop(scans);
+        if let Some(progress) = progress {
+            progress.finish();
         }
 
-        // Scan each resolved target
-        for scan_target in targets {
-            self.scan_target(
-                &scan_target,
-                &ports,
-                scan_type,
-                concurrency,
-                timeout_ms,
-                banner,
-                rate_limit,
-                verbose,
-                quiet,
-            )
-            .await?;
+        // Closing the channel lets the writer drain and report any error
+        drop(stream);
+        if let Some(writer) = stream_writer {
+            writer
+                .await
+                .map_err(|e| crate::error::CliError::Other(e.to_string()))??;
         }
 
+        let session = session.finalize(records);
+        if session_id.is_some() {
+            self.finish_session(&session, verbose, quiet)?;
+        }
+        if let Some(ref path) = self.open_ports_file {
+            self.write_open_ports(&session, path)?;
+        }
+
+        Ok(Some(session))
+    }
+
+    /// Save and summarize the session of a range scan.
+    fn finish_session(&self, session: &ScanSession, verbose: u8, quiet: bool) -> CliResult<()> {
+        if !self.no_save {
+            ScanStore::new()?.save_session(session)?;
+        }
+
+        match self.output {
+            _ if self.stdout_taken() => {}
+            OutputFormat::Json => output::print_json_records(&session.records)?,
+            OutputFormat::JsonCompact => output::write_json(&session.records, true)?,
+            OutputFormat::Csv => {
+                output::print_session(session, OutputFormat::Csv, &self.plain_options(verbose))?
+            }
+            OutputFormat::Plain if !quiet => {
+                output::print_session(session, OutputFormat::Plain, &self.plain_options(verbose))?;
+                if !self.no_save {
+                    output::print_info(&format!("Session saved as {}", session.id.short()));
+                }
+            }
+            OutputFormat::Plain | OutputFormat::Jsonl => {}
+        }
         Ok(())
     }
 
+    /// How plain and CSV output lays out port rows.
+    fn plain_options(&self, verbose: u8) -> PlainOptions {
+        PlainOptions {
+            limit: self.limit,
+            sort: self.sort,
+            show_errors: verbose > 0,
+        }
+    }
+
+    /// The `--deterministic-id` scan ID for `target`, if one was asked for.
+    fn derived_id(
+        &self,
+        target: &str,
+        port_spec: &PortSpec,
+        scan_type: ScanType,
+    ) -> Option<ScanId> {
+        self.deterministic_id.as_ref().map(|seed| {
+            ScanId::from_inputs(target, &port_spec.to_string(), scan_type.as_str(), seed)
+        })
+    }
+
+    /// Ports probed per host out of `ports`, after any `--sample`.
+    fn probed_ports(&self, ports: usize) -> usize {
+        self.sample
+            .map_or(ports, |percent| sample_size(ports, percent))
+    }
+
+    /// Hostname resolution settings from the DNS flags.
+    fn resolve_options(&self) -> ResolveOptions {
+        resolve_options(self.dns_retries, self.no_dns, self.all_ips, self.dns_server)
+    }
+
+    /// Set up the lookups asked for by `--resolve-ptr` and `--asn-db`.
+    ///
+    /// A database that cannot be opened is a warning, not an error.
+    fn enricher(&self, warnings: &mut Vec<String>) -> CliResult<Enricher> {
+        let mut vendors = OuiTable::builtin();
+        if let Some(ref path) = self.oui_file {
+            match OuiTable::load(path) {
+                Ok(table) => vendors.merge(table),
+                Err(e) => warn(
+                    warnings,
+                    format!("cannot load OUI file {}: {}", path.display(), e),
+                ),
+            }
+        }
+        let mut enricher = Enricher::default().with_vendors(vendors);
+        if self.resolve_ptr {
+            if self.no_dns {
+                warn(
+                    warnings,
+                    "--resolve-ptr is ignored with --no-dns.".to_string(),
+                );
+            }
+            enricher = enricher.with_ptr(&self.resolve_options());
+        }
+
+        if let Some(ref path) = self.asn_db {
+            #[cfg(feature = "asn")]
+            match crate::enrich::open_asn_db(path) {
+                Ok(db) => enricher = enricher.with_asn_db(db),
+                Err(e) => warn(
+                    warnings,
+                    format!("cannot open ASN database {}: {}", path.display(), e),
+                ),
+            }
+            #[cfg(not(feature = "asn"))]
+            return Err(crate::error::CliError::InvalidArgument(format!(
+                "--asn-db {} needs scuttle built with the `asn` feature",
+                path.display()
+            )));
+        }
+        Ok(enricher)
+    }
+
+    /// Whether `--stream-csv -` sends rows to stdout during the scan.
+    fn streams_to_stdout(&self) -> bool {
+        self.stream_csv
+            .as_ref()
+            .is_some_and(|path| path.as_os_str() == "-")
+    }
+
+    /// Whether `--open-ports-file -` sends the port list to stdout.
+    fn lists_ports_to_stdout(&self) -> bool {
+        self.open_ports_file
+            .as_ref()
+            .is_some_and(|path| path.as_os_str() == "-")
+    }
+
+    /// Whether stdout carries a raw CSV, port, or template stream instead of
+    /// formatted results.
+    fn stdout_taken(&self) -> bool {
+        self.streams_to_stdout() || self.lists_ports_to_stdout() || self.template.is_some()
+    }
+
+    /// Write the `--open-ports-file` list for a finished scan.
+    fn write_open_ports(&self, session: &ScanSession, path: &Path) -> CliResult<()> {
+        let written = if self.lists_ports_to_stdout() {
+            output::write_open_ports(session, std::io::stdout().lock(), self.unique_ports)
+        } else {
+            std::fs::File::create(path)
+                .and_then(|file| output::write_open_ports(session, file, self.unique_ports))
+        };
+        written.map_err(|e| {
+            crate::error::CliError::Other(format!("cannot write {}: {}", path.display(), e))
+        })
+    }
+
+    /// Whether host discovery should run for this many resolved targets.
+    fn host_discovery_enabled(&self, target_count: usize) -> bool {
+        !self.skip_host_discovery
+            && (self.ping.is_some() || !self.discovery_probes.is_empty() || target_count > 1)
+    }
+
+    /// The host discovery probe type, warning about ICMP probes it ignores
+    /// or cannot send.
+    fn discovery_mode(&self, warnings: &mut Vec<String>) -> DiscoveryMode {
+        if self.discovery_probes.is_empty() {
+            return self.ping.unwrap_or_default();
+        }
+
+        let mode = self.ping.unwrap_or(DiscoveryMode::Icmp);
+        if mode != DiscoveryMode::Icmp {
+            warn(
+                warnings,
+                format!(
+                    "--discovery-probes only applies to --ping icmp and will be ignored by {}.",
+                    mode
+                ),
+            );
+        } else if !has_raw_socket_access() {
+            let privileged = self
+                .discovery_probes
+                .iter()
+                .filter(|p| p.requires_privileges());
+            for probe in privileged {
+                warn(
+                    warnings,
+                    format!(
+                        "ICMP {} requests require root/sudo privileges; \
+                         falling back to TCP SYN ping for them.",
+                        probe
+                    ),
+                );
+            }
+        }
+        mode
+    }
+
+    /// The tracer for `--traceroute`, unless it cannot run without privileges.
+    fn traceroute(&self, timeout_ms: u64, warnings: &mut Vec<String>) -> Option<Traceroute> {
+        if !self.traceroute {
+            return None;
+        }
+        if !has_raw_socket_access() {
+            warn(
+                warnings,
+                "--traceroute requires root/sudo privileges and will be skipped.".to_string(),
+            );
+            return None;
+        }
+        Some(Traceroute::new(Duration::from_millis(timeout_ms)))
+    }
+
+    /// Record a host that did not answer discovery probes.
+    #[allow(clippy::too_many_arguments)]
+    fn record_down_host(
+        &self,
+        target: &ScanTarget,
+        scan_type: ScanType,
+        port_spec: &PortSpec,
+        warnings: &[String],
+        partial: bool,
+        session_id: Option<ScanId>,
+        quiet: bool,
+    ) -> CliResult<ScanRecord> {
+        let mut record =
+            ScanRecord::host_down(&target.original, target.ip.to_string(), scan_type);
+        record.port_spec = port_spec.to_string();
+        record.warnings = warnings.to_vec();
+        record.partial = partial;
+        record.session_id = session_id;
+        if let Some(id) = self.derived_id(record_key(&record), port_spec, scan_type) {
+            record.id = id;
+        }
+
+        if !self.no_save {
+            ScanStore::new()?.save(&record)?;
+        }
+
+        match self.output {
+            OutputFormat::Plain => {
+                if !quiet {
+                    output::print_info(&format!("Host {} appears down, skipping", target.ip));
+                }
+            }
+            // Range scans print every host in one array at the end
+            OutputFormat::Json | OutputFormat::JsonCompact if record.session_id.is_some() => {}
+            OutputFormat::Json => output::print_json(&record)?,
+            OutputFormat::JsonCompact => output::print_json_compact(&record)?,
+            OutputFormat::Jsonl => output::print_json_line(&record)?,
+            // No port rows to emit for a down host
+            OutputFormat::Csv => {}
+        }
+
+        Ok(record)
+    }
+
+    #[allow(clippy::too_many_arguments)]
     async fn scan_target(
         &self,
         target: &ScanTarget,
+        port_spec: &PortSpec,
         ports: &[Port],
         scan_type: ScanType,
-        concurrency: usize,
+        concurrency: Option<usize>,
         timeout_ms: u64,
         banner: bool,
+        interact: Option<&Script>,
+        traceroute: Option<&Traceroute>,
         rate_limit: u32,
-        verbose: bool,
+        rate_limiter: &RateLimiter,
+        host_limiter: &KeyedRateLimiter,
+        verbose: u8,
         quiet: bool,
-    ) -> CliResult<()> {
-        // Print scan header (unless JSON/CSV output for clean parsing)
-        if !quiet && self.output == OutputFormat::Plain {
-            output::print_scan_header(
-                &target.original,
-                &target.ip.to_string(),
-                &scan_type.to_string(),
-                ports.len(),
-            );
+        warnings: &[String],
+        stream: Option<&UnboundedSender<(IpAddr, PortResult)>>,
+        progress: Option<&ScanProgress>,
+    ) -> CliResult<ScanRecord> {
+        // Print scan header (unless JSON/CSV output for clean parsing, or
+        // hosts are scanned concurrently and headers would interleave)
+        if !quiet && self.output == OutputFormat::Plain && self.host_concurrency <= 1 {
+            suspend_progress(progress, || {
+                output::print_scan_header(
+                    &target.original,
+                    &target.ip.to_string(),
+                    &scan_type.to_string(),
+                    ports.len(),
+                )
+            });
         }
 
+        // Trace first, so that the route is not skewed by the scan's own traffic
+        let route = match traceroute {
+            Some(traceroute) => Some(traceroute.trace(target.ip).await),
+            None => None,
+        };
+
         // Build scan configuration
         let scan_config = ScanConfig::new(target.ip)
             .with_hostname(&target.original)
-            .with_timeout(Duration::from_millis(timeout_ms));
+            .with_timeout(Duration::from_millis(timeout_ms))
+            .with_timeout_jitter(Duration::from_millis(self.timeout_jitter));
 
         let scan_config = if banner {
             scan_config.with_banners()
@@ -185,6 +1146,60 @@ impl ScanCommand {
             scan_config
         };
 
+        let scan_config = scan_config.with_banner_size(self.banner_size);
+
+        let scan_config = if let Some(ms) = self.banner_timeout {
+            scan_config.with_banner_timeout(Duration::from_millis(ms))
+        } else {
+            scan_config
+        };
+
+        let scan_config = scan_config
+            .with_os_profile(self.os_profile)
+            .with_syn_retries(self.syn_retries);
+
+        let scan_config = match interact {
+            Some(script) => scan_config.with_interact(script.clone()),
+            None => scan_config,
+        };
+
+        let scan_config = if self.fragment {
+            scan_config.with_fragmentation()
+        } else {
+            scan_config
+        };
+
+        let scan_config = if self.fast_close {
+            scan_config.with_fast_close()
+        } else {
+            scan_config
+        };
+
+        let scan_config = match self.source_port_range {
+            Some(range) => scan_config.with_source_ports(range),
+            None => scan_config,
+        };
+
+        let scan_config = match self.fast_reset_threshold {
+            Some(ms) => scan_config.with_fast_reset_threshold(Duration::from_millis(ms)),
+            None => scan_config,
+        };
+
+        let scan_config = scan_config.with_rst_retries(self.max_retries_on_rst);
+
+        // Closed ports are dropped from the results, so they need no name
+        let scan_config = if self.show_closed {
+            scan_config
+        } else {
+            scan_config.without_closed_services()
+        };
+
+        let scan_config = if self.alpn {
+            scan_config.with_alpn()
+        } else {
+            scan_config
+        };
+
         let scan_config = if let Some(ref iface) = self.interface {
             scan_config.with_interface(iface)
         } else {
@@ -196,13 +1211,26 @@ impl ScanCommand {
 
         // Build job configuration
         let job_config = ScanJobConfig::new(ports.to_vec())
-            .with_concurrency(concurrency)
-            .with_rate_limit(rate_limit);
+            .with_port_spec(port_spec.clone())
+            .with_rate_limit(rate_limit)
+            .with_rate_limiter(rate_limiter.clone())
+            .with_host_rate_limiter(host_limiter.clone())
+            .with_min_rate(self.min_rate)
+            .with_backoff(self.backoff_threshold, self.recovery_threshold)
+            .with_verbosity(verbose)
+            .with_warnings(warnings.to_vec());
 
-        let job_config = if verbose {
-            job_config.with_verbose()
-        } else {
-            job_config
+        // Without --concurrency, run_scan picks a default for the scan type
+        let job_config = match concurrency {
+            Some(concurrency) => job_config.with_concurrency(concurrency),
+            None => job_config,
+        };
+
+        let job_config = job_config.with_batch_size(self.batch_size);
+
+        let job_config = match self.sample {
+            Some(percent) => job_config.with_sample(percent),
+            None => job_config,
         };
 
         let job_config = if self.show_closed {
@@ -211,13 +1239,251 @@ impl ScanCommand {
             job_config
         };
 
-        // Execute scan
-        let record = run_scan(scanner, job_config).await?;
+        let job_config = if let Some(progress) = progress {
+            job_config.with_progress(progress.clone())
+        } else {
+            job_config
+        };
+
+        let job_config = if let Some(stream) = stream {
+            job_config.with_result_stream(stream.clone())
+        } else {
+            job_config
+        };
+
+        // Execute scan, then surface any warnings it or the trace added
+        let mut record = run_scan(scanner, job_config).await?;
+        match route {
+            Some(Ok(route)) => record.route = route,
+            Some(Err(e)) => record.warnings.push(format!("Traceroute failed: {}", e)),
+            None => {}
+        }
+        record.local_host = target.is_local();
+        if record.local_host && scan_type == ScanType::Syn {
+            record.warnings.push(format!(
+                "{} is this machine; SYN scans of the local host may miss or misreport ports.",
+                target.ip
+            ));
+        }
+        for warning in &record.warnings[warnings.len()..] {
+            suspend_progress(progress, || output::print_warning(warning));
+        }
+        Ok(record)
+    }
+
+    /// Merge the user's service file and `--services-file` over the built-in map.
+    fn load_services(&self) -> CliResult<()> {
+        let default = Paths::get().services_file();
+        let mut names = if default.exists() {
+            services::load_service_file(&default)?
+        } else {
+            HashMap::new()
+        };
+        if let Some(path) = &self.services_file {
+            names.extend(services::load_service_file(path)?);
+        }
+
+        if !names.is_empty() {
+            services::install_user_services(names);
+        }
+        Ok(())
+    }
+
+    /// Load the `--interact` script, checking the scan has the single
+    /// connect-scanned port it needs.
+    fn interact_script(
+        &self,
+        scan_type: ScanType,
+        hosts: usize,
+        ports: usize,
+    ) -> CliResult<Option<Script>> {
+        let Some(ref path) = self.interact else {
+            return Ok(None);
+        };
+
+        if scan_type != ScanType::Connect {
+            return Err(crate::error::CliError::InvalidArgument(format!(
+                "--interact needs a connect scan, not {}",
+                scan_type.as_str()
+            )));
+        }
+        if hosts != 1 || ports != 1 {
+            return Err(crate::error::CliError::InvalidArgument(format!(
+                "--interact needs a single host and port, got {} hosts and {} ports",
+                hosts, ports
+            )));
+        }
+
+        Script::load(path).map(Some).map_err(|e| {
+            crate::error::CliError::InvalidArgument(format!("--interact {}: {}", path.display(), e))
+        })
+    }
+
+    /// Downgrade a SYN scan to a connect scan when `--fallback-connect` is set
+    /// and raw socket access is denied.
+    fn resolve_scan_type(
+        &self,
+        scan_type: ScanType,
+        probe: IpAddr,
+        timeout_ms: u64,
+        warnings: &mut Vec<String>,
+    ) -> ScanType {
+        if scan_type != ScanType::Syn || !self.fallback_connect {
+            return scan_type;
+        }
+
+        let timeout = Duration::from_millis(timeout_ms);
+        match SynScanner::new(probe, self.interface.as_deref(), timeout) {
+            Err(ScanError::PermissionDenied(reason)) => {
+                warn(
+                    warnings,
+                    format!("{}; falling back to a TCP connect scan.", reason),
+                );
+                ScanType::Connect
+            }
+            _ => scan_type,
+        }
+    }
+
+    /// Refuse scans above the probe cap from `--max-probes` or settings unless `--force` is given.
+    fn check_probe_cap(
+        &self,
+        hosts: usize,
+        ports: usize,
+        warnings: &mut Vec<String>,
+    ) -> CliResult<()> {
+        let cap = match self.max_probes {
+            Some(cap) => cap,
+            None => AppSettings::load()?.max_probes,
+        };
+        let total = hosts as u64 * ports as u64;
+        if cap == 0 || total <= cap {
+            return Ok(());
+        }
+
+        let message = format!(
+            "Scan would send {} probes ({} hosts x {} ports), above the cap of {}",
+            total, hosts, ports, cap
+        );
+        if self.force {
+            warn(warnings, format!("{}; continuing because of --force.", message));
+            return Ok(());
+        }
+        Err(crate::error::CliError::InvalidArgument(format!(
+            "{}; narrow the targets or ports, raise --max-probes, or pass --force.",
+            message
+        )))
+    }
+
+    /// Refuse to start a privileged scan without root unless `--force` is given.
+    fn check_privileges(&self, scan_type: ScanType, warnings: &mut Vec<String>) -> CliResult<()> {
+        if !scan_type.requires_privileges() || has_raw_socket_access() {
+            return Ok(());
+        }
 
+        if self.force {
+            warn(
+                warnings,
+                format!(
+                    "{} scan requires root/sudo privileges for raw socket access.",
+                    scan_type
+                ),
+            );
+            warn(
+                warnings,
+                "Continuing because of --force; results may be incomplete.".to_string(),
+            );
+            return Ok(());
+        }
+
+        let mut hint = String::from("re-run with sudo");
+        if scan_type == ScanType::Syn {
+            hint.push_str(", add --fallback-connect to use a TCP connect scan instead");
+        }
+        hint.push_str(", or pass --force to try anyway");
+
+        Err(crate::error::CliError::Privileges(format!(
+            "{} scan requires root/sudo privileges (or CAP_NET_RAW) for raw socket access; {}.",
+            scan_type, hint
+        )))
+    }
+
+    /// Print the total number of probes a scan would send.
+    ///
+    /// Plain output is a single integer; JSON includes the breakdown.
+    fn print_probe_count(&self, target: &TargetSpec, ports: &PortSpec) -> CliResult<()> {
+        let hosts = target.estimated_host_count();
+        let port_count = self.probed_ports(ports.count()) as u128;
+        let probes = hosts * port_count;
+
+        if self.output.is_json() {
+            let count = serde_json::json!({
+                "hosts": hosts,
+                "ports": port_count,
+                "probes": probes,
+            });
+            println!("{}", count);
+        } else {
+            println!("{}", probes);
+        }
+        Ok(())
+    }
+
+    /// Connect to each socket file under `dir` and print what accepted.
+    #[cfg(unix)]
+    async fn scan_unix_sockets(&self, dir: &Path) -> CliResult<()> {
+        let scanner = UnixSocketScanner::new(Duration::from_millis(self.timeout))
+            .with_banner_size(self.banner_size);
+        let scanner = if self.banner {
+            scanner.with_banners()
+        } else {
+            scanner
+        };
+
+        let record = scanner.scan_dir(dir).await.map_err(|e| {
+            crate::error::CliError::Other(format!("cannot read {}: {}", dir.display(), e))
+        })?;
+        output::print_socket_scan(&record, self.output)?;
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    async fn scan_unix_sockets(&self, _dir: &Path) -> CliResult<()> {
+        Err(crate::error::CliError::InvalidArgument(
+            "--unix-sockets is only supported on Unix platforms".to_string(),
+        ))
+    }
+
+    /// Print every scan type with its protocol and requirements.
+    fn list_scan_types(&self) -> CliResult<()> {
+        let infos = ScannerInfo::all();
+        match self.output {
+            OutputFormat::Json => output::write_json(&infos, false)?,
+            OutputFormat::JsonCompact => output::write_json(&infos, true)?,
+            OutputFormat::Jsonl => {
+                for info in &infos {
+                    let json = serde_json::to_string(info)
+                        .map_err(|e| crate::error::CliError::Other(e.to_string()))?;
+                    println!("{}", json);
+                }
+            }
+            _ => output::print_scan_types(&infos)?,
+        }
+        Ok(())
+    }
+
+    /// Save and print the record of a completed host scan.
+    fn finish_target(
+        &self,
+        record: &ScanRecord,
+        baseline: Option<&ScanRecord>,
+        verbose: u8,
+        quiet: bool,
+    ) -> CliResult<()> {
         // Save results unless disabled
         if !self.no_save {
             let store = ScanStore::new()?;
-            store.save(&record)?;
+            store.save(record)?;
 
             if !quiet && self.output == OutputFormat::Plain {
                 output::print_info(&format!("Scan saved as {}", record.id.short()));
@@ -225,24 +1491,148 @@ impl ScanCommand {
         }
 
         // Output results
-        output::print_results(&record, self.output)?;
+        let options = self.plain_options(verbose);
+        // Rows streamed to stdout already are not repeated, and the hosts of
+        // a range scan go out as one JSON array or CSV table once all are done
+        let in_session = matches!(
+            self.output,
+            OutputFormat::Json | OutputFormat::JsonCompact | OutputFormat::Csv
+        ) && record.session_id.is_some();
+        if let Some(ref template) = self.template {
+            template.write_record(record, std::io::stdout().lock())?;
+        } else if !self.stdout_taken() && !in_session {
+            output::print_results(record, self.output, &options)?;
+        }
+
+        // The table cuts banners short; show an --interact transcript whole
+        if self.interact.is_some() && !quiet && self.output == OutputFormat::Plain {
+            for result in &record.results {
+                if let Some(ref transcript) = result.banner {
+                    output::print_transcript(result.port.as_u16(), transcript);
+                }
+            }
+        }
+
+        // The delta goes to stderr for JSON, CSV, and templates so stdout stays parseable
+        if let Some(baseline) = baseline {
+            let diff = ScanDiff::between(baseline, record);
+            let to_stderr = self.output != OutputFormat::Plain || self.template.is_some();
+            output::print_scan_diff(&diff, baseline, to_stderr)?;
+        }
+
+        // JSON carries the settings in the record; plain shows them on request
+        if let Some(ref config) = record.config {
+            if verbose > 0 && !quiet && self.output == OutputFormat::Plain {
+                output::print_info(&format!("Scan settings: {}", config));
+            }
+        }
 
         Ok(())
     }
 }
 
-/// Check if running with root/admin privileges.
-fn is_root() -> bool {
-    #[cfg(unix)]
-    {
-        unsafe { libc::geteuid() == 0 }
+/// Ports a saved scan found open, for a follow-up scan of just the hits.
+fn open_ports_of(record: &ScanRecord) -> CliResult<PortSpec> {
+    let spec: PortSpec = record
+        .results
+        .iter()
+        .filter(|r| r.is_open())
+        .map(|r| r.port)
+        .collect();
+
+    if spec.is_empty() {
+        return Err(crate::error::CliError::InvalidArgument(format!(
+            "scan {} found no open ports to re-probe",
+            record.id.short()
+        )));
+    }
+    Ok(spec)
+}
+
+/// Ports and scan type for `--like`: the command line's, else the record's.
+fn like_defaults(
+    record: &ScanRecord,
+    cmd: &ScanCommand,
+) -> CliResult<(Option<String>, Option<ScanType>)> {
+    let ports = match (&cmd.ports, record.port_spec.is_empty()) {
+        (Some(ports), _) => Some(ports.clone()),
+        (None, false) => Some(record.port_spec.clone()),
+        (None, true) if cmd.ports_from_scan.is_some() => None,
+        (None, true) => {
+            return Err(crate::error::CliError::InvalidArgument(format!(
+                "scan {} does not record which ports were scanned; pass --ports",
+                record.id.short()
+            )))
+        }
+    };
+
+    let scan_type = match cmd.scan_type {
+        Some(scan_type) => scan_type,
+        None => record.scan_type.parse().map_err(|e| {
+            crate::error::CliError::InvalidArgument(format!(
+                "scan {}: {}; pass --scan-type",
+                record.id.short(),
+                e
+            ))
+        })?,
+    };
+
+    Ok((ports, Some(scan_type)))
+}
+
+/// Target a record's derived ID is based on: the host's IP within a range
+/// scan, otherwise the target as given.
+fn record_key(record: &ScanRecord) -> &str {
+    if record.session_id.is_some() {
+        &record.ip_address
+    } else {
+        &record.target
+    }
+}
+
+/// Resolution settings for the DNS flags shared by `scan` and `resolve`.
+pub(crate) fn resolve_options(
+    dns_retries: u32,
+    no_dns: bool,
+    all_ips: bool,
+    dns_server: Option<IpAddr>,
+) -> ResolveOptions {
+    let mut options = ResolveOptions::default().with_dns_retries(dns_retries);
+    if no_dns {
+        options = options.without_dns();
+    }
+    if all_ips {
+        options = options.with_all_ips();
+    }
+    if let Some(server) = dns_server {
+        options = options.with_dns_server(server);
     }
-    #[cfg(windows)]
-    {
-        false
+    options
+}
+
+/// Write streamed results to `sink` until every scan has finished.
+///
+/// Blocks between results, so run it with `spawn_blocking`.
+fn write_csv_stream(
+    mut sink: CsvStream,
+    mut receiver: UnboundedReceiver<(IpAddr, PortResult)>,
+) -> std::io::Result<()> {
+    while let Some((host, result)) = receiver.blocking_recv() {
+        sink.write_result(host, &result)?;
     }
-    #[cfg(not(any(unix, windows)))]
-    {
-        false
+    Ok(())
+}
+
+/// Print a non-fatal warning and keep it for the scan records.
+fn warn(warnings: &mut Vec<String>, message: String) {
+    output::print_warning(&message);
+    warnings.push(message);
+}
+
+/// Run `f` with the progress display hidden, if there is one.
+fn suspend_progress<F: FnOnce() -> R, R>(progress: Option<&ScanProgress>, f: F) -> R {
+    match progress {
+        Some(progress) => progress.suspend(f),
+        None => f(),
     }
 }
@@ -0,0 +1,52 @@
+//! Serve subcommand implementation.
+//!
+//! Handles the `scuttle serve` command, which exposes scans over an HTTP API
+//! (see [`crate::server`]). Only built with the `serve` feature.
+
+use crate::error::CliResult;
+use crate::output;
+use crate::server::{self, ServerConfig};
+use clap::Parser;
+use std::net::SocketAddr;
+
+/// Run an HTTP API for starting and fetching scans.
+#[derive(Parser, Debug)]
+pub struct ServeCommand {
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8787")]
+    pub listen: SocketAddr,
+
+    /// Highest concurrency a scan request may use
+    #[arg(long, default_value = "500")]
+    pub max_concurrency: usize,
+
+    /// Highest rate in probes per second a scan may use (0 = no cap)
+    #[arg(long, default_value = "0")]
+    pub max_rate: u32,
+
+    /// Most ports a single scan request may cover
+    #[arg(long, default_value = "65535")]
+    pub max_ports: usize,
+
+    /// Most scans running at once; further requests get 429 Too Many Requests
+    #[arg(long, default_value = "4")]
+    pub max_scans: usize,
+}
+
+impl ServeCommand {
+    /// Execute the serve command; runs until Ctrl-C.
+    pub async fn execute(&self, _verbose: u8, quiet: bool) -> CliResult<()> {
+        let config = ServerConfig {
+            max_concurrency: self.max_concurrency,
+            max_rate: self.max_rate,
+            max_ports: self.max_ports,
+            max_scans: self.max_scans,
+        };
+
+        if !quiet {
+            output::print_info(&format!("Listening on http://{}", self.listen));
+        }
+        server::serve(self.listen, config).await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,210 @@
+//! Watch subcommand implementation.
+//!
+//! Handles the `scuttle watch` command, which re-runs the same scan on a
+//! fixed schedule for lightweight monitoring. Every run is saved like a
+//! normal scan, so `scuttle history` and `--compare-with` work on the results.
+//! A run that fails is reported and the watch carries on with the next one.
+
+use crate::cli::{OutputFormat, ScanCommand};
+use crate::error::CliResult;
+use crate::output;
+use crate::storage::{ScanDiff, ScanRecord};
+use chrono::Local;
+use clap::Parser;
+use std::collections::HashMap;
+use std::future::Future;
+use std::time::Duration;
+use tokio::time::MissedTickBehavior;
+
+/// Re-run a scan at a fixed interval.
+#[derive(Parser, Debug)]
+pub struct WatchCommand {
+    /// Target to scan (IP, hostname, or CIDR notation)
+    #[arg(long, value_name = "TARGET")]
+    pub target: String,
+
+    /// Use a saved scan profile (re-read before every run)
+    #[arg(long, short = 'P')]
+    pub profile: Option<String>,
+
+    /// Ports to scan when no profile is given [default: 1-1000]
+    #[arg(short, long, conflicts_with = "profile")]
+    pub ports: Option<String>,
+
+    /// Time between run starts, e.g. "90s", "15m", "1h" (plain numbers are seconds)
+    #[arg(long, value_name = "DURATION", value_parser = parse_interval)]
+    pub interval: Duration,
+
+    /// After each run, print what changed since the previous one
+    #[arg(long)]
+    pub diff: bool,
+
+    /// Stop after this many runs instead of running until Ctrl-C
+    #[arg(long, value_name = "N")]
+    pub runs: Option<u32>,
+
+    /// Output format for results
+    #[arg(short, long, value_enum, default_value = "plain")]
+    pub output: OutputFormat,
+}
+
+impl WatchCommand {
+    /// Execute the watch command; runs until Ctrl-C or `--runs` is reached.
+    pub async fn execute(&self, verbose: u8, quiet: bool) -> CliResult<()> {
+        let mut scan = ScanCommand::with_defaults(&self.target);
+        scan.profile = self.profile.clone();
+        if let Some(ref ports) = self.ports {
+            scan.ports = Some(ports.clone());
+        }
+        scan.output = self.output;
+
+        let scan = &scan;
+        let completed = self
+            .watch(|| async move {
+                let session = scan.run(verbose, quiet).await?;
+                Ok(session.map(|s| s.records).unwrap_or_default())
+            })
+            .await?;
+
+        if !quiet && self.output == OutputFormat::Plain {
+            output::print_info(&format!("Watch stopped after {} runs", completed));
+        }
+        Ok(())
+    }
+
+    /// Call `run` on the schedule until Ctrl-C or `--runs` is reached, and
+    /// return how many runs were made.
+    ///
+    /// A failed run counts as a run. Its error is printed with the time it
+    /// happened and no diff is shown for it; the next run is compared with
+    /// the last one that succeeded.
+    async fn watch<F, Fut>(&self, mut run: F) -> CliResult<u32>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = CliResult<Vec<ScanRecord>>>,
+    {
+        // Runs are scheduled from the first start, not from the end of the
+        // previous run; a run that overruns skips the ticks it missed
+        let mut ticker = tokio::time::interval(self.interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+        let mut previous: HashMap<String, ScanRecord> = HashMap::new();
+        let mut completed = 0;
+
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => {}
+                _ = tokio::signal::ctrl_c() => break,
+            }
+
+            // Ctrl-C abandons a run in progress; its record is not saved
+            let outcome = tokio::select! {
+                outcome = run() => outcome,
+                _ = tokio::signal::ctrl_c() => break,
+            };
+            completed += 1;
+
+            match outcome {
+                Ok(records) => {
+                    if self.diff {
+                        self.print_changes(&previous, &records)?;
+                    }
+                    previous = records
+                        .into_iter()
+                        .map(|record| (record.ip_address.clone(), record))
+                        .collect();
+                }
+                Err(e) => output::print_error(&format!(
+                    "[{}] run {} failed: {}",
+                    Local::now().format("%Y-%m-%d %H:%M:%S"),
+                    completed,
+                    e
+                )),
+            }
+
+            if self.runs.is_some_and(|runs| completed >= runs) {
+                break;
+            }
+        }
+        Ok(completed)
+    }
+
+    /// Print the diff of each host against its record from the previous run.
+    fn print_changes(
+        &self,
+        previous: &HashMap<String, ScanRecord>,
+        records: &[ScanRecord],
+    ) -> CliResult<()> {
+        for record in records.iter().filter(|r| !r.is_down()) {
+            let Some(baseline) = previous.get(&record.ip_address) else {
+                continue;
+            };
+            if baseline.is_down() {
+                continue;
+            }
+            let diff = ScanDiff::between(baseline, record);
+            output::print_scan_diff(&diff, baseline, self.output != OutputFormat::Plain)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parse an interval such as "30s", "15m", "2h", or "45".
+fn parse_interval(s: &str) -> Result<Duration, String> {
+    let s = s.trim();
+    let (digits, unit) = s.split_at(s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len()));
+    let value: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid interval '{}'", s))?;
+
+    let seconds = match unit {
+        "" | "s" => value,
+        "m" => value.saturating_mul(60),
+        "h" => value.saturating_mul(3600),
+        _ => return Err(format!("invalid interval unit '{}' (use s, m, or h)", unit)),
+    };
+    if seconds == 0 {
+        return Err("interval must be at least 1s".to_string());
+    }
+    Ok(Duration::from_secs(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::CliError;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_watch_continues_after_failed_run() {
+        let watch = WatchCommand::try_parse_from([
+            "watch",
+            "--target",
+            "127.0.0.1",
+            "--interval",
+            "60",
+            "--runs",
+            "2",
+            "--diff",
+        ])
+        .unwrap();
+
+        let mut calls = 0;
+        let completed = watch
+            .watch(|| {
+                calls += 1;
+                let call = calls;
+                async move {
+                    if call == 1 {
+                        Err(CliError::Other("resolver unavailable".to_string()))
+                    } else {
+                        Ok(Vec::new())
+                    }
+                }
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(completed, 2);
+        assert_eq!(calls, 2);
+    }
+}
@@ -285,7 +285,7 @@ impl ProfileManager {
                 let entry = entry.map_err(|e| ProfileError::SaveFailed(e.to_string()))?;
                 let path = entry.path();
 
-                if path.extension().map_or(false, |ext| ext == "json") {
+                if path.extension().is_some_and(|ext| ext == "json") {
                     if let Ok(content) = fs::read_to_string(&path) {
                         if let Ok(profile) = serde_json::from_str::<Profile>(&content) {
                             self.cache.insert(profile.name.clone(), profile);
@@ -3,6 +3,7 @@
 //! Manages XDG-compliant paths for configuration, data, and cache.
 
 use crate::error::{ConfigError, ConfigResult};
+use crate::storage::StorageFormat;
 use directories::ProjectDirs;
 use serde::{Deserialize, Serialize};
 use std::fs;
@@ -53,6 +54,11 @@ impl Paths {
         self.config_dir.join("settings.json")
     }
 
+    /// Get the path to the user service name file.
+    pub fn services_file(&self) -> PathBuf {
+        self.config_dir.join("services.json")
+    }
+
     /// Get the path to the profiles directory.
     pub fn profiles_dir(&self) -> PathBuf {
         self.config_dir.join("profiles")
@@ -62,6 +68,11 @@ impl Paths {
     pub fn scans_dir(&self) -> PathBuf {
         self.data_dir.join("scans")
     }
+
+    /// Get the path to the scan sessions directory.
+    pub fn sessions_dir(&self) -> PathBuf {
+        self.data_dir.join("sessions")
+    }
 }
 
 /// Application-wide settings.
@@ -82,6 +93,12 @@ pub struct AppSettings {
     pub default_rate_limit: u32,
     /// Auto-save scan results.
     pub auto_save_scans: bool,
+    /// Refuse scans sending more probes (hosts x ports) than this, 0 for no cap.
+    pub max_probes: u64,
+    /// Encoding of saved scans: `json`, or `msgpack` for a compact history.
+    pub storage_format: StorageFormat,
+    /// Gzip saved scans (`.json.gz`); uncompressed scans still load.
+    pub compress_scans: bool,
 }
 
 impl Default for AppSettings {
@@ -94,6 +111,9 @@ impl Default for AppSettings {
             default_output_format: "plain".to_string(),
             default_rate_limit: 0,
             auto_save_scans: true,
+            max_probes: 0,
+            storage_format: StorageFormat::Json,
+            compress_scans: false,
         }
     }
 }
@@ -148,6 +168,7 @@ mod tests {
         let settings = AppSettings::default();
         assert_eq!(settings.default_concurrency, 500);
         assert_eq!(settings.default_timeout_ms, 3000);
+        assert_eq!(settings.max_probes, 0);
     }
 
     #[test]
@@ -157,4 +178,16 @@ mod tests {
         let parsed: AppSettings = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.default_concurrency, settings.default_concurrency);
     }
+
+    #[test]
+    fn test_settings_missing_fields() {
+        let parsed: AppSettings = serde_json::from_str(r#"{"max_probes": 100000}"#).unwrap();
+        assert_eq!(parsed.max_probes, 100_000);
+        assert_eq!(parsed.default_concurrency, 500);
+        assert_eq!(parsed.storage_format, StorageFormat::Json);
+        assert!(!parsed.compress_scans);
+
+        let parsed: AppSettings = serde_json::from_str(r#"{"storage_format": "msgpack"}"#).unwrap();
+        assert_eq!(parsed.storage_format, StorageFormat::Msgpack);
+    }
 }
@@ -0,0 +1,184 @@
+//! Host enrichment for scan records.
+//!
+//! Annotates a scanned host with its reverse DNS name (`--resolve-ptr`),
+//! the hardware vendor of an on-link host's MAC address, and, with the `asn`
+//! feature, the autonomous system it belongs to according to a MaxMind-format
+//! database (`--asn-db`). A lookup that fails leaves its fields empty;
+//! enrichment never fails a scan.
+
+use crate::oui::OuiTable;
+use crate::storage::ScanRecord;
+use crate::types::ResolveOptions;
+use std::net::IpAddr;
+use std::time::Duration;
+use trust_dns_resolver::config::ResolverOpts;
+use trust_dns_resolver::TokioAsyncResolver;
+
+/// How long a single PTR query may take.
+const PTR_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// An open MaxMind database with ASN records.
+#[cfg(feature = "asn")]
+pub type AsnDb = maxminddb::Reader<Vec<u8>>;
+
+/// Open a MaxMind ASN database such as GeoLite2-ASN.mmdb.
+#[cfg(feature = "asn")]
+pub fn open_asn_db(path: &std::path::Path) -> Result<AsnDb, maxminddb::MaxMindDBError> {
+    maxminddb::Reader::open_readfile(path)
+}
+
+/// Adds reverse DNS and ASN details to scan records.
+#[derive(Default)]
+pub struct Enricher {
+    resolver: Option<TokioAsyncResolver>,
+    vendors: Option<OuiTable>,
+    #[cfg(feature = "asn")]
+    asn_db: Option<AsnDb>,
+}
+
+impl Enricher {
+    /// Look up PTR records with the name servers in `options`.
+    ///
+    /// Does nothing when `options` disallow DNS queries.
+    pub fn with_ptr(mut self, options: &ResolveOptions) -> Self {
+        if options.allow_dns {
+            let mut opts = ResolverOpts::default();
+            opts.timeout = PTR_TIMEOUT;
+            opts.attempts = options.dns_retries as usize;
+            self.resolver = Some(TokioAsyncResolver::tokio(options.resolver_config(), opts));
+        }
+        self
+    }
+
+    /// Name the vendor of each host's MAC address from `table`.
+    ///
+    /// Hosts whose MAC the scan did not learn are looked up in the system's
+    /// neighbor (ARP) table where available.
+    pub fn with_vendors(mut self, table: OuiTable) -> Self {
+        self.vendors = Some(table);
+        self
+    }
+
+    /// Look up the ASN and organization of each host in `db`.
+    #[cfg(feature = "asn")]
+    pub fn with_asn_db(mut self, db: AsnDb) -> Self {
+        self.asn_db = Some(db);
+        self
+    }
+
+    /// Fill in the hostname and ASN fields of `record` where lookups succeed.
+    pub async fn enrich(&self, record: &mut ScanRecord) {
+        let Ok(ip) = record.ip_address.parse::<IpAddr>() else {
+            return;
+        };
+
+        if let Some(ref resolver) = self.resolver {
+            record.hostname = reverse_lookup(resolver, ip).await;
+        }
+
+        if let Some(ref vendors) = self.vendors {
+            if record.mac.is_none() {
+                record.mac = neighbor_mac(ip);
+            }
+            record.vendor = record
+                .mac
+                .as_deref()
+                .and_then(|mac| vendors.lookup(mac))
+                .map(str::to_string);
+        }
+
+        #[cfg(feature = "asn")]
+        if let Some(ref db) = self.asn_db {
+            match db.lookup::<maxminddb::geoip2::Asn>(ip) {
+                Ok(asn) => {
+                    record.asn = asn.autonomous_system_number;
+                    record.org = asn.autonomous_system_organization.map(str::to_string);
+                }
+                Err(e) => tracing::debug!("ASN lookup for {} failed: {}", ip, e),
+            }
+        }
+    }
+}
+
+/// MAC address of `ip` from the kernel's neighbor table.
+#[cfg(target_os = "linux")]
+fn neighbor_mac(ip: IpAddr) -> Option<String> {
+    let table = std::fs::read_to_string("/proc/net/arp").ok()?;
+    parse_arp_table(&table, ip)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn neighbor_mac(_ip: IpAddr) -> Option<String> {
+    None
+}
+
+/// Find the complete entry for `ip` in `/proc/net/arp` content.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_arp_table(table: &str, ip: IpAddr) -> Option<String> {
+    table.lines().skip(1).find_map(|line| {
+        // IP address, HW type, flags, HW address, mask, device; flags 0x0
+        // mark an incomplete entry
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        match fields[..] {
+            [addr, _, flags, mac, ..] if flags != "0x0" && addr.parse() == Ok(ip) => {
+                Some(mac.to_string())
+            }
+            _ => None,
+        }
+    })
+}
+
+/// First PTR name of `ip`, without the trailing root dot.
+async fn reverse_lookup(resolver: &TokioAsyncResolver, ip: IpAddr) -> Option<String> {
+    match resolver.reverse_lookup(ip).await {
+        Ok(names) => names
+            .iter()
+            .next()
+            .map(|name| name.to_string().trim_end_matches('.').to_string()),
+        Err(e) => {
+            tracing::debug!("PTR lookup for {} failed: {}", ip, e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::traits::ScanType;
+
+    #[tokio::test]
+    async fn test_no_lookups_without_dns() {
+        let options = ResolveOptions::default().without_dns();
+        let enricher = Enricher::default().with_ptr(&options);
+        assert!(enricher.resolver.is_none());
+
+        let mut record = ScanRecord::new("127.0.0.1", "127.0.0.1", ScanType::Connect);
+        enricher.enrich(&mut record).await;
+        assert_eq!(record.hostname, None);
+        assert_eq!(record.asn, None);
+    }
+
+    #[test]
+    fn test_parse_arp_table() {
+        let table = "IP address       HW type     Flags       HW address            Mask     Device\n\
+                     192.168.1.1      0x1         0x2         b8:27:eb:12:34:56     *        eth0\n\
+                     192.168.1.7      0x1         0x0         00:00:00:00:00:00     *        eth0\n";
+        let ip = |s: &str| s.parse::<IpAddr>().unwrap();
+        assert_eq!(
+            parse_arp_table(table, ip("192.168.1.1")).as_deref(),
+            Some("b8:27:eb:12:34:56")
+        );
+        assert_eq!(parse_arp_table(table, ip("192.168.1.7")), None);
+        assert_eq!(parse_arp_table(table, ip("192.168.1.9")), None);
+    }
+
+    #[tokio::test]
+    async fn test_vendor_from_scanned_mac() {
+        let enricher = Enricher::default().with_vendors(OuiTable::builtin());
+        let mut record = ScanRecord::new("192.0.2.1", "192.0.2.1", ScanType::Syn);
+        record.mac = Some("00:0c:29:aa:bb:cc".to_string());
+        enricher.enrich(&mut record).await;
+        assert_eq!(record.vendor.as_deref(), Some("VMware"));
+    }
+}
@@ -3,14 +3,14 @@
 //! Uses `thiserror` for ergonomic error definitions with proper
 //! error chaining and context.
 
-use crate::types::{PortError, ScanIdError, TargetError};
+use crate::types::{host_port, PortError, ScanIdError, TargetError};
 use std::path::PathBuf;
 use thiserror::Error;
 
 /// Main error type for scanning operations.
 #[derive(Error, Debug)]
 pub enum ScanError {
-    #[error("connection failed to {target}:{port}: {reason}")]
+    #[error("connection failed to {}: {reason}", host_port(target, *port))]
     ConnectionFailed {
         target: String,
         port: u16,
@@ -23,6 +23,9 @@ pub enum ScanError {
     #[error("connection refused")]
     ConnectionRefused,
 
+    #[error("out of local sockets or ports: {0}")]
+    LocalResources(String),
+
     #[error("network unreachable: {0}")]
     NetworkUnreachable(String),
 
@@ -100,8 +103,14 @@ pub enum StorageError {
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 
+    #[error("MessagePack error: {0}")]
+    Encoding(String),
+
     #[error("invalid scan ID: {0}")]
     InvalidScanId(#[from] ScanIdError),
+
+    #[error("cannot merge scans of different hosts: {0} and {1}")]
+    HostMismatch(String, String),
 }
 
 /// Error type for profile operations.
@@ -156,6 +165,12 @@ pub enum CliError {
     #[error("invalid scan ID: {0}")]
     ScanId(#[from] ScanIdError),
 
+    #[error("{0}")]
+    Unreachable(String),
+
+    #[error("{0}")]
+    Privileges(String),
+
     #[error("{0}")]
     Other(String),
 }
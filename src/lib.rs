@@ -16,6 +16,27 @@
 //! ## Example Usage
 //!
 //! ```rust,ignore
+//! use scuttle::{ScanBuilder, ScanType};
+//!
+//! #[tokio::main]
+//! async fn main() -> Result<(), scuttle::CliError> {
+//!     let record = ScanBuilder::new("192.168.1.1")
+//!         .with_ports("22,80,443".parse()?)
+//!         .with_scan_type(ScanType::Connect)
+//!         .run()
+//!         .await?;
+//!
+//!     for result in &record.results {
+//!         println!("Port {} is {}", result.port, result.status);
+//!     }
+//!     Ok(())
+//! }
+//! ```
+//!
+//! [`ScanBuilder`] handles resolution, scanner creation, and execution. For
+//! finer control, drive a scanner directly:
+//!
+//! ```rust,ignore
 //! use scuttle::scanner::{TcpConnectScanner, Scanner, ScanType};
 //! use scuttle::types::Port;
 //! use std::net::IpAddr;
@@ -47,14 +68,18 @@
 pub mod banner;
 pub mod cli;
 pub mod config;
+pub mod enrich;
 pub mod error;
+pub mod oui;
 pub mod output;
 pub mod scanner;
+#[cfg(feature = "serve")]
+pub mod server;
 pub mod services;
 pub mod storage;
 pub mod types;
 
 // Re-export commonly used types
 pub use error::{CliError, ScanError};
-pub use scanner::{PortResult, PortStatus, ScanType, Scanner};
+pub use scanner::{PortResult, PortStatus, ScanBuilder, ScanType, Scanner};
 pub use types::{Port, PortSpec, ScanId, ScanTarget, TargetSpec};
@@ -18,48 +18,63 @@
 //! # Export scan results
 //! scuttle export abc123 -f json -o results.json
 //!
+//! # Re-run a saved scan
+//! scuttle rescan abc123
+//!
 //! # View scan history
 //! scuttle history -n 20
 //!
+//! # Re-scan every 15 minutes and report changes
+//! scuttle watch --target 192.168.1.1 --interval 15m --diff
+//!
 //! # Legacy mode (backwards compatible)
 //! scuttle 192.168.1.1 -p 80,443
 //! ```
 
 use clap::Parser;
-use scuttle::cli::{Cli, Commands, HistoryCommand};
+use scuttle::cli::{Cli, Commands, HistoryAction, HistoryCommand, Outcome, OutputFormat};
+use scuttle::error::CliError;
 use scuttle::output;
-use scuttle::storage::ScanStore;
+use scuttle::storage::{ScanRecord, ScanSession, ScanStore, StorageStats};
+use std::collections::{HashMap, HashSet};
 use std::process::ExitCode;
 
 #[tokio::main]
 async fn main() -> ExitCode {
-    // Initialize tracing for debug output
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            tracing_subscriber::EnvFilter::from_default_env()
-                .add_directive(tracing::Level::WARN.into()),
-        )
-        .init();
-
     // Parse command-line arguments
     let cli = Cli::parse();
 
-    // Run the appropriate command
-    if let Err(e) = run(cli).await {
-        output::print_error(&e.to_string());
-        return ExitCode::FAILURE;
+    // Initialize tracing for debug output; `--trace` and `-vvv` are shorthand
+    // for RUST_LOG=scuttle=trace
+    let mut filter = tracing_subscriber::EnvFilter::from_default_env()
+        .add_directive(tracing::Level::WARN.into());
+    if cli.trace || cli.verbose >= 3 {
+        filter = filter.add_directive("scuttle=trace".parse().expect("valid directive"));
     }
+    tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .init();
 
-    ExitCode::SUCCESS
+    // Run the appropriate command; the exit code tells scripts how it went
+    match run(cli).await {
+        Ok(outcome) => outcome.into(),
+        Err(e) => {
+            output::print_error(&e.to_string());
+            e.downcast_ref::<CliError>()
+                .map_or(Outcome::Failure, Outcome::from)
+                .into()
+        }
+    }
 }
 
-async fn run(cli: Cli) -> anyhow::Result<()> {
+async fn run(cli: Cli) -> anyhow::Result<Outcome> {
     let verbose = cli.verbose;
     let quiet = cli.quiet;
 
     match cli.command {
         Some(Commands::Scan(cmd)) => {
-            cmd.execute(verbose, quiet).await?;
+            return Ok(cmd.execute(verbose, quiet).await?);
         }
         Some(Commands::Profiles(cmd)) => {
             cmd.execute(verbose, quiet)?;
@@ -67,28 +82,37 @@ async fn run(cli: Cli) -> anyhow::Result<()> {
         Some(Commands::Export(cmd)) => {
             cmd.execute(verbose, quiet)?;
         }
+        Some(Commands::Rescan(cmd)) => {
+            return Ok(cmd.execute(verbose, quiet).await?);
+        }
         Some(Commands::History(cmd)) => {
             execute_history(cmd, verbose, quiet)?;
         }
+        Some(Commands::Watch(cmd)) => {
+            cmd.execute(verbose, quiet).await?;
+        }
+        Some(Commands::Merge(cmd)) => {
+            cmd.execute(verbose, quiet)?;
+        }
+        Some(Commands::Diff(cmd)) => {
+            cmd.execute(verbose, quiet)?;
+        }
+        Some(Commands::Lookup(cmd)) => {
+            cmd.execute(verbose, quiet)?;
+        }
+        Some(Commands::Resolve(cmd)) => {
+            cmd.execute(verbose, quiet).await?;
+        }
+        #[cfg(feature = "serve")]
+        Some(Commands::Serve(cmd)) => {
+            cmd.execute(verbose, quiet).await?;
+        }
         None => {
             // Legacy mode: if target is provided without subcommand
             if let Some(target) = cli.legacy_target {
                 // Create a default scan command
-                let scan_cmd = scuttle::cli::ScanCommand {
-                    target,
-                    ports: "1-1000".to_string(),
-                    scan_type: scuttle::scanner::ScanType::Connect,
-                    concurrency: 500,
-                    output: scuttle::cli::OutputFormat::Plain,
-                    timeout: 3000,
-                    banner: false,
-                    show_closed: false,
-                    interface: None,
-                    rate_limit: 0,
-                    profile: None,
-                    no_save: false,
-                };
-                scan_cmd.execute(verbose, quiet).await?;
+                let scan_cmd = scuttle::cli::ScanCommand::with_defaults(target);
+                return Ok(scan_cmd.execute(verbose, quiet).await?);
             } else {
                 // No command and no target - show help
                 println!("Scuttle v{}", env!("CARGO_PKG_VERSION"));
@@ -99,20 +123,60 @@ async fn run(cli: Cli) -> anyhow::Result<()> {
                 println!("  scan      Scan a target for open ports");
                 println!("  profiles  Manage scan profiles");
                 println!("  export    Export scan results");
+                println!("  rescan    Re-run a saved scan");
                 println!("  history   View scan history");
+                println!("  watch     Re-run a scan at a fixed interval");
+                println!("  lookup    Look up well-known services and ports");
                 println!();
                 println!("Run 'scuttle --help' for more information.");
             }
         }
     }
 
-    Ok(())
+    Ok(Outcome::Success)
 }
 
 /// Execute the history command.
-fn execute_history(cmd: HistoryCommand, _verbose: bool, quiet: bool) -> anyhow::Result<()> {
+fn execute_history(cmd: HistoryCommand, _verbose: u8, quiet: bool) -> anyhow::Result<()> {
     let store = ScanStore::new()?;
 
+    if let Some(HistoryAction::Repair { dry_run }) = cmd.action {
+        let corrupt = store.repair(!dry_run)?;
+        if !quiet {
+            for file in &corrupt {
+                println!("{}: {}", file.path.display(), file.reason);
+            }
+            if corrupt.is_empty() {
+                output::print_success("No unreadable scans found");
+            } else if dry_run {
+                println!(
+                    "{} unreadable files; run without --dry-run to remove them",
+                    corrupt.len()
+                );
+            } else {
+                output::print_success(&format!("Removed {} unreadable files", corrupt.len()));
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(HistoryAction::Stats { output }) = cmd.action {
+        let stats = store.stats()?;
+        match output {
+            OutputFormat::Plain => print_stats(&stats),
+            OutputFormat::Json => output::write_json(&stats, false)?,
+            OutputFormat::JsonCompact => output::write_json(&stats, true)?,
+            OutputFormat::Jsonl => println!("{}", serde_json::to_string(&stats)?),
+            OutputFormat::Csv => {
+                return Err(CliError::InvalidArgument(
+                    "history stats output is plain, json, json-compact, or jsonl".to_string(),
+                )
+                .into())
+            }
+        }
+        return Ok(());
+    }
+
     if cmd.clear {
         // Clear all history
         if !quiet {
@@ -128,6 +192,9 @@ fn execute_history(cmd: HistoryCommand, _verbose: bool, quiet: bool) -> anyhow::
         for id in store.list_ids()? {
             store.delete(&id)?;
         }
+        for id in store.list_session_ids()? {
+            store.delete_session(&id)?;
+        }
 
         if !quiet {
             output::print_success("Scan history cleared");
@@ -144,10 +211,26 @@ fn execute_history(cmd: HistoryCommand, _verbose: bool, quiet: bool) -> anyhow::
         return Ok(());
     }
 
-    // List recent scans
-    let records = store.list_recent(cmd.count)?;
+    // List recent scans; the hosts of a range scan share one row
+    let sessions: HashMap<_, ScanSession> = store
+        .list_sessions()?
+        .into_iter()
+        .map(|session| (session.id, session))
+        .collect();
+    let records = store.list()?;
+    let mut listed = HashSet::new();
+    let entries: Vec<HistoryEntry> = records
+        .iter()
+        .filter_map(|record| match record.session_id.and_then(|id| sessions.get(&id)) {
+            Some(session) => listed
+                .insert(session.id)
+                .then_some(HistoryEntry::Session(session)),
+            None => Some(HistoryEntry::Scan(record)),
+        })
+        .take(cmd.count)
+        .collect();
 
-    if records.is_empty() {
+    if entries.is_empty() {
         if !quiet {
             println!("No scans in history.");
         }
@@ -158,7 +241,14 @@ fn execute_history(cmd: HistoryCommand, _verbose: bool, quiet: bool) -> anyhow::
         "ID", "TARGET", "DATE", "OPEN", "PORTS", "TIME");
     println!("{}", "-".repeat(80));
 
-    for record in &records {
+    for entry in &entries {
+        let record = match entry {
+            HistoryEntry::Scan(record) => record,
+            HistoryEntry::Session(session) => {
+                print_session_entry(session, cmd.detailed);
+                continue;
+            }
+        };
         let date = record.started_at.format("%Y-%m-%d %H:%M");
         let duration = format!("{:.1}s", record.duration_ms as f64 / 1000.0);
 
@@ -174,6 +264,9 @@ fn execute_history(cmd: HistoryCommand, _verbose: bool, quiet: bool) -> anyhow::
 
         if cmd.detailed {
             println!("           IP: {}, Type: {}", record.ip_address, record.scan_type);
+            if !record.port_spec.is_empty() {
+                println!("           Ports: {}", record.port_spec);
+            }
             if record.open_ports > 0 {
                 let open_ports: Vec<String> = record
                     .results
@@ -193,6 +286,82 @@ fn execute_history(cmd: HistoryCommand, _verbose: bool, quiet: bool) -> anyhow::
     Ok(())
 }
 
+/// A row of `scuttle history`: a single-host scan or a range scan session.
+enum HistoryEntry<'a> {
+    Scan(&'a ScanRecord),
+    Session(&'a ScanSession),
+}
+
+/// Print the history row of a session, and its hosts if `detailed` is set.
+fn print_session_entry(session: &ScanSession, detailed: bool) {
+    println!(
+        "{:<10} {:<20} {:<20} {:>6} {:>6} {:>8}",
+        session.id.short(),
+        truncate(&session.target, 18),
+        session.started_at.format("%Y-%m-%d %H:%M"),
+        session.open_ports,
+        session.ports_scanned,
+        format!("{:.1}s", session.duration_ms as f64 / 1000.0)
+    );
+
+    if detailed {
+        println!(
+            "           Hosts: {} ({} up, {} down), Type: {}",
+            session.hosts(),
+            session.hosts_up,
+            session.hosts_down,
+            session.scan_type
+        );
+        if !session.port_spec.is_empty() {
+            println!("           Ports: {}", session.port_spec);
+        }
+        for record in session.records.iter().filter(|r| r.open_ports > 0) {
+            let open_ports: Vec<String> = record
+                .results
+                .iter()
+                .filter(|r| r.is_open())
+                .take(10)
+                .map(|r| format!("{}/{}", r.port, r.service))
+                .collect();
+            println!("           {}: {}", record.ip_address, open_ports.join(", "));
+        }
+        println!();
+    }
+}
+
+/// Print history statistics as a table.
+fn print_stats(stats: &StorageStats) {
+    let date = |at: Option<chrono::DateTime<chrono::Utc>>| {
+        at.map_or("-".to_string(), |at| {
+            at.format("%Y-%m-%d %H:%M").to_string()
+        })
+    };
+
+    println!("Scans:          {}", stats.scan_count);
+    println!("Range scans:    {}", stats.session_count);
+    println!("Hosts:          {}", stats.host_count);
+    println!("Ports scanned:  {}", stats.total_ports_scanned);
+    println!("Open ports:     {}", stats.total_open_ports);
+    println!("Storage used:   {}", format_size(stats.total_size_bytes));
+    println!("Oldest scan:    {}", date(stats.oldest_scan));
+    println!("Newest scan:    {}", date(stats.newest_scan));
+}
+
+/// Format a byte count with a binary unit, such as `1.5 MiB`.
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
 /// Truncate a string to a maximum length.
 fn truncate(s: &str, max: usize) -> String {
     if s.len() <= max {
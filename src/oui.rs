@@ -0,0 +1,151 @@
+//! Hardware vendor lookup for MAC addresses.
+//!
+//! The first three bytes of a MAC address (the OUI) identify the vendor that
+//! registered it, which hints at the kind of device on a LAN: a printer, a
+//! Raspberry Pi, a VM. A compact table of common vendors is built in; the
+//! full registry can be loaded from the IEEE's `oui.txt` or from nmap's
+//! `nmap-mac-prefixes`, and is merged over the built-in entries.
+
+use crate::error::{ConfigError, ConfigResult};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+/// Built-in prefixes, in `nmap-mac-prefixes` format.
+const BUILTIN_PREFIXES: &str = include_str!("oui-prefixes.txt");
+
+/// Maps OUI prefixes to vendor names.
+#[derive(Debug, Clone, Default)]
+pub struct OuiTable {
+    vendors: HashMap<[u8; 3], String>,
+}
+
+impl OuiTable {
+    /// The table of common vendors shipped with Scuttle.
+    pub fn builtin() -> Self {
+        Self {
+            vendors: parse_prefixes(BUILTIN_PREFIXES).expect("built-in OUI table parses"),
+        }
+    }
+
+    /// Load a table from an IEEE `oui.txt` or `nmap-mac-prefixes` file.
+    pub fn load(path: &Path) -> ConfigResult<Self> {
+        let content = fs::read_to_string(path).map_err(|e| ConfigError::ReadFailed {
+            path: path.to_path_buf(),
+            reason: e.to_string(),
+        })?;
+        Ok(Self {
+            vendors: parse_prefixes(&content)?,
+        })
+    }
+
+    /// Add the entries of `other`, replacing any for the same prefix.
+    pub fn merge(&mut self, other: OuiTable) {
+        self.vendors.extend(other.vendors);
+    }
+
+    /// Number of prefixes in the table.
+    pub fn len(&self) -> usize {
+        self.vendors.len()
+    }
+
+    /// Check if the table has no prefixes.
+    pub fn is_empty(&self) -> bool {
+        self.vendors.is_empty()
+    }
+
+    /// Vendor of a MAC address written with `:`, `-`, or `.` separators.
+    pub fn lookup(&self, mac: &str) -> Option<&str> {
+        self.vendors.get(&parse_oui(mac)?).map(String::as_str)
+    }
+}
+
+/// Parse prefix lines, either IEEE (`00-00-0C   (hex)   Cisco Systems, Inc`)
+/// or nmap (`00000C Cisco`). Other IEEE lines, such as addresses, are skipped.
+fn parse_prefixes(content: &str) -> ConfigResult<HashMap<[u8; 3], String>> {
+    let ieee = content.contains("(hex)");
+    let mut vendors = HashMap::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let entry = if ieee {
+            match line.split_once("(hex)") {
+                Some((prefix, vendor)) => (prefix.trim(), vendor.trim()),
+                None => continue,
+            }
+        } else {
+            line.split_once(char::is_whitespace)
+                .map(|(prefix, vendor)| (prefix, vendor.trim()))
+                .unwrap_or((line, ""))
+        };
+
+        let invalid = || ConfigError::InvalidFormat(format!("line {}: '{}'", number + 1, line));
+        let oui = parse_oui(entry.0)
+            .filter(|_| !entry.1.is_empty())
+            .ok_or_else(invalid)?;
+        vendors.insert(oui, entry.1.to_string());
+    }
+
+    Ok(vendors)
+}
+
+/// First three bytes of a MAC address or bare OUI, ignoring separators.
+fn parse_oui(mac: &str) -> Option<[u8; 3]> {
+    let hex: Vec<u8> = mac
+        .bytes()
+        .filter(|b| !matches!(b, b':' | b'-' | b'.'))
+        .take(6)
+        .collect();
+    if hex.len() < 6 {
+        return None;
+    }
+
+    let byte = |i: usize| u8::from_str_radix(std::str::from_utf8(&hex[i..i + 2]).ok()?, 16).ok();
+    Some([byte(0)?, byte(2)?, byte(4)?])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_lookup() {
+        let table = OuiTable::builtin();
+        assert!(!table.is_empty());
+        assert_eq!(table.lookup("b8:27:eb:12:34:56"), Some("Raspberry Pi"));
+        assert_eq!(table.lookup("00-50-56-C0-00-08"), Some("VMware"));
+        assert_eq!(table.lookup("0800.2712.3456"), Some("Oracle VirtualBox"));
+        assert_eq!(table.lookup("ff:ff:ff:ff:ff:ff"), None);
+        assert_eq!(table.lookup("not a mac"), None);
+    }
+
+    #[test]
+    fn test_parse_ieee_format() {
+        let content = "OUI/MA-L                                                    Organization\n\
+                       00-00-0C   (hex)\t\tCisco Systems, Inc\n\
+                       00000C     (base 16)\t\tCisco Systems, Inc\n\
+                       \t\t\t\t170 WEST TASMAN DRIVE\n";
+        let vendors = parse_prefixes(content).unwrap();
+        assert_eq!(vendors.len(), 1);
+        assert_eq!(vendors[&[0x00, 0x00, 0x0c]], "Cisco Systems, Inc");
+    }
+
+    #[test]
+    fn test_parse_rejects_bad_lines() {
+        assert!(parse_prefixes("00000C Cisco\nZZZZZZ Nobody\n").is_err());
+        assert!(parse_prefixes("00000C\n").is_err());
+    }
+
+    #[test]
+    fn test_merge_overrides() {
+        let mut table = OuiTable::builtin();
+        table.merge(OuiTable {
+            vendors: parse_prefixes("B827EB Lab Pi").unwrap(),
+        });
+        assert_eq!(table.lookup("b8:27:eb:00:00:01"), Some("Lab Pi"));
+    }
+}
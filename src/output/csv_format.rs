@@ -1,7 +1,11 @@
 //! CSV output formatting.
 
+use crate::scanner::PortResult;
 use crate::storage::ScanRecord;
-use std::io;
+use std::fs::File;
+use std::io::{self, Write};
+use std::net::IpAddr;
+use std::path::Path;
 
 /// Print results in CSV format.
 pub fn print_csv(record: &ScanRecord) -> io::Result<()> {
@@ -25,3 +29,115 @@ pub fn print_csv(record: &ScanRecord) -> io::Result<()> {
     wtr.flush()?;
     Ok(())
 }
+
+/// CSV sink that writes open ports while a scan is still running.
+///
+/// The header goes out with the first row and every row is flushed
+/// immediately, so the file can be followed with `tail -f`.
+pub struct CsvStream {
+    writer: csv::Writer<Box<dyn Write + Send>>,
+    header_written: bool,
+}
+
+impl CsvStream {
+    /// Stream rows to `writer`.
+    pub fn new(writer: impl Write + Send + 'static) -> Self {
+        Self {
+            writer: csv::Writer::from_writer(Box::new(writer)),
+            header_written: false,
+        }
+    }
+
+    /// Stream rows to standard output.
+    pub fn stdout() -> Self {
+        Self::new(io::stdout())
+    }
+
+    /// Stream rows to a new file at `path`, replacing any existing file.
+    pub fn create(path: &Path) -> io::Result<Self> {
+        Ok(Self::new(File::create(path)?))
+    }
+
+    /// Write a row for `result` if the port is open; other results are skipped.
+    pub fn write_result(&mut self, host: IpAddr, result: &PortResult) -> io::Result<()> {
+        if !result.is_open() {
+            return Ok(());
+        }
+
+        if !self.header_written {
+            self.writer.write_record([
+                "host",
+                "port",
+                "status",
+                "service",
+                "banner",
+                "response_time_ms",
+            ])?;
+            self.header_written = true;
+        }
+
+        self.writer.write_record([
+            &host.to_string(),
+            &result.port.to_string(),
+            &result.status.to_string(),
+            &result.service,
+            result.banner.as_deref().unwrap_or(""),
+            &result
+                .response_time_ms
+                .map_or(String::new(), |t| t.to_string()),
+        ])?;
+        self.writer.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::PortStatus;
+    use crate::types::Port;
+    use std::net::Ipv4Addr;
+    use std::sync::{Arc, Mutex};
+
+    /// Writer that keeps what was written for inspection.
+    #[derive(Clone, Default)]
+    struct Shared(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Shared {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_csv_stream_writes_header_once() {
+        let buffer = Shared::default();
+        let mut stream = CsvStream::new(buffer.clone());
+        let host = IpAddr::V4(Ipv4Addr::LOCALHOST);
+        let result = |port, status| PortResult::new(Port::new(port).unwrap(), status, "svc");
+
+        stream
+            .write_result(host, &result(22, PortStatus::Closed))
+            .unwrap();
+        assert!(buffer.0.lock().unwrap().is_empty());
+
+        stream
+            .write_result(host, &result(80, PortStatus::Open))
+            .unwrap();
+        // Rows are flushed as they are written
+        assert_eq!(
+            String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap(),
+            "host,port,status,service,banner,response_time_ms\n127.0.0.1,80,open,svc,,\n"
+        );
+
+        stream
+            .write_result(host, &result(443, PortStatus::Open))
+            .unwrap();
+        let written = String::from_utf8(buffer.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(written.matches("host,port").count(), 1);
+        assert!(written.ends_with("127.0.0.1,443,open,svc,,\n"));
+    }
+}
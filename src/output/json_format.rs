@@ -1,12 +1,42 @@
-//! JSON output formatting.
+//! JSON and JSON Lines output formatting.
 
 use crate::storage::ScanRecord;
-use std::io;
+use serde::Serialize;
+use std::io::{self, Write};
+
+/// Write `value` to stdout as JSON and a newline, compact or pretty-printed.
+///
+/// Serializes straight into a buffered lock on stdout instead of building
+/// the whole document as a string first; a record of every port runs to
+/// megabytes.
+pub fn write_json<T: Serialize + ?Sized>(value: &T, compact: bool) -> io::Result<()> {
+    let mut out = io::BufWriter::new(io::stdout().lock());
+    if compact {
+        serde_json::to_writer(&mut out, value)
+    } else {
+        serde_json::to_writer_pretty(&mut out, value)
+    }
+    .map_err(io::Error::other)?;
+    writeln!(out)?;
+    out.flush()
+}
 
 /// Print results in JSON format.
 pub fn print_json(record: &ScanRecord) -> io::Result<()> {
-    let json = serde_json::to_string_pretty(record)
-        .map_err(|e| io::Error::new(io::ErrorKind::Other, e))?;
-    println!("{}", json);
-    Ok(())
+    write_json(record, false)
+}
+
+/// Print results as compact JSON, on one line without whitespace.
+pub fn print_json_compact(record: &ScanRecord) -> io::Result<()> {
+    write_json(record, true)
+}
+
+/// Print several records as one JSON array.
+pub fn print_json_records(records: &[ScanRecord]) -> io::Result<()> {
+    write_json(records, false)
+}
+
+/// Print a record as a single line of compact JSON (JSON Lines).
+pub fn print_json_line(record: &ScanRecord) -> io::Result<()> {
+    write_json(record, true)
 }
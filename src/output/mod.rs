@@ -5,12 +5,25 @@
 mod csv_format;
 mod json_format;
 mod plain;
+mod port_list;
+mod session;
+#[cfg(unix)]
+mod sockets;
+mod template;
 
-pub use csv_format::print_csv;
-pub use json_format::print_json;
+pub use csv_format::{print_csv, CsvStream};
+pub use json_format::{
+    print_json, print_json_compact, print_json_line, print_json_records, write_json,
+};
 pub use plain::{
-    print_error, print_info, print_results, print_scan_header, print_success, print_warning,
+    print_error, print_info, print_results, print_scan_diff, print_scan_header, print_scan_types,
+    print_session_diff, print_success, print_transcript, print_warning, PlainOptions, SortKey,
 };
+pub use port_list::write_open_ports;
+pub use session::{print_session, write_session_csv};
+#[cfg(unix)]
+pub use sockets::print_socket_scan;
+pub use template::{Template, TemplateError};
 
 use crate::cli::OutputFormat;
 use crate::storage::ScanRecord;
@@ -19,8 +32,10 @@ use std::io;
 /// Format and print scan results according to the specified format.
 pub fn format_results(record: &ScanRecord, format: OutputFormat) -> io::Result<()> {
     match format {
-        OutputFormat::Plain => plain::print_plain(record),
+        OutputFormat::Plain => plain::print_plain(record, &PlainOptions::default()),
         OutputFormat::Json => json_format::print_json(record),
+        OutputFormat::JsonCompact => json_format::print_json_compact(record),
+        OutputFormat::Jsonl => json_format::print_json_line(record),
         OutputFormat::Csv => csv_format::print_csv(record),
     }
 }
@@ -3,22 +3,89 @@
 //! Produces human-readable output with colors and formatting.
 
 use crate::cli::OutputFormat;
-use crate::scanner::PortStatus;
-use crate::storage::ScanRecord;
+use crate::scanner::{HostState, PortResult, PortStatus, ScanType, ScannerInfo};
+use crate::services::ServiceSource;
+use crate::storage::{HostChange, ScanDiff, ScanRecord, SessionDiff};
 use console::{style, Style};
+use std::cmp::Ordering;
 use std::io::{self, Write};
 
+/// Display options for plain output; JSON and CSV are always complete.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlainOptions {
+    /// Show at most this many ports, preferring open ones.
+    pub limit: Option<usize>,
+    /// Row order for plain and CSV output (stored records stay in port order).
+    pub sort: SortKey,
+    /// Show why the scanner failed to probe a port under its row.
+    pub show_errors: bool,
+}
+
+/// Order in which port rows are displayed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortKey {
+    /// Port number
+    #[default]
+    Port,
+    /// Service name, grouping identical services
+    Service,
+    /// Status, open ports first
+    Status,
+    /// Response time, fastest first
+    ResponseTime,
+}
+
+impl SortKey {
+    /// Compare two results by this key, then by port so output is stable.
+    pub fn compare(self, a: &PortResult, b: &PortResult) -> Ordering {
+        let primary = match self {
+            Self::Port => Ordering::Equal,
+            Self::Service => {
+                // Unidentified services sort last
+                (a.service == "unknown", &a.service).cmp(&(b.service == "unknown", &b.service))
+            }
+            Self::Status => status_rank(a.status).cmp(&status_rank(b.status)),
+            Self::ResponseTime => {
+                let time = |r: &PortResult| r.response_time_ms.unwrap_or(u64::MAX);
+                time(a).cmp(&time(b))
+            }
+        };
+        primary.then(a.port.cmp(&b.port))
+    }
+}
+
+/// Rank of a status when sorting open-first.
+fn status_rank(status: PortStatus) -> u8 {
+    match status {
+        PortStatus::Open => 0,
+        PortStatus::OpenFiltered => 1,
+        PortStatus::Filtered => 2,
+        PortStatus::Closed => 3,
+    }
+}
+
 /// Format and print scan results.
-pub fn print_results(record: &ScanRecord, format: OutputFormat) -> io::Result<()> {
+pub fn print_results(
+    record: &ScanRecord,
+    format: OutputFormat,
+    options: &PlainOptions,
+) -> io::Result<()> {
     match format {
-        OutputFormat::Plain => print_plain(record),
+        OutputFormat::Plain => print_plain(record, options),
         OutputFormat::Json => super::json_format::print_json(record),
+        OutputFormat::JsonCompact => super::json_format::print_json_compact(record),
+        OutputFormat::Jsonl => super::json_format::print_json_line(record),
+        OutputFormat::Csv if options.sort != SortKey::Port => {
+            let mut sorted = record.clone();
+            sorted.results.sort_by(|a, b| options.sort.compare(a, b));
+            super::csv_format::print_csv(&sorted)
+        }
         OutputFormat::Csv => super::csv_format::print_csv(record),
     }
 }
 
 /// Print results in human-readable plain text format.
-pub fn print_plain(record: &ScanRecord) -> io::Result<()> {
+pub fn print_plain(record: &ScanRecord, options: &PlainOptions) -> io::Result<()> {
     let stdout = io::stdout();
     let mut out = stdout.lock();
 
@@ -51,18 +118,74 @@ pub fn print_plain(record: &ScanRecord) -> io::Result<()> {
         style("IP Address:").bold(),
         record.ip_address
     )?;
+    if record.local_host {
+        writeln!(out, "  {} this machine", style("Local Host:").bold())?;
+    }
+    if let Some(ref hostname) = record.hostname {
+        writeln!(out, "  {} {}", style("Hostname:").bold(), hostname)?;
+    }
+    match (record.asn, &record.org) {
+        (Some(asn), Some(org)) => writeln!(out, "  {} AS{} {}", style("ASN:").bold(), asn, org)?,
+        (Some(asn), None) => writeln!(out, "  {} AS{}", style("ASN:").bold(), asn)?,
+        (None, Some(org)) => writeln!(out, "  {} {}", style("Org:").bold(), org)?,
+        (None, None) => {}
+    }
+    if let Some(ref mac) = record.mac {
+        match record.vendor {
+            Some(ref vendor) => writeln!(out, "  {} {} ({})", style("MAC:").bold(), mac, vendor)?,
+            None => writeln!(out, "  {} {}", style("MAC:").bold(), mac)?,
+        }
+    }
+    if let Some(ref source_ip) = record.source_ip {
+        match record.interface {
+            Some(ref iface) => {
+                writeln!(out, "  {} {} ({})", style("Source:").bold(), source_ip, iface)?
+            }
+            None => writeln!(out, "  {} {}", style("Source:").bold(), source_ip)?,
+        }
+    }
+    if !record.route.is_empty() {
+        let route: Vec<String> = record.route.iter().map(ToString::to_string).collect();
+        writeln!(out, "  {} {}", style("Route:").bold(), route.join(" -> "))?;
+        if let Some(hop) = record.last_hop() {
+            writeln!(
+                out,
+                "  {} {} (host did not answer)",
+                style("Last Hop:").bold(),
+                hop
+            )?;
+        }
+    }
     writeln!(
         out,
         "  {} {}",
         style("Scan Type:").bold(),
         record.scan_type
     )?;
+    if !record.port_spec.is_empty() {
+        writeln!(out, "  {} {}", style("Ports:").bold(), record.port_spec)?;
+    }
     writeln!(
         out,
         "  {} {}",
         style("Scan ID:").bold(),
         style(record.id.short()).dim()
     )?;
+    match record.host_state {
+        HostState::Up => writeln!(
+            out,
+            "  {} {}",
+            style("Host State:").bold(),
+            style("up").green()
+        )?,
+        HostState::Down => writeln!(
+            out,
+            "  {} {}",
+            style("Host State:").bold(),
+            style("DOWN").red().bold()
+        )?,
+        HostState::Unknown => {}
+    }
     writeln!(out)?;
 
     // Statistics
@@ -80,6 +203,27 @@ pub fn print_plain(record: &ScanRecord) -> io::Result<()> {
         style(record.closed_ports).red(),
         style(record.filtered_ports).yellow()
     )?;
+    if let Some(percent) = record.sampled {
+        writeln!(
+            out,
+            "               {}",
+            style(format!(
+                "sampled {}% of the ports; coverage is partial",
+                percent
+            ))
+            .yellow()
+        )?;
+    }
+    if let Some(stats) = record.probe_stats {
+        let loss = record
+            .packet_loss
+            .map_or("unknown".to_string(), |loss| format!("~{:.0}%", loss));
+        writeln!(
+            out,
+            "               {} SYNs sent, {} retransmitted, packet loss {}",
+            stats.sent, stats.retransmissions, loss
+        )?;
+    }
     writeln!(out)?;
 
     // Port table
@@ -108,8 +252,21 @@ pub fn print_plain(record: &ScanRecord) -> io::Result<()> {
                 .dim()
         )?;
 
+        // With banners grabbed, mark names that are only port-number guesses
+        let mark_guesses = record.config.as_ref().is_some_and(|c| c.banner_grab);
+
+        let mut shown = visible_results(&record.results, options.limit);
+        shown.sort_by(|a, b| options.sort.compare(a, b));
+
+        // Room for "65535/udp" on merged TCP and UDP results
+        let port_width = if record.results.iter().any(|r| r.protocol.is_some()) {
+            9
+        } else {
+            6
+        };
+
         // Port results
-        for result in &record.results {
+        for result in &shown {
             let status_style = match result.status {
                 PortStatus::Open | PortStatus::OpenFiltered => Style::new().green().bold(),
                 PortStatus::Closed => Style::new().red(),
@@ -119,17 +276,39 @@ pub fn print_plain(record: &ScanRecord) -> io::Result<()> {
             let banner_display = result
                 .banner
                 .as_ref()
-                .map(|b| truncate_string(b, 35))
+                .map(|b| truncate_string(&b.replace('\n', " | "), 35))
                 .unwrap_or_default();
 
             writeln!(
                 out,
-                "  {:>6}  {:^14}  {:<15}  {}",
-                result.port,
-                status_style.apply_to(&result.status.to_string()),
-                result.service,
+                "  {:>port_width$}  {:^14}  {:<15}  {}",
+                port_display(result),
+                status_style.apply_to(&status_display(result)),
+                service_display(result, mark_guesses),
                 style(banner_display).dim()
             )?;
+            if let Some(error) = result.error.as_ref().filter(|_| options.show_errors) {
+                writeln!(
+                    out,
+                    "  {:>port_width$}  {}",
+                    "",
+                    style(format!("error: {}", error)).red().dim()
+                )?;
+            }
+        }
+
+        let hidden = record.results.len() - shown.len();
+        if hidden > 0 {
+            writeln!(
+                out,
+                "  {}",
+                style(format!(
+                    "... and {} more (use -o json or `scuttle export {}` for all)",
+                    hidden,
+                    record.id.short()
+                ))
+                .dim()
+            )?;
         }
 
         writeln!(
@@ -152,6 +331,230 @@ pub fn print_plain(record: &ScanRecord) -> io::Result<()> {
     Ok(())
 }
 
+/// Results to show under `limit`: open ports first, then the rest, in port order.
+fn visible_results(results: &[PortResult], limit: Option<usize>) -> Vec<&PortResult> {
+    let mut shown: Vec<&PortResult> = results.iter().collect();
+    if let Some(limit) = limit.filter(|&limit| limit < results.len()) {
+        shown.sort_by_key(|r| (!r.is_open(), r.port));
+        shown.truncate(limit);
+        shown.sort_by_key(|r| r.port);
+    }
+    shown
+}
+
+/// Port status, marking refusals flagged as fast resets.
+fn status_display(result: &PortResult) -> String {
+    if result.fast_reset {
+        format!("{} (fast)", result.status)
+    } else {
+        result.status.to_string()
+    }
+}
+
+/// Port number, suffixed with the protocol on merged TCP and UDP results.
+fn port_display(result: &PortResult) -> String {
+    match result.protocol {
+        Some(ref protocol) => format!("{}/{}", result.port, protocol),
+        None => result.port.to_string(),
+    }
+}
+
+/// Service name, suffixed with `?` when it is a port-number guess and `mark_guesses` is set.
+fn service_display(result: &PortResult, mark_guesses: bool) -> String {
+    let guessed = result.service_source == ServiceSource::Guessed && result.service != "unknown";
+    if mark_guesses && guessed {
+        format!("{}?", result.service)
+    } else {
+        result.service.clone()
+    }
+}
+
+/// Print the available scan types as a table.
+pub fn print_scan_types(infos: &[ScannerInfo]) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    writeln!(
+        out,
+        "  {:<8}  {:<8}  {:<10}  {:<10}  {:<6}  {:>4}  {}",
+        style("TYPE").bold(),
+        style("PROTO").bold(),
+        style("ROOT").bold(),
+        style("FAMILIES").bold(),
+        style("BANNER").bold(),
+        style("CONC").bold(),
+        style("DESCRIPTION").bold()
+    )?;
+    for info in infos {
+        let root = if info.requires_privileges {
+            style("required").yellow()
+        } else {
+            style("no").green()
+        };
+        writeln!(
+            out,
+            "  {:<8}  {:<8}  {:<10}  {:<10}  {:<6}  {:>4}  {}",
+            info.scan_type.as_str(),
+            info.protocol,
+            root,
+            info.address_families.join("/"),
+            if info.supports_banner { "yes" } else { "no" },
+            info.default_concurrency,
+            style(info.description).dim()
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Print the changes since a baseline scan, to stderr if `stderr` is set.
+pub fn print_scan_diff(diff: &ScanDiff, baseline: &ScanRecord, stderr: bool) -> io::Result<()> {
+    let mut out: Box<dyn Write> = if stderr {
+        Box::new(io::stderr().lock())
+    } else {
+        Box::new(io::stdout().lock())
+    };
+    let since = format!(
+        "scan {} ({})",
+        baseline.id.short(),
+        baseline.started_at.format("%Y-%m-%d %H:%M")
+    );
+
+    if diff.is_empty() {
+        writeln!(out, "  {} No changes since {}", style("Compare:").bold(), since)?;
+        return writeln!(out);
+    }
+
+    writeln!(out, "  {} Changes since {}", style("Compare:").bold(), since)?;
+    write_port_changes(&mut out, diff, protocol_of(&baseline.scan_type), "    ")?;
+    writeln!(out)
+}
+
+/// Print the host-level changes between two scans of a range.
+///
+/// `since` describes the baseline, e.g. "session 1a2b3c4d (2024-05-01 09:00)".
+pub fn print_session_diff(diff: &SessionDiff, scan_type: &str, since: &str) -> io::Result<()> {
+    let mut out = io::stdout().lock();
+    let protocol = protocol_of(scan_type);
+
+    let summary = if diff.is_empty() {
+        "No changes"
+    } else {
+        "Changes"
+    };
+    writeln!(
+        out,
+        "  {} {} since {}",
+        style("Compare:").bold(),
+        summary,
+        since
+    )?;
+
+    let ports = |host: &HostChange| {
+        host.open_ports
+            .iter()
+            .map(|port| format!("{}/{}", port, protocol))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    for host in &diff.new_hosts {
+        writeln!(
+            out,
+            "    {} {:<39}  {}  {}",
+            style("+").green().bold(),
+            host.ip,
+            style(format!("{:<4}", "new")).green(),
+            ports(host)
+        )?;
+    }
+    for host in &diff.gone_hosts {
+        writeln!(
+            out,
+            "    {} {:<39}  {}  {}",
+            style("-").red().bold(),
+            host.ip,
+            style(format!("{:<4}", "gone")).red(),
+            style(ports(host)).dim()
+        )?;
+    }
+    for host in &diff.changed_hosts {
+        writeln!(out, "    {} {}", style("~").yellow().bold(), host.ip)?;
+        write_port_changes(&mut out, &host.diff, protocol, "        ")?;
+    }
+
+    if !diff.not_compared.is_empty() {
+        writeln!(
+            out,
+            "  {}",
+            style(format!(
+                "{} hosts scanned by only one of the scans were not compared",
+                diff.not_compared.len()
+            ))
+            .dim()
+        )?;
+    }
+    writeln!(out)
+}
+
+/// Transport protocol label for a stored scan type.
+fn protocol_of(scan_type: &str) -> &'static str {
+    scan_type
+        .parse::<ScanType>()
+        .map_or("tcp", ScanType::protocol)
+}
+
+/// Write one line per opened, closed, or re-bannered port.
+fn write_port_changes(
+    out: &mut impl Write,
+    diff: &ScanDiff,
+    protocol: &str,
+    indent: &str,
+) -> io::Result<()> {
+    for change in &diff.newly_open {
+        writeln!(
+            out,
+            "{}{} {:>5}/{}  {} {} (was {})",
+            indent,
+            style("+").green().bold(),
+            change.port,
+            protocol,
+            style(format!("{:<8}", change.after.to_string())).green(),
+            change.service,
+            change.before
+        )?;
+    }
+    for change in &diff.newly_closed {
+        writeln!(
+            out,
+            "{}{} {:>5}/{}  {} {} (was {})",
+            indent,
+            style("-").red().bold(),
+            change.port,
+            protocol,
+            style(format!("{:<8}", change.after.to_string())).red(),
+            change.service,
+            change.before
+        )?;
+    }
+    for change in &diff.banner_changes {
+        let banner = |b: &Option<String>| {
+            b.as_deref()
+                .map_or("none".to_string(), |b| truncate_string(&b.replace('\n', " | "), 30))
+        };
+        writeln!(
+            out,
+            "{}{} {:>5}/{}  banner   {} -> {}",
+            indent,
+            style("~").yellow().bold(),
+            change.port,
+            protocol,
+            style(banner(&change.before)).dim(),
+            banner(&change.after)
+        )?;
+    }
+    Ok(())
+}
+
 /// Print a scan header before scanning begins.
 pub fn print_scan_header(target: &str, ip: &str, scan_type: &str, ports: usize) {
     println!();
@@ -200,6 +603,22 @@ pub fn print_info(msg: &str) {
     println!("{} {}", style("ℹ").blue().bold(), msg);
 }
 
+/// Print the transcript of an `--interact` exchange under a heading.
+pub fn print_transcript(port: u16, transcript: &str) {
+    println!("  {} {}", style("Transcript:").bold(), port);
+    for line in transcript.lines() {
+        let styled = if line.starts_with('>') {
+            style(line).cyan()
+        } else if line.starts_with('!') {
+            style(line).red()
+        } else {
+            style(line).dim()
+        };
+        println!("    {}", styled);
+    }
+    println!();
+}
+
 /// Truncate a string to a maximum length, adding ellipsis if truncated.
 fn truncate_string(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
@@ -213,6 +632,64 @@ fn truncate_string(s: &str, max_len: usize) -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_visible_results_prefers_open() {
+        let results: Vec<PortResult> = [
+            (21, PortStatus::Filtered),
+            (22, PortStatus::Open),
+            (23, PortStatus::Filtered),
+            (80, PortStatus::Open),
+        ]
+        .into_iter()
+        .map(|(port, status)| PortResult::new(crate::types::Port::new(port).unwrap(), status, ""))
+        .collect();
+
+        let ports =
+            |shown: Vec<&PortResult>| shown.iter().map(|r| r.port.as_u16()).collect::<Vec<_>>();
+        assert_eq!(ports(visible_results(&results, Some(3))), vec![21, 22, 80]);
+        assert_eq!(ports(visible_results(&results, Some(1))), vec![22]);
+        assert_eq!(ports(visible_results(&results, None)).len(), 4);
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let result = |port, status, service: &str, time| {
+            let r = PortResult::new(crate::types::Port::new(port).unwrap(), status, service);
+            match time {
+                Some(t) => r.with_response_time(t),
+                None => r,
+            }
+        };
+        let mut results = vec![
+            result(8080, PortStatus::Filtered, "http-proxy", None),
+            result(443, PortStatus::Open, "https", Some(9)),
+            result(80, PortStatus::Open, "http", Some(2)),
+            result(9999, PortStatus::Open, "unknown", Some(5)),
+            result(81, PortStatus::Closed, "http", None),
+        ];
+        let order = |results: &mut Vec<PortResult>, key: SortKey| {
+            results.sort_by(|a, b| key.compare(a, b));
+            results.iter().map(|r| r.port.as_u16()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            order(&mut results, SortKey::Port),
+            vec![80, 81, 443, 8080, 9999]
+        );
+        assert_eq!(
+            order(&mut results, SortKey::Service),
+            vec![80, 81, 8080, 443, 9999]
+        );
+        assert_eq!(
+            order(&mut results, SortKey::Status),
+            vec![80, 443, 9999, 8080, 81]
+        );
+        assert_eq!(
+            order(&mut results, SortKey::ResponseTime),
+            vec![80, 9999, 443, 81, 8080]
+        );
+    }
+
     #[test]
     fn test_truncate_string() {
         assert_eq!(truncate_string("hello", 10), "hello");
@@ -0,0 +1,90 @@
+//! Bare lists of open ports for piping into other tools.
+
+use crate::storage::ScanSession;
+use crate::types::host_port;
+use std::collections::BTreeSet;
+use std::io::{self, Write};
+
+/// Write the open ports of a session, one per line.
+///
+/// A single host gives bare port numbers and several hosts give `ip:port`
+/// pairs (`[ip]:port` for IPv6). With `unique`, each port number is written
+/// once, in ascending order, whichever hosts it was open on.
+pub fn write_open_ports(
+    session: &ScanSession,
+    mut writer: impl Write,
+    unique: bool,
+) -> io::Result<()> {
+    let open = session.records.iter().flat_map(|record| {
+        record
+            .results
+            .iter()
+            .filter(|r| r.is_open())
+            .map(move |r| (record.ip_address.as_str(), r.port.as_u16()))
+    });
+
+    if unique {
+        for port in open.map(|(_, port)| port).collect::<BTreeSet<_>>() {
+            writeln!(writer, "{}", port)?;
+        }
+    } else if session.records.len() > 1 {
+        for (ip, port) in open {
+            writeln!(writer, "{}", host_port(ip, port))?;
+        }
+    } else {
+        for (_, port) in open {
+            writeln!(writer, "{}", port)?;
+        }
+    }
+
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::traits::{PortResult, PortStatus, ScanType};
+    use crate::storage::ScanRecord;
+    use crate::types::Port;
+
+    fn session(hosts: &[(&str, &[u16])]) -> ScanSession {
+        let records = hosts
+            .iter()
+            .map(|(ip, open)| {
+                let mut results: Vec<PortResult> = open
+                    .iter()
+                    .map(|&port| PortResult::new(Port::new(port).unwrap(), PortStatus::Open, "svc"))
+                    .collect();
+                results.push(PortResult::new(
+                    Port::new(9).unwrap(),
+                    PortStatus::Closed,
+                    "discard",
+                ));
+                ScanRecord::new("target", *ip, ScanType::Connect).finalize(results, 0)
+            })
+            .collect();
+        ScanSession::new("target", ScanType::Connect).finalize(records)
+    }
+
+    fn written(session: &ScanSession, unique: bool) -> String {
+        let mut buffer = Vec::new();
+        write_open_ports(session, &mut buffer, unique).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_single_host_lists_ports() {
+        let session = session(&[("10.0.0.1", &[22, 443])]);
+        assert_eq!(written(&session, false), "22\n443\n");
+    }
+
+    #[test]
+    fn test_hosts_listed_with_ports() {
+        let session = session(&[("10.0.0.1", &[22, 80]), ("::1", &[80]), ("10.0.0.3", &[])]);
+        assert_eq!(
+            written(&session, false),
+            "10.0.0.1:22\n10.0.0.1:80\n[::1]:80\n"
+        );
+        assert_eq!(written(&session, true), "22\n80\n");
+    }
+}
@@ -0,0 +1,145 @@
+//! Output for multi-host scan sessions.
+
+use super::{PlainOptions, SortKey};
+use crate::cli::OutputFormat;
+use crate::scanner::HostState;
+use crate::storage::ScanSession;
+use console::style;
+use std::io::{self, Write};
+
+/// Open ports listed per host in the plain summary.
+const MAX_LISTED_PORTS: usize = 8;
+
+/// Print a session summary in the requested format.
+///
+/// Plain output is a per-host table; JSON and CSV include every result,
+/// with CSV rows in `options.sort` order. JSON Lines has one line per host
+/// record.
+pub fn print_session(
+    session: &ScanSession,
+    format: OutputFormat,
+    options: &PlainOptions,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Plain => print_plain(session),
+        OutputFormat::Json => super::write_json(session, false),
+        OutputFormat::JsonCompact => super::write_json(session, true),
+        OutputFormat::Jsonl => {
+            for record in &session.records {
+                super::json_format::print_json_line(record)?;
+            }
+            Ok(())
+        }
+        OutputFormat::Csv => write_session_csv(session, options.sort, io::stdout().lock()),
+    }
+}
+
+fn print_plain(session: &ScanSession) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "  {} {}  {}",
+        style("Session:").bold(),
+        session.id.short(),
+        session.target
+    )?;
+    writeln!(
+        out,
+        "  {} {} hosts ({} up, {} down), {} open ports in {:.2}s",
+        style("Statistics:").bold(),
+        session.hosts(),
+        session.hosts_up,
+        session.hosts_down,
+        style(session.open_ports).green(),
+        session.duration_ms as f64 / 1000.0
+    )?;
+    writeln!(out)?;
+
+    writeln!(
+        out,
+        "  {}  {}  {}  {}",
+        style(format!("{:<39}", "HOST")).bold(),
+        style(format!("{:<5}", "STATE")).bold(),
+        style(format!("{:>4}", "OPEN")).bold(),
+        style("PORTS").bold()
+    )?;
+    for record in &session.records {
+        let host = format!("{:<39}", record.ip_address);
+        if record.is_down() {
+            writeln!(out, "  {}  {}", host, style("down").dim())?;
+            continue;
+        }
+
+        let open: Vec<String> = record
+            .results
+            .iter()
+            .filter(|r| r.is_open())
+            .map(|r| format!("{}/{}", r.port, r.service))
+            .collect();
+        let mut ports = open
+            .iter()
+            .take(MAX_LISTED_PORTS)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if open.len() > MAX_LISTED_PORTS {
+            ports.push_str(&format!(", +{} more", open.len() - MAX_LISTED_PORTS));
+        }
+        writeln!(
+            out,
+            "  {}  {}  {:>4}  {}",
+            host,
+            match record.host_state {
+                HostState::Up => style(format!("{:<5}", "up")).green(),
+                // Scanned without discovery, so not known to be up
+                _ => style(format!("{:<5}", "?")).dim(),
+            },
+            record.open_ports,
+            ports
+        )?;
+    }
+    writeln!(out)?;
+
+    Ok(())
+}
+
+/// Write every result of a session as CSV rows, with the host in the first
+/// column and each host's rows in `sort` order.
+pub fn write_session_csv(
+    session: &ScanSession,
+    sort: SortKey,
+    writer: impl Write,
+) -> io::Result<()> {
+    let mut wtr = csv::Writer::from_writer(writer);
+
+    wtr.write_record([
+        "host",
+        "port",
+        "status",
+        "service",
+        "banner",
+        "response_time_ms",
+    ])?;
+    for record in &session.records {
+        let mut results: Vec<_> = record.results.iter().collect();
+        results.sort_by(|a, b| sort.compare(a, b));
+        for result in results {
+            wtr.write_record([
+                &record.ip_address,
+                &result.port.to_string(),
+                &result.status.to_string(),
+                &result.service,
+                result.banner.as_deref().unwrap_or(""),
+                &result
+                    .response_time_ms
+                    .map_or(String::new(), |t| t.to_string()),
+            ])?;
+        }
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
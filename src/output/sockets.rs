@@ -0,0 +1,95 @@
+//! Output for Unix domain socket scans.
+
+use crate::cli::OutputFormat;
+use crate::scanner::{PortStatus, SocketScanRecord};
+use console::style;
+use std::io::{self, Write};
+
+/// Print a socket scan in the requested format.
+pub fn print_socket_scan(record: &SocketScanRecord, format: OutputFormat) -> io::Result<()> {
+    match format {
+        OutputFormat::Plain => print_plain(record),
+        OutputFormat::Json => super::write_json(record, false),
+        OutputFormat::JsonCompact | OutputFormat::Jsonl => super::write_json(record, true),
+        OutputFormat::Csv => print_csv(record),
+    }
+}
+
+fn print_plain(record: &SocketScanRecord) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    writeln!(out)?;
+    writeln!(
+        out,
+        "  {} {}",
+        style("Directory:").bold(),
+        record.directory.display()
+    )?;
+    writeln!(
+        out,
+        "  {} {} sockets found, {} accepting in {:.2}s",
+        style("Statistics:").bold(),
+        record.sockets_found,
+        style(record.accepting).green(),
+        record.duration_ms as f64 / 1000.0
+    )?;
+    writeln!(out)?;
+
+    if record.results.is_empty() {
+        writeln!(out, "  {}", style("No sockets found").yellow())?;
+        return Ok(());
+    }
+
+    writeln!(
+        out,
+        "  {}  {}",
+        style(format!("{:<10}", "STATE")).bold(),
+        style("PATH").bold()
+    )?;
+    for result in &record.results {
+        let state = format!("{:<10}", result.status.to_string());
+        let state = match result.status {
+            PortStatus::Open => style(state).green(),
+            PortStatus::Closed => style(state).red(),
+            PortStatus::Filtered | PortStatus::OpenFiltered => style(state).yellow(),
+        };
+        let detail = result
+            .banner
+            .as_deref()
+            .or(result.error.as_deref())
+            .map(|d| format!("  {}", d.replace('\n', " | ")))
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "  {}  {}{}",
+            state,
+            result.path.display(),
+            style(detail).dim()
+        )?;
+    }
+    writeln!(out)?;
+
+    Ok(())
+}
+
+fn print_csv(record: &SocketScanRecord) -> io::Result<()> {
+    let stdout = io::stdout();
+    let mut wtr = csv::Writer::from_writer(stdout.lock());
+
+    wtr.write_record(["path", "status", "banner", "response_time_ms", "error"])?;
+    for result in &record.results {
+        wtr.write_record([
+            &result.path.display().to_string(),
+            &result.status.to_string(),
+            result.banner.as_deref().unwrap_or(""),
+            &result
+                .response_time_ms
+                .map_or(String::new(), |t| t.to_string()),
+            result.error.as_deref().unwrap_or(""),
+        ])?;
+    }
+
+    wtr.flush()?;
+    Ok(())
+}
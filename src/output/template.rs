@@ -0,0 +1,258 @@
+//! User-defined one-line-per-port output.
+//!
+//! A template such as `{addr} {status} {service}` is written once for every
+//! port result. `{{` and `}}` stand for literal braces, and `\n`, `\t`,
+//! and `\\` for a newline, a tab, and a backslash.
+
+use crate::scanner::{PortResult, ScanType};
+use crate::storage::ScanRecord;
+use crate::types::host_port;
+use std::io::{self, Write};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Errors from parsing a template.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TemplateError {
+    #[error("unknown field {{{name}}}; valid fields are {}", Field::names())]
+    UnknownField { name: String },
+
+    #[error("unclosed {{ at position {0} (write {{{{ for a literal brace)")]
+    Unclosed(usize),
+
+    #[error("unmatched }} at position {0} (write }}}} for a literal brace)")]
+    Unmatched(usize),
+
+    #[error("unknown escape \\{0} (use \\n, \\t, or \\\\)")]
+    UnknownEscape(char),
+}
+
+/// A value that can be substituted into a template.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Ip,
+    Address,
+    Target,
+    Hostname,
+    Port,
+    Protocol,
+    Status,
+    Service,
+    Banner,
+    ResponseTime,
+    ScanId,
+    ScanType,
+}
+
+impl Field {
+    /// Every field with its placeholder name, in documentation order.
+    const ALL: [(Field, &'static str); 12] = [
+        (Field::Ip, "ip"),
+        (Field::Address, "addr"),
+        (Field::Target, "target"),
+        (Field::Hostname, "hostname"),
+        (Field::Port, "port"),
+        (Field::Protocol, "protocol"),
+        (Field::Status, "status"),
+        (Field::Service, "service"),
+        (Field::Banner, "banner"),
+        (Field::ResponseTime, "rtt"),
+        (Field::ScanId, "scan_id"),
+        (Field::ScanType, "scan_type"),
+    ];
+
+    fn from_name(name: &str) -> Option<Self> {
+        Self::ALL
+            .iter()
+            .find(|(_, field_name)| *field_name == name)
+            .map(|(field, _)| *field)
+    }
+
+    /// Placeholder names, for error messages.
+    fn names() -> String {
+        Self::ALL
+            .iter()
+            .map(|(_, name)| *name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// This field's value for one result; absent values are empty.
+    fn value(self, record: &ScanRecord, result: &PortResult) -> String {
+        match self {
+            Self::Ip => record.ip_address.clone(),
+            // `{ip}:{port}` would be ambiguous for IPv6
+            Self::Address => host_port(&record.ip_address, result.port.as_u16()),
+            Self::Target => record.target.clone(),
+            Self::Hostname => record.hostname.clone().unwrap_or_default(),
+            Self::Port => result.port.to_string(),
+            Self::Protocol => result.protocol.clone().unwrap_or_else(|| {
+                record
+                    .scan_type
+                    .parse::<ScanType>()
+                    .map_or(String::new(), |t| t.protocol().to_string())
+            }),
+            Self::Status => result.status.to_string(),
+            Self::Service => result.service.clone(),
+            // Keep each result on one line
+            Self::Banner => result
+                .banner
+                .as_deref()
+                .unwrap_or("")
+                .chars()
+                .map(|c| if c.is_control() { ' ' } else { c })
+                .collect(),
+            Self::ResponseTime => result
+                .response_time_ms
+                .map_or(String::new(), |t| t.to_string()),
+            Self::ScanId => record.id.to_string(),
+            Self::ScanType => record.scan_type.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Part {
+    Literal(String),
+    Field(Field),
+}
+
+/// A parsed output template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<Part>,
+}
+
+impl Template {
+    /// Render the template for one result of `record`.
+    pub fn render(&self, record: &ScanRecord, result: &PortResult) -> String {
+        self.parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(text) => text.clone(),
+                Part::Field(field) => field.value(record, result),
+            })
+            .collect()
+    }
+
+    /// Write one line per result of `record`.
+    pub fn write_record(&self, record: &ScanRecord, mut writer: impl Write) -> io::Result<()> {
+        for result in &record.results {
+            writeln!(writer, "{}", self.render(record, result))?;
+        }
+        writer.flush()
+    }
+}
+
+impl FromStr for Template {
+    type Err = TemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.char_indices().peekable();
+
+        while let Some((position, c)) = chars.next() {
+            match c {
+                '{' if chars.next_if(|&(_, c)| c == '{').is_some() => literal.push('{'),
+                '}' if chars.next_if(|&(_, c)| c == '}').is_some() => literal.push('}'),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some((_, '}')) => break,
+                            Some((_, c)) => name.push(c),
+                            None => return Err(TemplateError::Unclosed(position)),
+                        }
+                    }
+                    let field = Field::from_name(name.trim())
+                        .ok_or(TemplateError::UnknownField { name })?;
+                    if !literal.is_empty() {
+                        parts.push(Part::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(Part::Field(field));
+                }
+                '}' => return Err(TemplateError::Unmatched(position)),
+                '\\' => match chars.next().map(|(_, c)| c) {
+                    Some('n') => literal.push('\n'),
+                    Some('t') => literal.push('\t'),
+                    Some('\\') => literal.push('\\'),
+                    Some(other) => return Err(TemplateError::UnknownEscape(other)),
+                    None => literal.push('\\'),
+                },
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            parts.push(Part::Literal(literal));
+        }
+        Ok(Self { parts })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::PortStatus;
+    use crate::types::Port;
+
+    fn record() -> ScanRecord {
+        let results = vec![
+            PortResult::new(Port::new(22).unwrap(), PortStatus::Open, "ssh")
+                .with_banner(Some("SSH-2.0-OpenSSH_9.6\r\n".to_string())),
+            PortResult::new(Port::new(80).unwrap(), PortStatus::Filtered, "http"),
+        ];
+        ScanRecord::new("example.com", "93.184.216.34", ScanType::Connect).finalize(results, 0)
+    }
+
+    fn render_all(template: &str) -> String {
+        let template: Template = template.parse().unwrap();
+        let mut buffer = Vec::new();
+        template.write_record(&record(), &mut buffer).unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn test_render_fields() {
+        assert_eq!(
+            render_all("{ip}:{port}/{protocol} {status} {service}"),
+            "93.184.216.34:22/tcp open ssh\n93.184.216.34:80/tcp filtered http\n"
+        );
+        assert_eq!(render_all("{addr}"), "93.184.216.34:22\n93.184.216.34:80\n");
+        assert_eq!(
+            render_all("{target}\\t{banner}|{rtt}"),
+            "example.com\tSSH-2.0-OpenSSH_9.6  |\nexample.com\t|\n"
+        );
+    }
+
+    #[test]
+    fn test_literal_braces_and_escapes() {
+        assert_eq!(
+            render_all("{{\"port\": {port}}}\\\\"),
+            "{\"port\": 22}\\\n{\"port\": 80}\\\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = "{ip} {state}".parse::<Template>().unwrap_err();
+        assert_eq!(
+            err,
+            TemplateError::UnknownField {
+                name: "state".to_string()
+            }
+        );
+        assert!(err.to_string().contains("valid fields are ip, addr,"));
+
+        assert_eq!("{port".parse::<Template>(), Err(TemplateError::Unclosed(0)));
+        assert_eq!(
+            "port}".parse::<Template>(),
+            Err(TemplateError::Unmatched(4))
+        );
+        assert_eq!(
+            "\\r".parse::<Template>(),
+            Err(TemplateError::UnknownEscape('r'))
+        );
+    }
+}
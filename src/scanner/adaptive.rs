@@ -0,0 +1,350 @@
+//! Adaptive concurrency control for a single host scan.
+//!
+//! The controller periodically measures probe completions and timeouts and
+//! adjusts the number of semaphore permits available to the scan loop:
+//!
+//! - **Minimum rate**: concurrency is raised (up to the file-descriptor
+//!   limit) whenever throughput falls below the floor because probes are
+//!   finishing quickly and the scan is under-utilizing the network.
+//! - **Congestion backoff**: when the share of timed-out probes crosses a
+//!   threshold, concurrency is halved, then ramped back up once the timeout
+//!   ratio recovers. This protects fragile targets and avoids false
+//!   "filtered" results caused by our own flooding.
+//!
+//! Each `run_scan` owns its own controller, so one slow host in a range
+//! scan never throttles the others. Backoff only engages once the host has
+//! given definitive answers: a host where *every* probe times out is
+//! firewalled, not congested, and slowing down would not help.
+
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+
+/// How often the controller re-evaluates throughput.
+pub const ADJUST_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Default timeout ratio above which concurrency is reduced.
+pub const DEFAULT_BACKOFF_THRESHOLD: f64 = 0.5;
+
+/// Default timeout ratio below which concurrency ramps back up.
+pub const DEFAULT_RECOVERY_THRESHOLD: f64 = 0.1;
+
+/// File descriptors kept free for everything other than probe sockets.
+const FD_RESERVE: usize = 128;
+
+/// Completions needed in a window before its timeout ratio is trusted.
+const MIN_SAMPLES: u64 = 20;
+
+/// Adjusts the permit count of a scan's semaphore based on measured throughput.
+#[derive(Debug)]
+pub struct ConcurrencyController {
+    semaphore: Arc<Semaphore>,
+    limits: Limits,
+    current: AtomicUsize,
+    /// Permits that should be removed but were held by in-flight probes.
+    debt: AtomicUsize,
+    completed: AtomicU64,
+    timeouts: AtomicU64,
+    responsive: AtomicBool,
+}
+
+/// Bounds and thresholds the controller works within.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Limits {
+    /// Configured concurrency, restored after a backoff recovers.
+    baseline: usize,
+    /// Lowest concurrency a backoff may reach.
+    floor: usize,
+    /// Highest concurrency the minimum rate may reach.
+    max: usize,
+    /// Throughput floor in probes per second (0 = disabled).
+    min_rate: u32,
+    /// Timeout ratio that triggers backoff (0 = disabled).
+    backoff_threshold: f64,
+    /// Timeout ratio under which concurrency ramps back up.
+    recovery_threshold: f64,
+}
+
+/// Probe outcomes observed during one adjustment window.
+#[derive(Debug, Clone, Copy)]
+struct Window {
+    completed: u64,
+    timeouts: u64,
+    duration: Duration,
+    /// Whether the host has ever given a definitive answer.
+    responsive: bool,
+}
+
+impl ConcurrencyController {
+    /// Create a controller for `semaphore`, which starts with `initial` permits.
+    ///
+    /// Congestion backoff is enabled with default thresholds; the minimum
+    /// rate is disabled until [`with_min_rate`](Self::with_min_rate) is called.
+    pub fn new(semaphore: Arc<Semaphore>, initial: usize) -> Self {
+        let initial = initial.max(1);
+        Self {
+            semaphore,
+            limits: Limits {
+                baseline: initial,
+                floor: (initial / 8).max(1),
+                max: initial,
+                min_rate: 0,
+                backoff_threshold: DEFAULT_BACKOFF_THRESHOLD,
+                recovery_threshold: DEFAULT_RECOVERY_THRESHOLD,
+            },
+            current: AtomicUsize::new(initial),
+            debt: AtomicUsize::new(0),
+            completed: AtomicU64::new(0),
+            timeouts: AtomicU64::new(0),
+            responsive: AtomicBool::new(false),
+        }
+    }
+
+    /// Raise concurrency up to `max` while throughput is below `min_rate` per second.
+    pub fn with_min_rate(mut self, min_rate: u32, max: usize) -> Self {
+        self.limits.min_rate = min_rate;
+        self.limits.max = max.max(self.limits.baseline);
+        self
+    }
+
+    /// Set the timeout ratios that trigger backoff and recovery (0 disables backoff).
+    pub fn with_backoff(mut self, threshold: f64, recovery: f64) -> Self {
+        self.limits.backoff_threshold = threshold;
+        self.limits.recovery_threshold = recovery.min(threshold);
+        self
+    }
+
+    /// Record that a probe finished, and whether it timed out.
+    pub fn record_completion(&self, timed_out: bool) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        if timed_out {
+            self.timeouts.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.responsive.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Current permit count.
+    pub fn current(&self) -> usize {
+        self.current.load(Ordering::Relaxed)
+    }
+
+    /// Maximum permit count the controller may grant.
+    pub fn max(&self) -> usize {
+        self.limits.max
+    }
+
+    /// Fewest permits the controller backs off to.
+    pub fn floor(&self) -> usize {
+        self.limits.floor
+    }
+
+    /// Start the background adjustment loop. Abort the handle when the scan ends.
+    pub fn spawn(self: &Arc<Self>) -> JoinHandle<()> {
+        let controller = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(ADJUST_INTERVAL);
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                controller.adjust(Window {
+                    completed: controller.completed.swap(0, Ordering::Relaxed),
+                    timeouts: controller.timeouts.swap(0, Ordering::Relaxed),
+                    duration: ADJUST_INTERVAL,
+                    responsive: controller.responsive.load(Ordering::Relaxed),
+                });
+            }
+        })
+    }
+
+    /// Apply one adjustment step for the probes seen in `window`.
+    fn adjust(&self, window: Window) {
+        self.settle_debt();
+
+        let current = self.current();
+        let next = next_concurrency(current, &self.limits, &window);
+        if next == current {
+            return;
+        }
+
+        tracing::debug!(
+            "{} probes ({} timed out) in {:?}, concurrency {} -> {}",
+            window.completed,
+            window.timeouts,
+            window.duration,
+            current,
+            next
+        );
+
+        self.current.store(next, Ordering::Relaxed);
+        if next > current {
+            // Cancel outstanding removals before granting new permits
+            let grow = next - current;
+            let debt = self.debt.load(Ordering::Relaxed);
+            let cancelled = debt.min(grow);
+            self.debt.store(debt - cancelled, Ordering::Relaxed);
+            self.semaphore.add_permits(grow - cancelled);
+        } else {
+            self.debt.fetch_add(current - next, Ordering::Relaxed);
+            self.settle_debt();
+        }
+    }
+
+    /// Remove as many owed permits as are currently idle.
+    fn settle_debt(&self) {
+        let debt = self.debt.load(Ordering::Relaxed);
+        if debt > 0 {
+            let forgotten = self.semaphore.forget_permits(debt);
+            self.debt.store(debt - forgotten, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Decide the next concurrency level for one window.
+///
+/// Halves on congestion, grows by 25% (at least one permit) toward the
+/// baseline after recovery or toward the maximum while below the minimum rate.
+fn next_concurrency(current: usize, limits: &Limits, window: &Window) -> usize {
+    let timeout_ratio = (window.completed >= MIN_SAMPLES)
+        .then(|| window.timeouts as f64 / window.completed as f64);
+    let grow = |cap: usize| (current + (current / 4).max(1)).min(cap).max(current);
+
+    if let Some(ratio) = timeout_ratio {
+        if limits.backoff_threshold > 0.0 && window.responsive && ratio > limits.backoff_threshold
+        {
+            return (current / 2).max(limits.floor).min(current);
+        }
+    }
+
+    let calm = |ratio: f64| ratio <= limits.recovery_threshold;
+    let rate = window.completed as f64 / window.duration.as_secs_f64();
+
+    // A slow window may simply be too small to judge, so the minimum rate
+    // grows without a sample; returning to baseline needs evidence of recovery
+    if limits.min_rate > 0 && rate < f64::from(limits.min_rate) && timeout_ratio.is_none_or(calm)
+    {
+        grow(limits.max)
+    } else if current < limits.baseline && timeout_ratio.is_some_and(calm) {
+        grow(limits.baseline)
+    } else {
+        current
+    }
+}
+
+/// Highest concurrency the process can sustain given its open-file limit.
+pub fn fd_limit() -> usize {
+    #[cfg(unix)]
+    {
+        let mut limit = libc::rlimit {
+            rlim_cur: 0,
+            rlim_max: 0,
+        };
+        if unsafe { libc::getrlimit(libc::RLIMIT_NOFILE, &mut limit) } == 0 {
+            let soft = usize::try_from(limit.rlim_cur).unwrap_or(usize::MAX);
+            return soft.saturating_sub(FD_RESERVE).max(1);
+        }
+        1024 - FD_RESERVE
+    }
+    #[cfg(not(unix))]
+    {
+        8192
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(min_rate: u32) -> Limits {
+        Limits {
+            baseline: 100,
+            floor: 12,
+            max: 1000,
+            min_rate,
+            backoff_threshold: DEFAULT_BACKOFF_THRESHOLD,
+            recovery_threshold: DEFAULT_RECOVERY_THRESHOLD,
+        }
+    }
+
+    fn window(completed: u64, timeouts: u64) -> Window {
+        Window {
+            completed,
+            timeouts,
+            duration: Duration::from_secs(1),
+            responsive: true,
+        }
+    }
+
+    #[test]
+    fn test_next_concurrency_min_rate_disabled() {
+        assert_eq!(next_concurrency(100, &limits(0), &window(5, 0)), 100);
+    }
+
+    #[test]
+    fn test_next_concurrency_grows_below_floor() {
+        assert_eq!(next_concurrency(100, &limits(500), &window(50, 0)), 125);
+        assert_eq!(next_concurrency(900, &limits(500), &window(50, 0)), 1000);
+    }
+
+    #[test]
+    fn test_next_concurrency_holds_above_floor() {
+        assert_eq!(next_concurrency(100, &limits(500), &window(600, 0)), 100);
+    }
+
+    #[test]
+    fn test_next_concurrency_backs_off_on_timeouts() {
+        assert_eq!(next_concurrency(100, &limits(0), &window(100, 80)), 50);
+        assert_eq!(next_concurrency(20, &limits(0), &window(100, 80)), 12);
+        // Backoff wins over the minimum rate
+        assert_eq!(next_concurrency(100, &limits(500), &window(100, 80)), 50);
+    }
+
+    #[test]
+    fn test_next_concurrency_ignores_unresponsive_host() {
+        let mut all_filtered = window(100, 100);
+        all_filtered.responsive = false;
+        assert_eq!(next_concurrency(100, &limits(0), &all_filtered), 100);
+    }
+
+    #[test]
+    fn test_next_concurrency_recovers_to_baseline() {
+        assert_eq!(next_concurrency(50, &limits(0), &window(100, 5)), 62);
+        assert_eq!(next_concurrency(90, &limits(0), &window(100, 5)), 100);
+        // Still congested enough to hold, not enough to back off
+        assert_eq!(next_concurrency(50, &limits(0), &window(100, 30)), 50);
+    }
+
+    #[tokio::test]
+    async fn test_adjust_adds_permits() {
+        let semaphore = Arc::new(Semaphore::new(10));
+        let controller = ConcurrencyController::new(Arc::clone(&semaphore), 10)
+            .with_min_rate(1000, 100);
+
+        controller.adjust(window(5, 0));
+        assert_eq!(controller.current(), 12);
+        assert_eq!(semaphore.available_permits(), 12);
+    }
+
+    #[tokio::test]
+    async fn test_adjust_removes_idle_permits() {
+        let semaphore = Arc::new(Semaphore::new(100));
+        let held = Arc::clone(&semaphore).acquire_many_owned(80).await.unwrap();
+        let controller = ConcurrencyController::new(Arc::clone(&semaphore), 100);
+
+        controller.adjust(window(100, 90));
+        assert_eq!(controller.current(), 50);
+        assert_eq!(semaphore.available_permits(), 0);
+
+        // Removal completes once in-flight probes release their permits
+        drop(held);
+        controller.adjust(window(0, 0));
+        assert_eq!(semaphore.available_permits(), 50);
+    }
+
+    #[test]
+    fn test_fd_limit_positive() {
+        assert!(fd_limit() > 0);
+    }
+}
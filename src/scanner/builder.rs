@@ -0,0 +1,239 @@
+//! One-call scans for library users.
+//!
+//! [`ScanBuilder`] resolves a target, creates the scanner for the scan type,
+//! and runs the job, which otherwise means wiring [`TargetSpec`],
+//! [`create_scanner`], [`ScanJobConfig`], and [`run_scan`] by hand. Those
+//! pieces stay public for anything the builder does not cover.
+
+use super::{create_scanner, run_scan, ScanConfig, ScanJobConfig, ScanType};
+use crate::error::{CliError, CliResult};
+use crate::storage::{ScanRecord, ScanSession};
+use crate::types::{Port, PortRange, PortSpec, ResolveOptions, ScanTarget, TargetSpec};
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+/// Builder for a complete scan of one target.
+///
+/// ```rust,ignore
+/// use scuttle::{ScanBuilder, ScanType};
+///
+/// let record = ScanBuilder::new("192.168.1.1")
+///     .with_ports("22,80,443".parse()?)
+///     .with_scan_type(ScanType::Connect)
+///     .with_concurrency(100)
+///     .run()
+///     .await?;
+/// println!("{} open ports", record.open_ports);
+/// ```
+#[derive(Debug, Clone)]
+pub struct ScanBuilder {
+    target: String,
+    ports: PortSpec,
+    scan_type: ScanType,
+    concurrency: Option<usize>,
+    timeout: Duration,
+    banners: bool,
+    show_closed: bool,
+    rate_limit: u32,
+    resolve: ResolveOptions,
+    cancel: Option<CancellationToken>,
+}
+
+impl ScanBuilder {
+    /// Start a scan of `target`: an IP address, hostname, or CIDR range.
+    ///
+    /// Defaults match `scuttle scan`: a connect scan of ports 1-1000 with a
+    /// 3 second timeout.
+    pub fn new(target: impl Into<String>) -> Self {
+        let ports = PortRange::new(Port::new_unchecked(1), Port::new_unchecked(1000))
+            .expect("1-1000 is a valid range");
+        let mut spec = PortSpec::new();
+        spec.add_range(ports);
+
+        Self {
+            target: target.into(),
+            ports: spec,
+            scan_type: ScanType::Connect,
+            concurrency: None,
+            timeout: Duration::from_secs(3),
+            banners: false,
+            show_closed: false,
+            rate_limit: 0,
+            resolve: ResolveOptions::default(),
+            cancel: None,
+        }
+    }
+
+    /// Set the ports to scan.
+    pub fn with_ports(mut self, ports: PortSpec) -> Self {
+        self.ports = ports;
+        self
+    }
+
+    /// Set the scan type.
+    pub fn with_scan_type(mut self, scan_type: ScanType) -> Self {
+        self.scan_type = scan_type;
+        self
+    }
+
+    /// Set concurrency level; by default the scan type picks one.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = Some(concurrency);
+        self
+    }
+
+    /// Set the connection/response timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Grab banners from open ports.
+    pub fn with_banners(mut self) -> Self {
+        self.banners = true;
+        self
+    }
+
+    /// Include closed ports in results.
+    pub fn with_closed(mut self) -> Self {
+        self.show_closed = true;
+        self
+    }
+
+    /// Set rate limit in packets per second (0 = unlimited).
+    pub fn with_rate_limit(mut self, rate: u32) -> Self {
+        self.rate_limit = rate;
+        self
+    }
+
+    /// Set how the target is resolved.
+    pub fn with_resolve_options(mut self, options: ResolveOptions) -> Self {
+        self.resolve = options;
+        self
+    }
+
+    /// Stop scanning when `token` is cancelled, keeping the results so far.
+    ///
+    /// Hosts of a range not yet reached are skipped.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Scan the target, which must resolve to a single host.
+    ///
+    /// Use [`run_all`](Self::run_all) for ranges.
+    pub async fn run(self) -> CliResult<ScanRecord> {
+        let targets = self.resolve().await?;
+        if targets.len() > 1 {
+            return Err(CliError::InvalidArgument(format!(
+                "{} covers {} hosts; use run_all to scan more than one",
+                self.target,
+                targets.len()
+            )));
+        }
+        self.scan(&targets[0]).await
+    }
+
+    /// Scan every host the target resolves to, one after another.
+    pub async fn run_all(self) -> CliResult<ScanSession> {
+        let targets = self.resolve().await?;
+        let mut records = Vec::with_capacity(targets.len());
+        for target in &targets {
+            if self.cancel.as_ref().is_some_and(|t| t.is_cancelled()) {
+                break;
+            }
+            records.push(self.scan(target).await?);
+        }
+        Ok(ScanSession::new(&self.target, self.scan_type).finalize(records))
+    }
+
+    /// Resolve the target, failing if it names no hosts.
+    async fn resolve(&self) -> CliResult<Vec<ScanTarget>> {
+        let targets = TargetSpec::parse(&self.target)?
+            .resolve_with(&self.resolve)
+            .await?;
+        if targets.is_empty() {
+            return Err(CliError::Unreachable(format!(
+                "{} has no hosts to scan",
+                self.target
+            )));
+        }
+        Ok(targets)
+    }
+
+    async fn scan(&self, target: &ScanTarget) -> CliResult<ScanRecord> {
+        let config = ScanConfig::new(target.ip)
+            .with_hostname(&target.original)
+            .with_timeout(self.timeout);
+        let config = if self.banners {
+            config.with_banners()
+        } else {
+            config
+        };
+        let config = if self.show_closed {
+            config
+        } else {
+            config.without_closed_services()
+        };
+        let scanner = create_scanner(self.scan_type, config)?;
+
+        let job = ScanJobConfig::from_spec(self.ports.clone()).with_rate_limit(self.rate_limit);
+        let job = match self.concurrency {
+            Some(concurrency) => job.with_concurrency(concurrency),
+            None => job,
+        };
+        let job = if self.show_closed {
+            job.with_closed()
+        } else {
+            job
+        };
+        let job = match self.cancel {
+            Some(ref token) => job.with_cancellation(token.clone()),
+            None => job,
+        };
+
+        Ok(run_scan(scanner, job).await?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::PortStatus;
+    use tokio::net::TcpListener;
+
+    #[tokio::test]
+    async fn test_scan_builder_run() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let record = ScanBuilder::new("127.0.0.1")
+            .with_ports(PortSpec::from_ports(&[Port::new(port).unwrap()]))
+            .with_timeout(Duration::from_millis(500))
+            .run()
+            .await
+            .unwrap();
+
+        assert_eq!(record.ip_address, "127.0.0.1");
+        assert_eq!(record.port_spec, port.to_string());
+        assert_eq!(record.results.len(), 1);
+        assert_eq!(record.results[0].status, PortStatus::Open);
+    }
+
+    #[tokio::test]
+    async fn test_scan_builder_range() {
+        let builder = ScanBuilder::new("127.0.0.0/30")
+            .with_ports(PortSpec::from_ports(&[Port::new(9).unwrap()]))
+            .with_timeout(Duration::from_millis(200));
+
+        assert!(matches!(
+            builder.clone().run().await,
+            Err(CliError::InvalidArgument(_))
+        ));
+
+        let session = builder.run_all().await.unwrap();
+        assert_eq!(session.records.len(), 2);
+        assert_eq!(session.target, "127.0.0.0/30");
+    }
+}
@@ -0,0 +1,558 @@
+//! Host discovery (ping) probes.
+//!
+//! Before port scanning a range, a single probe per host tells us which
+//! addresses are alive so that dead hosts can be skipped entirely. On a
+//! sparse /24 this avoids thousands of pointless port probes per empty address.
+//!
+//! # Probe Types
+//!
+//! - **ICMP echo**: Classic ping. Uses an unprivileged datagram ICMP socket
+//!   where the OS allows it, falling back to a raw socket.
+//! - **ICMP timestamp / address mask**: Requests that some hosts filtering
+//!   echo still answer. Sent alongside or instead of echo with
+//!   [`HostDiscovery::with_icmp_probes`]. Require raw socket access.
+//! - **TCP SYN ping**: Connects to common ports; either a completed handshake
+//!   or a refusal (RST) proves the host is up. No privileges required.
+//! - **TCP ACK ping**: Sends a bare ACK; a live host answers with RST even
+//!   behind stateless firewalls that drop SYNs. Requires raw socket access.
+//!
+//! If a probe cannot be sent (e.g. missing privileges, or IPv6 for the raw
+//! probes), discovery falls back to the TCP SYN ping for that host.
+
+use crate::error::{ScanError, ScanResult};
+use crate::scanner::syn::rand_source_port;
+use crate::types::ScanTarget;
+use chrono::Timelike;
+use futures::stream::{self, StreamExt};
+use pnet::packet::icmp::{self, IcmpPacket, IcmpType, IcmpTypes};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use pnet::packet::tcp::{self, MutableTcpPacket, TcpFlags};
+use pnet::transport::{self, TransportChannelType, TransportProtocol};
+use serde::{Deserialize, Serialize};
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::time::timeout;
+
+/// Ports probed by the TCP pings (same defaults as most scanners).
+pub const PING_PORTS: &[u16] = &[80, 443];
+
+/// Host discovery probe type.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum DiscoveryMode {
+    /// ICMP echo request
+    Icmp,
+    /// TCP SYN ping via connect() to common ports (no privileges required)
+    #[default]
+    TcpSyn,
+    /// TCP ACK ping using raw sockets (requires root/sudo)
+    TcpAck,
+}
+
+impl DiscoveryMode {
+    /// Check if this probe type requires elevated privileges.
+    pub fn requires_privileges(self) -> bool {
+        matches!(self, Self::TcpAck)
+    }
+}
+
+impl std::fmt::Display for DiscoveryMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Icmp => write!(f, "ICMP echo"),
+            Self::TcpSyn => write!(f, "TCP SYN ping"),
+            Self::TcpAck => write!(f, "TCP ACK ping"),
+        }
+    }
+}
+
+/// ICMP request sent by ICMP host discovery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, clap::ValueEnum)]
+pub enum IcmpProbe {
+    /// Echo request (type 8), as sent by ping
+    Echo,
+    /// Timestamp request (type 13; requires root/sudo)
+    Timestamp,
+    /// Address mask request (type 17; requires root/sudo)
+    Mask,
+}
+
+impl IcmpProbe {
+    /// Check if this probe needs a raw socket, which requires privileges.
+    ///
+    /// Datagram ICMP sockets only carry echo requests.
+    pub fn requires_privileges(self) -> bool {
+        !matches!(self, Self::Echo)
+    }
+
+    /// Type of the request message.
+    fn request_type(self) -> IcmpType {
+        match self {
+            Self::Echo => IcmpTypes::EchoRequest,
+            Self::Timestamp => IcmpTypes::Timestamp,
+            Self::Mask => IcmpTypes::AddressMaskRequest,
+        }
+    }
+
+    /// Type of the reply message.
+    fn reply_type(self) -> IcmpType {
+        match self {
+            Self::Echo => IcmpTypes::EchoReply,
+            Self::Timestamp => IcmpTypes::TimestampReply,
+            Self::Mask => IcmpTypes::AddressMaskReply,
+        }
+    }
+
+    /// Length of the request message: the 8-byte header, plus three
+    /// timestamps or one mask.
+    fn request_len(self) -> usize {
+        match self {
+            Self::Echo => 8,
+            Self::Timestamp => 20,
+            Self::Mask => 12,
+        }
+    }
+}
+
+impl std::fmt::Display for IcmpProbe {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Echo => write!(f, "echo"),
+            Self::Timestamp => write!(f, "timestamp"),
+            Self::Mask => write!(f, "address mask"),
+        }
+    }
+}
+
+/// Whether a host answered host discovery.
+///
+/// Hosts scanned without discovery are `Unknown`: a host whose ports all
+/// come back filtered may then be down or firewalled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HostState {
+    /// Answered a discovery probe.
+    Up,
+    /// Did not answer discovery probes; its ports were not scanned.
+    Down,
+    /// Discovery did not run.
+    #[default]
+    Unknown,
+}
+
+impl HostState {
+    /// Check if discovery did not run for the host.
+    pub fn is_unknown(&self) -> bool {
+        *self == Self::Unknown
+    }
+
+    /// State of a host seen by two scans: up if either found it up, down
+    /// only if both found it down.
+    pub fn combine(self, other: Self) -> Self {
+        match (self, other) {
+            (Self::Up, _) | (_, Self::Up) => Self::Up,
+            (Self::Down, Self::Down) => Self::Down,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+impl std::fmt::Display for HostState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Up => write!(f, "up"),
+            Self::Down => write!(f, "down"),
+            Self::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Runs host discovery probes against a set of targets.
+#[derive(Debug, Clone)]
+pub struct HostDiscovery {
+    mode: DiscoveryMode,
+    timeout: Duration,
+    icmp_probes: Vec<IcmpProbe>,
+}
+
+impl HostDiscovery {
+    /// Hosts probed at once when the scan sets no concurrency.
+    pub const DEFAULT_CONCURRENCY: usize = 500;
+
+    /// Create a new host discovery runner.
+    ///
+    /// # Arguments
+    /// * `mode` - Probe type to send
+    /// * `timeout` - How long to wait for a reply from each host
+    pub fn new(mode: DiscoveryMode, timeout: Duration) -> Self {
+        Self {
+            mode,
+            timeout,
+            icmp_probes: vec![IcmpProbe::Echo],
+        }
+    }
+
+    /// Set the requests sent by ICMP discovery, echo by default. A host
+    /// answering any of them is up.
+    pub fn with_icmp_probes(mut self, probes: Vec<IcmpProbe>) -> Self {
+        if !probes.is_empty() {
+            self.icmp_probes = probes;
+        }
+        self
+    }
+
+    /// Get the probe type in use.
+    pub fn mode(&self) -> DiscoveryMode {
+        self.mode
+    }
+
+    /// Describe the probes sent, such as "ICMP echo, timestamp".
+    pub fn describe(&self) -> String {
+        match self.mode {
+            DiscoveryMode::Icmp => {
+                let probes: Vec<String> = self.icmp_probes.iter().map(|p| p.to_string()).collect();
+                format!("ICMP {}", probes.join(", "))
+            }
+            mode => mode.to_string(),
+        }
+    }
+
+    /// Probe every target, returning `(up, down)` hosts in input order.
+    pub async fn discover(
+        &self,
+        targets: Vec<ScanTarget>,
+        concurrency: usize,
+    ) -> (Vec<ScanTarget>, Vec<ScanTarget>) {
+        let probed: Vec<(ScanTarget, bool)> = stream::iter(targets)
+            .map(|target| async move {
+                let up = self.is_up(target.ip).await;
+                (target, up)
+            })
+            .buffered(concurrency.clamp(1, 1000))
+            .collect()
+            .await;
+
+        let (up, down): (Vec<_>, Vec<_>) = probed.into_iter().partition(|(_, up)| *up);
+        (
+            up.into_iter().map(|(t, _)| t).collect(),
+            down.into_iter().map(|(t, _)| t).collect(),
+        )
+    }
+
+    /// Check whether a single host responds to the configured probe.
+    pub async fn is_up(&self, ip: IpAddr) -> bool {
+        let result = match (self.mode, ip) {
+            (DiscoveryMode::Icmp, IpAddr::V4(v4)) => self.icmp_ping(v4).await,
+            (DiscoveryMode::TcpAck, IpAddr::V4(v4)) => {
+                let timeout = self.timeout;
+                run_blocking(move || tcp_ack_ping(v4, timeout)).await
+            }
+            (DiscoveryMode::TcpSyn, _) => return tcp_syn_ping(ip, self.timeout).await,
+            (_, IpAddr::V6(_)) => Err(ScanError::InvalidConfig(format!(
+                "{} only supports IPv4 currently",
+                self.mode
+            ))),
+        };
+
+        match result {
+            Ok(up) => up,
+            Err(e) => {
+                tracing::debug!("{} to {} failed ({}), using TCP SYN ping", self.mode, ip, e);
+                tcp_syn_ping(ip, self.timeout).await
+            }
+        }
+    }
+
+    /// Send each ICMP probe in turn until one is answered.
+    ///
+    /// Fails if a probe could not be sent and none was answered, so that the
+    /// TCP SYN ping stands in for it.
+    async fn icmp_ping(&self, target: Ipv4Addr) -> ScanResult<bool> {
+        let mut failed = None;
+        for &probe in &self.icmp_probes {
+            let timeout = self.timeout;
+            match run_blocking(move || icmp_probe(target, probe, timeout)).await {
+                Ok(true) => return Ok(true),
+                Ok(false) => {}
+                Err(e) => failed = Some(e),
+            }
+        }
+        failed.map_or(Ok(false), Err)
+    }
+}
+
+/// Run a blocking raw-socket probe off the async runtime.
+async fn run_blocking<F>(probe: F) -> ScanResult<bool>
+where
+    F: FnOnce() -> ScanResult<bool> + Send + 'static,
+{
+    tokio::task::spawn_blocking(probe)
+        .await
+        .map_err(|e| ScanError::RawSocketError(e.to_string()))?
+}
+
+/// TCP SYN ping: any answer on a common port, even a refusal, means the host is up.
+async fn tcp_syn_ping(ip: IpAddr, wait: Duration) -> bool {
+    for &port in PING_PORTS {
+        match timeout(wait, TcpStream::connect(SocketAddr::new(ip, port))).await {
+            Ok(Ok(_)) => return true,
+            Ok(Err(e)) if e.kind() == std::io::ErrorKind::ConnectionRefused => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Send an ICMP request and wait for the matching reply.
+fn icmp_probe(target: Ipv4Addr, probe: IcmpProbe, wait: Duration) -> ScanResult<bool> {
+    // Datagram ICMP sockets work unprivileged on Linux/macOS, but only for
+    // echo; raw needs root.
+    let datagram = if probe.requires_privileges() {
+        None
+    } else {
+        Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::ICMPV4)).ok()
+    };
+    let (socket, raw) = match datagram {
+        Some(socket) => (socket, false),
+        None => (
+            Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::ICMPV4)).map_err(map_socket_error)?,
+            true,
+        ),
+    };
+
+    let identifier: u16 = rand::random();
+    let sequence: u16 = rand::random();
+    let buffer = icmp_request(probe, identifier, sequence)?;
+
+    let addr = SocketAddr::new(IpAddr::V4(target), 0);
+    socket.connect(&addr.into())?;
+    socket.send(&buffer)?;
+
+    let deadline = Instant::now() + wait;
+    let mut recv_buf = [0u8; 1500];
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining))?;
+
+        let n = match (&socket).read(&mut recv_buf) {
+            Ok(n) => n,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                break
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        // Raw sockets deliver the IP header; datagram sockets do not
+        let payload = if raw {
+            match Ipv4Packet::new(&recv_buf[..n]) {
+                Some(ip) if ip.get_source() == target => {
+                    let header_len = ip.get_header_length() as usize * 4;
+                    &recv_buf[header_len.min(n)..n]
+                }
+                _ => continue,
+            }
+        } else {
+            &recv_buf[..n]
+        };
+
+        if is_reply(payload, probe, raw.then_some(identifier), sequence) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+/// Build an ICMP request message with its checksum set.
+///
+/// Timestamp requests carry the originate timestamp, in milliseconds since
+/// midnight UTC; the receive and transmit timestamps and the mask of an
+/// address mask request are left zero for the host to fill in.
+pub(crate) fn icmp_request(
+    probe: IcmpProbe,
+    identifier: u16,
+    sequence: u16,
+) -> ScanResult<Vec<u8>> {
+    let mut buffer = vec![0u8; probe.request_len()];
+    buffer[0] = probe.request_type().0;
+    buffer[4..6].copy_from_slice(&identifier.to_be_bytes());
+    buffer[6..8].copy_from_slice(&sequence.to_be_bytes());
+    if probe == IcmpProbe::Timestamp {
+        let now = chrono::Utc::now();
+        let originate = now.num_seconds_from_midnight() * 1000 + now.nanosecond() / 1_000_000;
+        buffer[8..12].copy_from_slice(&originate.to_be_bytes());
+    }
+
+    let checksum = IcmpPacket::new(&buffer)
+        .map(|p| icmp::checksum(&p))
+        .ok_or_else(|| ScanError::InvalidPacket("Failed to checksum ICMP packet".to_string()))?;
+    buffer[2..4].copy_from_slice(&checksum.to_be_bytes());
+    Ok(buffer)
+}
+
+/// Check an ICMP message is the reply to our `probe` request.
+///
+/// Datagram sockets have the identifier rewritten by the kernel, so it is
+/// only checked when `identifier` is given.
+fn is_reply(payload: &[u8], probe: IcmpProbe, identifier: Option<u16>, sequence: u16) -> bool {
+    if payload.len() < 8 || payload[0] != probe.reply_type().0 {
+        return false;
+    }
+
+    let reply_id = u16::from_be_bytes([payload[4], payload[5]]);
+    let reply_seq = u16::from_be_bytes([payload[6], payload[7]]);
+    reply_seq == sequence && identifier.is_none_or(|id| id == reply_id)
+}
+
+/// Send a bare TCP ACK; a live host answers with RST regardless of port state.
+fn tcp_ack_ping(target: Ipv4Addr, wait: Duration) -> ScanResult<bool> {
+    let protocol = TransportChannelType::Layer4(TransportProtocol::Ipv4(
+        IpNextHeaderProtocols::Tcp,
+    ));
+    let (mut tx, mut rx) = transport::transport_channel(4096, protocol).map_err(map_socket_error)?;
+
+    let source_ip = egress_ipv4(target)?;
+    let source_port = rand_source_port();
+    let dest_port = PING_PORTS[0];
+
+    let mut buffer = [0u8; 20];
+    let mut packet = MutableTcpPacket::new(&mut buffer)
+        .ok_or_else(|| ScanError::InvalidPacket("Failed to create TCP packet".to_string()))?;
+    packet.set_source(source_port);
+    packet.set_destination(dest_port);
+    packet.set_sequence(rand::random());
+    packet.set_acknowledgement(rand::random());
+    packet.set_data_offset(5);
+    packet.set_flags(TcpFlags::ACK);
+    packet.set_window(1024);
+    let checksum = tcp::ipv4_checksum(&packet.to_immutable(), &source_ip, &target);
+    packet.set_checksum(checksum);
+
+    tx.send_to(packet, IpAddr::V4(target))
+        .map_err(|e| ScanError::RawSocketError(e.to_string()))?;
+
+    let deadline = Instant::now() + wait;
+    let mut iter = transport::tcp_packet_iter(&mut rx);
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        match iter.next_with_timeout(remaining) {
+            Ok(Some((reply, addr))) => {
+                if addr == IpAddr::V4(target)
+                    && reply.get_source() == dest_port
+                    && reply.get_destination() == source_port
+                    && reply.get_flags() & TcpFlags::RST != 0
+                {
+                    return Ok(true);
+                }
+            }
+            Ok(None) => break,
+            Err(e) => return Err(ScanError::RawSocketError(e.to_string())),
+        }
+    }
+
+    Ok(false)
+}
+
+/// Determine the local IPv4 address used to reach a target.
+fn egress_ipv4(target: Ipv4Addr) -> ScanResult<Ipv4Addr> {
+    let socket = std::net::UdpSocket::bind("0.0.0.0:0")?;
+    socket.connect(SocketAddr::new(IpAddr::V4(target), PING_PORTS[0]))?;
+    match socket.local_addr()?.ip() {
+        IpAddr::V4(ip) => Ok(ip),
+        IpAddr::V6(_) => Err(ScanError::InvalidConfig(
+            "no IPv4 route to target".to_string(),
+        )),
+    }
+}
+
+/// Map raw socket creation errors, surfacing permission problems clearly.
+pub(crate) fn map_socket_error(e: std::io::Error) -> ScanError {
+    if e.kind() == std::io::ErrorKind::PermissionDenied {
+        ScanError::PermissionDenied("Raw socket access requires root/sudo privileges".to_string())
+    } else {
+        ScanError::RawSocketError(e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_discovery_mode_privileges() {
+        assert!(!DiscoveryMode::Icmp.requires_privileges());
+        assert!(!DiscoveryMode::TcpSyn.requires_privileges());
+        assert!(DiscoveryMode::TcpAck.requires_privileges());
+        assert_eq!(DiscoveryMode::default(), DiscoveryMode::TcpSyn);
+    }
+
+    #[test]
+    fn test_is_echo_reply() {
+        let echo = IcmpProbe::Echo;
+        let reply = [0u8, 0, 0, 0, 0x12, 0x34, 0x00, 0x07];
+        assert!(is_reply(&reply, echo, Some(0x1234), 7));
+        assert!(is_reply(&reply, echo, None, 7));
+        assert!(!is_reply(&reply, echo, Some(0x4321), 7));
+        assert!(!is_reply(&reply, echo, None, 8));
+
+        let request = [8u8, 0, 0, 0, 0x12, 0x34, 0x00, 0x07];
+        assert!(!is_reply(&request, echo, None, 7));
+    }
+
+    #[test]
+    fn test_icmp_requests() {
+        for (probe, request_type, reply_type, len) in [
+            (IcmpProbe::Echo, 8, 0, 8),
+            (IcmpProbe::Timestamp, 13, 14, 20),
+            (IcmpProbe::Mask, 17, 18, 12),
+        ] {
+            let request = icmp_request(probe, 0x1234, 7).unwrap();
+            assert_eq!(request.len(), len);
+            assert_eq!(request[0], request_type);
+            assert_eq!(&request[4..8], &[0x12, 0x34, 0x00, 0x07]);
+            // A correct checksum makes the whole message sum to zero
+            let packet = IcmpPacket::new(&request).unwrap();
+            assert_eq!(icmp::checksum(&packet), packet.get_checksum());
+
+            let mut reply = request.clone();
+            reply[0] = reply_type;
+            assert!(is_reply(&reply, probe, Some(0x1234), 7));
+            assert!(!is_reply(&request, probe, Some(0x1234), 7));
+        }
+        let timestamp_reply = [14u8, 0, 0, 0, 0x12, 0x34, 0, 7];
+        assert!(!is_reply(&timestamp_reply, IcmpProbe::Mask, None, 7));
+
+        assert!(!IcmpProbe::Echo.requires_privileges());
+        assert!(IcmpProbe::Timestamp.requires_privileges());
+        assert!(IcmpProbe::Mask.requires_privileges());
+    }
+
+    #[test]
+    fn test_describe_probes() {
+        let wait = Duration::from_secs(1);
+        let icmp = HostDiscovery::new(DiscoveryMode::Icmp, wait);
+        assert_eq!(icmp.describe(), "ICMP echo");
+        let icmp = icmp.with_icmp_probes(vec![IcmpProbe::Timestamp, IcmpProbe::Mask]);
+        assert_eq!(icmp.describe(), "ICMP timestamp, address mask");
+        assert_eq!(
+            HostDiscovery::new(DiscoveryMode::TcpSyn, wait).describe(),
+            "TCP SYN ping"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_tcp_syn_ping_localhost() {
+        // Loopback always answers, either with a handshake or a refusal
+        let discovery = HostDiscovery::new(DiscoveryMode::TcpSyn, Duration::from_millis(500));
+        assert!(discovery.is_up(IpAddr::V4(Ipv4Addr::LOCALHOST)).await);
+    }
+}
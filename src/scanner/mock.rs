@@ -0,0 +1,162 @@
+//! Deterministic scanner for tests and benchmarks.
+//!
+//! [`MockScanner`] answers from a configured port map instead of the
+//! network, so scan coordination, rate limiting, filtering and output can be
+//! exercised without sockets. Available in this crate's tests and, for
+//! downstream crates, behind the `test-util` feature.
+
+use crate::scanner::traits::{PortResult, PortStatus, ScanType, Scanner};
+use crate::services::get_service_description;
+use crate::types::Port;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Scanner that reports configured statuses without sending packets.
+#[derive(Debug)]
+pub struct MockScanner {
+    target: IpAddr,
+    scan_type: ScanType,
+    statuses: HashMap<u16, PortStatus>,
+    banners: HashMap<u16, String>,
+    default_status: PortStatus,
+    delay: Option<Duration>,
+    started: AtomicUsize,
+    probes: AtomicUsize,
+}
+
+impl MockScanner {
+    /// Create a mock scanner for `target` where every port is closed.
+    pub fn new(target: IpAddr) -> Self {
+        Self {
+            target,
+            scan_type: ScanType::Connect,
+            statuses: HashMap::new(),
+            banners: HashMap::new(),
+            default_status: PortStatus::Closed,
+            delay: None,
+            started: AtomicUsize::new(0),
+            probes: AtomicUsize::new(0),
+        }
+    }
+
+    /// Report `status` for `port`.
+    pub fn with_port(mut self, port: u16, status: PortStatus) -> Self {
+        self.statuses.insert(port, status);
+        self
+    }
+
+    /// Report every port in `ports` as open.
+    pub fn with_open_ports(mut self, ports: impl IntoIterator<Item = u16>) -> Self {
+        self.statuses
+            .extend(ports.into_iter().map(|port| (port, PortStatus::Open)));
+        self
+    }
+
+    /// Attach a banner to results for `port`.
+    pub fn with_banner(mut self, port: u16, banner: impl Into<String>) -> Self {
+        self.banners.insert(port, banner.into());
+        self
+    }
+
+    /// Status reported for ports not configured explicitly.
+    pub fn with_default_status(mut self, status: PortStatus) -> Self {
+        self.default_status = status;
+        self
+    }
+
+    /// Sleep for `delay` before answering each probe.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+
+    /// Scan type the mock reports itself as.
+    pub fn with_scan_type(mut self, scan_type: ScanType) -> Self {
+        self.scan_type = scan_type;
+        self
+    }
+
+    /// Number of probes answered so far.
+    pub fn probe_count(&self) -> usize {
+        self.probes.load(Ordering::Relaxed)
+    }
+
+    /// Number of probes started so far, including any still waiting out
+    /// their delay.
+    pub fn started_count(&self) -> usize {
+        self.started.load(Ordering::Relaxed)
+    }
+}
+
+impl Default for MockScanner {
+    fn default() -> Self {
+        Self::new(IpAddr::V4(Ipv4Addr::LOCALHOST))
+    }
+}
+
+#[async_trait]
+impl Scanner for MockScanner {
+    fn scan_type(&self) -> ScanType {
+        self.scan_type
+    }
+
+    fn requires_privileges(&self) -> bool {
+        false
+    }
+
+    async fn scan_port(&self, port: Port) -> PortResult {
+        self.started.fetch_add(1, Ordering::Relaxed);
+        if let Some(delay) = self.delay {
+            tokio::time::sleep(delay).await;
+        }
+        self.probes.fetch_add(1, Ordering::Relaxed);
+
+        let port_num = port.as_u16();
+        let status = self
+            .statuses
+            .get(&port_num)
+            .copied()
+            .unwrap_or(self.default_status);
+
+        PortResult::new(port, status, get_service_description(port_num))
+            .with_banner(self.banners.get(&port_num).cloned())
+            .with_response_time(self.delay.map_or(0, |d| d.as_millis() as u64))
+    }
+
+    fn target(&self) -> IpAddr {
+        self.target
+    }
+
+    fn timeout(&self) -> Duration {
+        self.delay.unwrap_or(Duration::from_millis(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_mock_scanner_statuses() {
+        let scanner = MockScanner::default()
+            .with_open_ports([22, 80])
+            .with_port(443, PortStatus::Filtered)
+            .with_banner(22, "SSH-2.0-mock");
+
+        let ssh = scanner.scan_port(Port::new(22).unwrap()).await;
+        assert_eq!(ssh.status, PortStatus::Open);
+        assert_eq!(ssh.service, "ssh");
+        assert_eq!(ssh.banner.as_deref(), Some("SSH-2.0-mock"));
+
+        let https = scanner.scan_port(Port::new(443).unwrap()).await;
+        assert_eq!(https.status, PortStatus::Filtered);
+
+        let other = scanner.scan_port(Port::new(8080).unwrap()).await;
+        assert_eq!(other.status, PortStatus::Closed);
+        assert_eq!(scanner.probe_count(), 3);
+        assert_eq!(scanner.started_count(), 3);
+    }
+}
@@ -3,50 +3,120 @@
 //! This module provides a unified interface for TCP, SYN, and UDP scanning,
 //! managing concurrent scanning tasks using the tokio runtime.
 
+pub mod adaptive;
+pub mod builder;
+pub mod discovery;
+#[cfg(any(test, feature = "test-util"))]
+pub mod mock;
+pub mod privileges;
+pub mod progress;
 pub mod rate_limiter;
 pub mod syn;
 pub mod tcp;
+pub mod traceroute;
 pub mod traits;
 pub mod udp;
-
-pub use rate_limiter::RateLimiter;
-pub use syn::SynScanner;
+#[cfg(unix)]
+pub mod unix_socket;
+
+pub use adaptive::ConcurrencyController;
+pub use builder::ScanBuilder;
+pub use discovery::{DiscoveryMode, HostDiscovery, HostState, IcmpProbe};
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::MockScanner;
+pub use privileges::has_raw_socket_access;
+pub use progress::ScanProgress;
+pub use rate_limiter::{KeyedRateLimiter, RateLimiter};
+pub use syn::{OsProfile, SynScanner};
 pub use tcp::TcpConnectScanner;
-pub use traits::{PortResult, PortStatus, ScanConfig, ScanType, Scanner};
+pub use traceroute::Traceroute;
+pub use traits::{
+    PortResult, PortStatus, ProbeStats, ScanConfig, ScanType, Scanner, ScannerInfo,
+};
 pub use udp::UdpScanner;
+#[cfg(unix)]
+pub use unix_socket::{SocketResult, SocketScanRecord, UnixSocketScanner};
 
 use crate::error::ScanResult;
-use crate::storage::ScanRecord;
-use crate::types::Port;
+use crate::storage::{ScanRecord, ScanSettings};
+use crate::types::{Port, PortSpec};
 use futures::stream::{self, StreamExt};
-use indicatif::{ProgressBar, ProgressStyle};
+use indicatif::{HumanDuration, ProgressBar, ProgressState, ProgressStyle};
+use std::fmt;
+use std::net::IpAddr;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::UnboundedSender;
 use tokio::sync::Semaphore;
+use tokio_util::sync::CancellationToken;
+use tracing::Instrument;
 
 /// Configuration for a complete scan job.
 #[derive(Debug, Clone)]
 pub struct ScanJobConfig {
     /// Ports to scan.
     pub ports: Vec<Port>,
-    /// Maximum concurrent connections.
-    pub concurrency: usize,
-    /// Show verbose output with progress bar.
-    pub verbose: bool,
+    /// Specification the ports were expanded from, recorded in the result.
+    pub port_spec: Option<PortSpec>,
+    /// Maximum concurrent connections; `None` uses the scanner's
+    /// [`ScanType::default_concurrency`].
+    pub concurrency: Option<usize>,
+    /// Verbosity level: 1 shows a progress bar, 2 also prints open ports as found.
+    pub verbose: u8,
     /// Include closed ports in results.
     pub show_closed: bool,
     /// Rate limit in packets per second (0 = unlimited).
     pub rate_limit: u32,
+    /// Token bucket shared with other scans, used instead of one built
+    /// from `rate_limit` so that the limit caps them together.
+    pub rate_limiter: Option<RateLimiter>,
+    /// Per-target token buckets; probes wait on their target's bucket as
+    /// well as the overall one.
+    pub host_rate_limiter: Option<KeyedRateLimiter>,
+    /// Minimum sustained rate in probes per second (0 = disabled).
+    ///
+    /// When set, concurrency is raised above `concurrency` while throughput
+    /// lags this floor, up to the process file-descriptor limit.
+    pub min_rate: u32,
+    /// Timeout ratio above which concurrency is reduced (0 = no backoff).
+    pub backoff_threshold: f64,
+    /// Timeout ratio below which concurrency ramps back up after a backoff.
+    pub recovery_threshold: f64,
+    /// Shared progress display for multi-host scans (replaces the per-scan bar).
+    pub progress: Option<ScanProgress>,
+    /// Warnings raised before the scan started, kept on the record.
+    pub warnings: Vec<String>,
+    /// Receives every result as soon as it is probed, tagged with the target.
+    pub result_stream: Option<UnboundedSender<(IpAddr, PortResult)>>,
+    /// Probe only this percentage (0-100] of the ports, chosen at random.
+    pub sample: Option<f64>,
+    /// Stops the scan when cancelled, keeping the results gathered so far.
+    pub cancel: Option<CancellationToken>,
+    /// Ports handed to the scanner together; see
+    /// [`with_batch_size`](Self::with_batch_size).
+    pub batch_size: usize,
 }
 
 impl Default for ScanJobConfig {
     fn default() -> Self {
         Self {
             ports: Vec::new(),
-            concurrency: 500,
-            verbose: false,
+            port_spec: None,
+            concurrency: None,
+            verbose: 0,
             show_closed: false,
             rate_limit: 0,
+            rate_limiter: None,
+            host_rate_limiter: None,
+            min_rate: 0,
+            backoff_threshold: adaptive::DEFAULT_BACKOFF_THRESHOLD,
+            recovery_threshold: adaptive::DEFAULT_RECOVERY_THRESHOLD,
+            progress: None,
+            warnings: Vec::new(),
+            result_stream: None,
+            sample: None,
+            cancel: None,
+            batch_size: 1,
         }
     }
 }
@@ -60,15 +130,30 @@ impl ScanJobConfig {
         }
     }
 
-    /// Set concurrency level.
+    /// Create a job config for every port in a specification.
+    pub fn from_spec(spec: PortSpec) -> Self {
+        Self {
+            ports: spec.to_ports(),
+            port_spec: Some(spec),
+            ..Default::default()
+        }
+    }
+
+    /// Record the specification the ports came from.
+    pub fn with_port_spec(mut self, spec: PortSpec) -> Self {
+        self.port_spec = Some(spec);
+        self
+    }
+
+    /// Set concurrency level (at least 1).
     pub fn with_concurrency(mut self, concurrency: usize) -> Self {
-        self.concurrency = concurrency;
+        self.concurrency = Some(concurrency.max(1));
         self
     }
 
-    /// Enable verbose mode.
-    pub fn with_verbose(mut self) -> Self {
-        self.verbose = true;
+    /// Set the verbosity level.
+    pub fn with_verbosity(mut self, level: u8) -> Self {
+        self.verbose = level;
         self
     }
 
@@ -83,25 +168,136 @@ impl ScanJobConfig {
         self.rate_limit = rate;
         self
     }
+
+    /// Draw probes from `limiter`, shared with other scans, rather than a
+    /// bucket of this scan's own.
+    pub fn with_rate_limiter(mut self, limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Also limit the rate each target is probed at.
+    pub fn with_host_rate_limiter(mut self, limiter: KeyedRateLimiter) -> Self {
+        self.host_rate_limiter = Some(limiter);
+        self
+    }
+
+    /// Set the minimum sustained rate.
+    pub fn with_min_rate(mut self, rate: u32) -> Self {
+        self.min_rate = rate;
+        self
+    }
+
+    /// Set the congestion backoff and recovery timeout ratios.
+    pub fn with_backoff(mut self, threshold: f64, recovery: f64) -> Self {
+        self.backoff_threshold = threshold;
+        self.recovery_threshold = recovery;
+        self
+    }
+
+    /// Report into a shared progress display.
+    pub fn with_progress(mut self, progress: ScanProgress) -> Self {
+        self.progress = Some(progress);
+        self
+    }
+
+    /// Carry warnings raised while preparing the scan into its record.
+    pub fn with_warnings(mut self, warnings: Vec<String>) -> Self {
+        self.warnings = warnings;
+        self
+    }
+
+    /// Send each result to `sender` as it arrives, before the scan completes.
+    ///
+    /// Results are streamed in completion order and include closed ports.
+    /// The scan carries on if the receiver is dropped.
+    pub fn with_result_stream(mut self, sender: UnboundedSender<(IpAddr, PortResult)>) -> Self {
+        self.result_stream = Some(sender);
+        self
+    }
+
+    /// Probe a random `percent` of the ports instead of all of them.
+    pub fn with_sample(mut self, percent: f64) -> Self {
+        self.sample = Some(percent);
+        self
+    }
+
+    /// Stop the scan when `token` is cancelled.
+    ///
+    /// No further probes are sent, probes already sent are waited for, and
+    /// the record holds the results gathered so far, marked `cancelled`.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Hand the scanner `size` ports at a time, through
+    /// [`Scanner::scan_ports`], instead of one.
+    ///
+    /// A batch takes as many concurrency permits as it has ports and probes
+    /// them together, so the per-port bookkeeping of dispatch is paid once
+    /// per batch. Batches are capped at the fewest permits the scan may run
+    /// with, and cancellation is checked between batches rather than ports.
+    pub fn with_batch_size(mut self, size: usize) -> Self {
+        self.batch_size = size.max(1);
+        self
+    }
+}
+
+/// Number of probes a `percent` sample of `total` sends, at least one.
+pub fn sample_size(total: usize, percent: f64) -> usize {
+    let size = (total as f64 * percent / 100.0).round() as usize;
+    size.clamp(total.min(1), total)
+}
+
+/// Pick a random `percent` of `ports`, keeping them in their original order.
+fn sample_ports(ports: &[Port], percent: f64) -> Vec<Port> {
+    let size = sample_size(ports.len(), percent);
+    let mut picked =
+        rand::seq::index::sample(&mut rand::thread_rng(), ports.len(), size).into_vec();
+    picked.sort_unstable();
+    picked.into_iter().map(|i| ports[i]).collect()
 }
 
+/// Most probes kept in flight at once, with or without the adaptive
+/// controller; only a minimum rate raises concurrency past it, up to the
+/// open-file limit.
+const MAX_CONCURRENCY: usize = 1000;
+
 /// Execute a complete port scan using the provided scanner.
 pub async fn run_scan(
     scanner: Arc<dyn Scanner>,
     config: ScanJobConfig,
 ) -> ScanResult<ScanRecord> {
     let start_time = Instant::now();
-    let total_ports = config.ports.len();
+    let ports = match config.sample {
+        Some(percent) => sample_ports(&config.ports, percent),
+        None => config.ports.clone(),
+    };
+    let total_ports = ports.len();
     let scan_type = scanner.scan_type();
     let target = scanner.target();
 
-    // Set up progress bar
-    let progress = if config.verbose {
+    // Set up progress bar, reporting into the shared display if there is one
+    let progress = if let Some(ref shared) = config.progress {
+        Some(shared.host_bar(target, total_ports))
+    } else if config.verbose > 0 {
         let pb = ProgressBar::new(total_ports as u64);
+        // Whichever limit is tighter sets the pace
+        let rate = match config.host_rate_limiter.as_ref().map_or(0, |l| l.rate()) {
+            0 => config.rate_limit,
+            host_rate if config.rate_limit == 0 => host_rate,
+            host_rate => host_rate.min(config.rate_limit),
+        };
         pb.set_style(
             ProgressStyle::default_bar()
-                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%) | {msg}")
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%) ETA {eta} | {msg}")
                 .unwrap()
+                .with_key("eta", move |state: &ProgressState, w: &mut dyn fmt::Write| {
+                    let remaining = state.len().unwrap_or(0).saturating_sub(state.pos());
+                    let eta = rate_aware_eta(state.eta(), remaining, rate);
+                    let _ = write!(w, "{:#}", HumanDuration(eta));
+                })
                 .progress_chars("=>-"),
         );
         pb.set_message("Starting scan...");
@@ -111,55 +307,186 @@ pub async fn run_scan(
     };
 
     // Create semaphore for bounded concurrency
-    let semaphore = Arc::new(Semaphore::new(config.concurrency));
+    let requested = config
+        .concurrency
+        .unwrap_or_else(|| scanner.scan_type().default_concurrency());
+    let concurrency = requested.clamp(1, MAX_CONCURRENCY);
+    let semaphore = Arc::new(Semaphore::new(concurrency));
+
+    // Let an adaptive controller back off on timeouts and chase the minimum rate
+    let controller = (config.min_rate > 0 || config.backoff_threshold > 0.0).then(|| {
+        let controller = ConcurrencyController::new(Arc::clone(&semaphore), concurrency)
+            .with_backoff(config.backoff_threshold, config.recovery_threshold);
+        let controller = if config.min_rate > 0 {
+            controller.with_min_rate(config.min_rate, adaptive::fd_limit())
+        } else {
+            controller
+        };
+        Arc::new(controller)
+    });
+    let controller_task = controller.as_ref().map(|c| c.spawn());
+    let max_in_flight = controller.as_ref().map_or(concurrency, |c| c.max());
+
+    let mut warnings = config.warnings.clone();
+    if requested > concurrency {
+        warnings.push(format!(
+            "concurrency {} was clamped to {}",
+            requested, concurrency
+        ));
+    }
 
-    // Create rate limiter if needed
-    let rate_limiter = if config.rate_limit > 0 {
-        Some(Arc::new(RateLimiter::new(config.rate_limit)))
-    } else {
-        None
+    // Shared by all probes; a rate limit of 0 gives a limiter that never waits
+    let rate_limiter = config
+        .rate_limiter
+        .clone()
+        .unwrap_or_else(|| RateLimiter::from_limit(config.rate_limit));
+    let host_limiter = config
+        .host_rate_limiter
+        .clone()
+        .unwrap_or_else(KeyedRateLimiter::unlimited);
+
+    // At -vv, announce open ports as they are found
+    let live_open = config.verbose >= 2;
+    let protocol = scan_type.protocol();
+
+    // Resolves once the scan is cancelled, or never without a token
+    let cancelled = async {
+        match config.cancel {
+            Some(ref token) => token.cancelled().await,
+            None => std::future::pending().await,
+        }
     };
 
-    // Execute concurrent scans
-    let results: Vec<PortResult> = stream::iter(config.ports.clone())
-        .map(|port| {
+    // A batch holds a permit per port, so it must fit in the fewest the
+    // scan can be left with
+    let batch_size = config
+        .batch_size
+        .clamp(1, controller.as_ref().map_or(max_in_flight, |c| c.floor()));
+
+    // Execute concurrent scans. Cancelling stops new ports from being
+    // dispatched and drops ports still waiting for a permit or a token,
+    // but probes already sent run to completion and keep their results.
+    let results: Vec<PortResult> = stream::iter(ports)
+        .take_until(cancelled)
+        .chunks(batch_size)
+        .map(|batch| {
             let sem = Arc::clone(&semaphore);
+            let cancel = config.cancel.clone();
             let scanner = Arc::clone(&scanner);
             let limiter = rate_limiter.clone();
+            let host_limiter = host_limiter.clone();
+            let controller = controller.clone();
             let progress = progress.clone();
+            let shared = config.progress.clone();
+            let stream = config.result_stream.clone();
 
             async move {
-                // Acquire semaphore permit for concurrency control
-                let _permit = sem.acquire().await.unwrap();
-
-                // Apply rate limiting if configured
-                if let Some(ref limiter) = limiter {
-                    limiter.wait().await;
-                }
+                let dispatch = async {
+                    // Acquire semaphore permits for concurrency control
+                    let permit = sem.acquire_many_owned(batch.len() as u32).await.unwrap();
+
+                    // Wait on the host's bucket first so no overall token is
+                    // held while it refills
+                    for _ in 0..batch.len() {
+                        host_limiter.wait(target).await;
+                        limiter.wait().await;
+                    }
+                    permit
+                };
+                let _permit = match cancel {
+                    Some(token) => tokio::select! {
+                        biased;
+                        _ = token.cancelled() => return Vec::new(),
+                        permit = dispatch => permit,
+                    },
+                    None => dispatch.await,
+                };
+
+                let results = match *batch {
+                    [port] => {
+                        let span = tracing::debug_span!("probe", target = %scanner.target(), %port);
+                        let result = scanner.scan_port(port).instrument(span.clone()).await;
+                        span.in_scope(
+                            || tracing::debug!(status = %result.status, "probe finished"),
+                        );
+                        vec![result]
+                    }
+                    _ => {
+                        let span = tracing::debug_span!(
+                            "batch",
+                            target = %scanner.target(),
+                            first = %batch[0],
+                            len = batch.len()
+                        );
+                        scanner.scan_ports(&batch).instrument(span).await
+                    }
+                };
 
-                let result = scanner.scan_port(port).await;
+                for (port, result) in batch.iter().zip(&results) {
+                    if let Some(ref controller) = controller {
+                        controller.record_completion(result.status == PortStatus::Filtered);
+                    }
 
-                // Update progress bar
-                if let Some(ref pb) = progress {
-                    pb.inc(1);
-                    if result.status == PortStatus::Open {
-                        pb.set_message(format!("Found: {}/tcp open", port));
+                    // Update progress bar
+                    if let Some(ref pb) = progress {
+                        pb.inc(1);
+                        if result.status == PortStatus::Open {
+                            pb.set_message(format!("Found: {}/{} open", port, protocol));
+                            if live_open {
+                                pb.suspend(|| {
+                                    eprintln!(
+                                        "Discovered open port {}/{} on {}",
+                                        port, protocol, target
+                                    )
+                                });
+                            }
+                        }
+                    }
+                    if let Some(ref shared) = shared {
+                        shared.inc();
+                    }
+                    if let Some(ref stream) = stream {
+                        let _ = stream.send((target, result.clone()));
                     }
                 }
 
-                result
+                results
             }
         })
-        .buffer_unordered(config.concurrency.min(1000))
-        .collect()
+        .buffer_unordered(max_in_flight.div_ceil(batch_size))
+        .concat()
         .await;
+    // A token that fired after the last port came back cancelled nothing
+    let short = results.len() < total_ports;
+    let cancelled = short && config.cancel.as_ref().is_some_and(|t| t.is_cancelled());
+    let missing = short && !cancelled;
 
-    if let Some(pb) = progress {
+    if let Some(task) = controller_task {
+        task.abort();
+    }
+
+    if let (Some(shared), Some(pb)) = (&config.progress, &progress) {
+        shared.host_complete(pb);
+    } else if let Some(pb) = progress {
         pb.finish_with_message(format!(
-            "Scan complete - {} open ports found",
+            "Scan {} - {} open ports found",
+            if cancelled { "cancelled" } else { "complete" },
             results.iter().filter(|r| r.is_open()).count()
         ));
     }
+    if cancelled {
+        warnings.push(format!(
+            "scan cancelled after {} of {} ports",
+            results.len(),
+            total_ports
+        ));
+    } else if missing {
+        warnings.push(format!(
+            "scanner returned results for {} of {} ports",
+            results.len(),
+            total_ports
+        ));
+    }
 
     // Filter and sort results
     let mut filtered_results: Vec<PortResult> = if config.show_closed {
@@ -175,29 +502,125 @@ pub async fn run_scan(
     let duration = start_time.elapsed();
 
     // Create scan record
-    let record = ScanRecord::new(target.to_string(), target.to_string(), scan_type)
-        .finalize(filtered_results, duration.as_millis() as u64);
+    let mut record = ScanRecord::new(target.to_string(), target.to_string(), scan_type)
+        .finalize_with_warnings(filtered_results, duration.as_millis() as u64, warnings);
+    if let Some(ref spec) = config.port_spec {
+        record.port_spec = spec.to_string();
+    }
+    record.sampled = config.sample;
+    record.cancelled = cancelled;
+    record.partial = missing;
+    record.mac = scanner.target_mac();
+    if let Some(stats) = scanner.probe_stats() {
+        record.packet_loss = stats.loss_percent();
+        record.probe_stats = Some(stats);
+        if let Some(warning) = loss_warning(&stats) {
+            record.warnings.push(warning);
+            record.partial = true;
+        }
+    }
+    if let Some(source_ip) = scanner.source_ip() {
+        record.source_ip = Some(source_ip.to_string());
+        record.interface = scanner
+            .interface()
+            .map(str::to_string)
+            .or_else(|| syn::interface_for_ip(source_ip));
+    }
+    record.config = Some(ScanSettings {
+        scan_type: record.scan_type.clone(),
+        port_spec: record.port_spec.clone(),
+        timeout_ms: scanner.timeout().as_millis() as u64,
+        concurrency,
+        rate_limit: config.rate_limit,
+        min_rate: config.min_rate,
+        banner_grab: scanner.grabs_banners(),
+        syn_retries: scanner.retries(),
+    });
 
     Ok(record)
 }
 
+/// Estimated loss at or above which a scan gets a warning.
+const HIGH_LOSS_PERCENT: f64 = 10.0;
+
+/// Responsive ports needed before the loss estimate is trusted.
+const MIN_LOSS_SAMPLE: u64 = 10;
+
+/// Warn when enough probes went unanswered that filtered results are suspect.
+fn loss_warning(stats: &ProbeStats) -> Option<String> {
+    let loss = stats.loss_percent()?;
+    if stats.answered_ports < MIN_LOSS_SAMPLE || loss < HIGH_LOSS_PERCENT {
+        return None;
+    }
+    Some(format!(
+        "estimated packet loss {:.0}%: {} of {} SYNs to responsive ports went unanswered; \
+         filtered ports may be lost probes (try a lower --rate or more --syn-retries)",
+        loss,
+        stats.answered_port_probes - stats.answered_ports,
+        stats.answered_port_probes
+    ))
+}
+
+/// Estimate remaining time, never below what the rate limit allows.
+///
+/// Early in a scan the observed throughput reflects the initial burst, not
+/// the token bucket, so the plain estimate is wildly optimistic.
+fn rate_aware_eta(estimate: Duration, remaining: u64, rate_limit: u32) -> Duration {
+    if rate_limit == 0 {
+        return estimate;
+    }
+    let floor = Duration::from_secs_f64(remaining as f64 / f64::from(rate_limit));
+    estimate.max(floor)
+}
+
 /// Create a scanner based on scan type and configuration.
 pub fn create_scanner(
     scan_type: ScanType,
     config: ScanConfig,
 ) -> ScanResult<Arc<dyn Scanner>> {
     match scan_type {
-        ScanType::Connect => Ok(Arc::new(TcpConnectScanner::new(
-            config.target,
-            config.timeout,
-            config.grab_banners,
-        ))),
+        ScanType::Connect => {
+            let scanner =
+                TcpConnectScanner::new(config.target, config.timeout, config.grab_banners)
+                    .with_banner_timeout(config.banner_timeout())
+                    .with_banner_size(config.banner_size)
+                    .with_timeout_jitter(config.timeout_jitter)
+                    .with_fast_close(config.fast_close)
+                    .with_rst_retries(config.rst_retries)
+                    .with_closed_services(config.closed_services);
+            let scanner = match config.interact {
+                Some(script) => scanner.with_interact(script),
+                None => scanner,
+            };
+            let scanner = match config.source_ports {
+                Some(range) => scanner.with_source_ports(range),
+                None => scanner,
+            };
+            let scanner = match config.fast_reset_threshold {
+                Some(threshold) => scanner.with_fast_reset_threshold(threshold),
+                None => scanner,
+            };
+            #[cfg(feature = "tls")]
+            let scanner = if config.alpn {
+                scanner.with_alpn(&config.target_hostname)
+            } else {
+                scanner
+            };
+            let scanner = match config.interface {
+                Some(ref iface) => scanner.with_interface(iface)?,
+                None => scanner,
+            };
+            Ok(Arc::new(scanner))
+        }
         ScanType::Syn => {
             let scanner = SynScanner::new(
                 config.target,
                 config.interface.as_deref(),
                 config.timeout,
-            )?;
+            )?
+            .with_fragmentation(config.fragment)
+            .with_retries(config.syn_retries)
+            .with_os_profile(config.os_profile);
             Ok(Arc::new(scanner))
         }
         ScanType::Udp => Ok(Arc::new(UdpScanner::new(config.target, config.timeout))),
@@ -212,13 +635,24 @@ mod tests {
     fn test_scan_job_config() {
         let config = ScanJobConfig::new(vec![Port::new(80).unwrap(), Port::new(443).unwrap()])
             .with_concurrency(100)
-            .with_verbose()
+            .with_verbosity(2)
             .with_rate_limit(1000);
 
         assert_eq!(config.ports.len(), 2);
-        assert_eq!(config.concurrency, 100);
-        assert!(config.verbose);
+        assert_eq!(config.concurrency, Some(100));
+        assert_eq!(config.verbose, 2);
         assert_eq!(config.rate_limit, 1000);
+        assert!(config.port_spec.is_none());
+        assert_eq!(config.min_rate, 0);
+    }
+
+    #[test]
+    fn test_scan_job_config_from_spec() {
+        let spec: PortSpec = "22,80-82".parse().unwrap();
+        let config = ScanJobConfig::from_spec(spec);
+
+        assert_eq!(config.ports.len(), 4);
+        assert_eq!(config.port_spec.unwrap().to_string(), "22,80-82");
     }
 
     #[test]
@@ -232,4 +666,329 @@ mod tests {
         let scanner = create_scanner(ScanType::Connect, config);
         assert!(scanner.is_ok());
     }
+
+    #[tokio::test]
+    async fn test_run_scan_with_mock() {
+        let scanner = Arc::new(
+            MockScanner::default()
+                .with_open_ports([22, 80])
+                .with_port(443, PortStatus::Filtered),
+        );
+        let config = ScanJobConfig::from_spec("1-1000".parse().unwrap()).with_concurrency(50);
+
+        let record = run_scan(scanner.clone(), config).await.unwrap();
+        assert_eq!(scanner.probe_count(), 1000);
+        assert_eq!(record.open_ports, 2);
+        assert_eq!(record.filtered_ports, 1);
+        // Closed ports are dropped unless requested, and results are sorted
+        let ports: Vec<u16> = record.results.iter().map(|r| r.port.as_u16()).collect();
+        assert_eq!(ports, vec![22, 80, 443]);
+        assert_eq!(record.port_spec, "1-1000");
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_default_concurrency() {
+        let udp = || Arc::new(MockScanner::default().with_scan_type(ScanType::Udp));
+
+        let record = run_scan(udp(), ScanJobConfig::from_spec("1-10".parse().unwrap()))
+            .await
+            .unwrap();
+        assert_eq!(record.config.unwrap().concurrency, 20);
+
+        let config = ScanJobConfig::from_spec("1-10".parse().unwrap()).with_concurrency(300);
+        let record = run_scan(udp(), config).await.unwrap();
+        assert_eq!(record.config.unwrap().concurrency, 300);
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_with_closed() {
+        let scanner = Arc::new(MockScanner::default().with_open_ports([2]));
+        let config = ScanJobConfig::from_spec("1-5".parse().unwrap()).with_closed();
+
+        let record = run_scan(scanner, config).await.unwrap();
+        assert_eq!(record.results.len(), 5);
+        assert_eq!(record.closed_ports, 4);
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_respects_rate_limit() {
+        let scanner = Arc::new(MockScanner::default());
+        let config = ScanJobConfig::from_spec("1-15".parse().unwrap()).with_rate_limit(10);
+
+        let start = Instant::now();
+        run_scan(scanner, config).await.unwrap();
+        // A burst of 10 goes out at once; the last 5 wait 100ms each
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_shares_host_rate_limiter() {
+        let limiter = KeyedRateLimiter::new(10).unwrap();
+        let scan = |ip: &str, spec: &str| {
+            let scanner = Arc::new(MockScanner::new(ip.parse().unwrap()));
+            let config = ScanJobConfig::from_spec(spec.parse().unwrap())
+                .with_host_rate_limiter(limiter.clone());
+            run_scan(scanner, config)
+        };
+
+        // Each host's burst of 10 goes out at once
+        let start = Instant::now();
+        scan("10.0.0.1", "1-10").await.unwrap();
+        scan("10.0.0.2", "1-10").await.unwrap();
+        assert!(start.elapsed() < Duration::from_millis(300));
+
+        // The first host's bucket is still empty; 5 more wait 100ms each
+        scan("10.0.0.1", "11-15").await.unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_records_warnings() {
+        let scanner = Arc::new(MockScanner::default());
+        let config = ScanJobConfig::from_spec("1-10".parse().unwrap())
+            .with_concurrency(5000)
+            .with_backoff(0.0, 0.0)
+            .with_warnings(vec!["fell back to a connect scan".to_string()]);
+
+        let record = run_scan(scanner, config).await.unwrap();
+        assert_eq!(
+            record.warnings,
+            vec![
+                "fell back to a connect scan".to_string(),
+                "concurrency 5000 was clamped to 1000".to_string(),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_zero_concurrency() {
+        // Raised to one permit instead of waiting forever or panicking
+        for backoff in [0.0, 0.5] {
+            let scanner = Arc::new(MockScanner::default());
+            let mut config = ScanJobConfig::from_spec("1-10".parse().unwrap())
+                .with_closed()
+                .with_backoff(backoff, 0.0);
+            config.concurrency = Some(0);
+
+            let record = run_scan(scanner, config).await.unwrap();
+            assert_eq!(record.results.len(), 10);
+            assert_eq!(record.config.unwrap().concurrency, 1);
+        }
+        assert_eq!(
+            ScanJobConfig::default().with_concurrency(0).concurrency,
+            Some(1)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_clamps_adaptive_concurrency() {
+        // Backoff is on by default, so the controller runs this scan
+        let scanner = Arc::new(MockScanner::default());
+        let config = ScanJobConfig::from_spec("1-10".parse().unwrap()).with_concurrency(5000);
+        assert!(config.backoff_threshold > 0.0);
+
+        let record = run_scan(scanner, config).await.unwrap();
+        assert_eq!(record.config.unwrap().concurrency, MAX_CONCURRENCY);
+        assert_eq!(
+            record.warnings,
+            vec!["concurrency 5000 was clamped to 1000".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_streams_results() {
+        let scanner = Arc::new(MockScanner::default().with_open_ports([22, 80]));
+        let (sender, mut receiver) = tokio::sync::mpsc::unbounded_channel();
+        let config = ScanJobConfig::from_spec("1-100".parse().unwrap()).with_result_stream(sender);
+
+        let record = run_scan(scanner, config).await.unwrap();
+        let mut streamed = Vec::new();
+        while let Ok((_, result)) = receiver.try_recv() {
+            streamed.push(result);
+        }
+        // Closed ports are streamed even though the record drops them
+        assert_eq!(streamed.len(), 100);
+        assert_eq!(
+            streamed.iter().filter(|r| r.is_open()).count(),
+            record.open_ports
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_sampled() {
+        let scanner = Arc::new(MockScanner::default());
+        let config = ScanJobConfig::from_spec("1-1000".parse().unwrap())
+            .with_closed()
+            .with_sample(10.0);
+
+        let record = run_scan(scanner.clone(), config).await.unwrap();
+        assert_eq!(scanner.probe_count(), 100);
+        assert_eq!(record.ports_scanned, 100);
+        assert_eq!(record.sampled, Some(10.0));
+        assert_eq!(record.port_spec, "1-1000");
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_cancelled() {
+        let scanner = Arc::new(
+            MockScanner::default()
+                .with_open_ports([1, 2])
+                .with_delay(Duration::from_millis(50)),
+        );
+        let token = CancellationToken::new();
+        let config = ScanJobConfig::from_spec("1-100".parse().unwrap())
+            .with_concurrency(2)
+            .with_backoff(0.0, 0.0)
+            .with_cancellation(token.clone());
+
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(120)).await;
+            token.cancel();
+        });
+        let started = Instant::now();
+        let record = run_scan(scanner, config).await.unwrap();
+        canceller.await.unwrap();
+
+        // 100 probes at 2 x 50ms would take 2.5s
+        assert!(started.elapsed() < Duration::from_secs(1));
+        assert!(record.cancelled);
+        assert_eq!(record.open_ports, 2);
+        assert!(record.ports_scanned < 100);
+        assert!(record.warnings[0].starts_with("scan cancelled after"));
+    }
+
+    /// Answers batches with their last port left out.
+    struct ShortBatches(MockScanner);
+
+    #[async_trait::async_trait]
+    impl Scanner for ShortBatches {
+        fn scan_type(&self) -> ScanType {
+            self.0.scan_type()
+        }
+
+        fn requires_privileges(&self) -> bool {
+            false
+        }
+
+        async fn scan_port(&self, port: Port) -> PortResult {
+            self.0.scan_port(port).await
+        }
+
+        async fn scan_ports(&self, ports: &[Port]) -> Vec<PortResult> {
+            let mut results = self.0.scan_ports(ports).await;
+            results.pop();
+            results
+        }
+
+        fn target(&self) -> IpAddr {
+            self.0.target()
+        }
+
+        fn timeout(&self) -> Duration {
+            self.0.timeout()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_short_batches_are_not_cancelled() {
+        let scanner = Arc::new(ShortBatches(MockScanner::default()));
+        let config = ScanJobConfig::from_spec("1-20".parse().unwrap())
+            .with_closed()
+            .with_batch_size(5);
+
+        let record = run_scan(scanner, config).await.unwrap();
+        assert!(!record.cancelled);
+        assert!(record.partial);
+        assert_eq!(record.results.len(), 16);
+        assert_eq!(
+            record.warnings,
+            vec!["scanner returned results for 16 of 20 ports".to_string()]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_keeps_dispatched_probes() {
+        // Clean completion: every port is probed once and reported
+        let scanner = Arc::new(MockScanner::default().with_delay(Duration::from_millis(5)));
+        let config = ScanJobConfig::from_spec("1-200".parse().unwrap())
+            .with_concurrency(50)
+            .with_closed();
+        let record = run_scan(scanner.clone(), config).await.unwrap();
+        assert_eq!(scanner.started_count(), 200);
+        assert_eq!(record.ports_scanned, 200);
+
+        // Cancelled mid-scan: probes in flight at the time still report
+        let scanner = Arc::new(MockScanner::default().with_delay(Duration::from_millis(50)));
+        let token = CancellationToken::new();
+        let config = ScanJobConfig::from_spec("1-100".parse().unwrap())
+            .with_concurrency(4)
+            .with_backoff(0.0, 0.0)
+            .with_closed()
+            .with_cancellation(token.clone());
+
+        let canceller = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(120)).await;
+            token.cancel();
+        });
+        let record = run_scan(scanner.clone(), config).await.unwrap();
+        canceller.await.unwrap();
+
+        assert!(record.cancelled);
+        assert!(scanner.started_count() > 0 && scanner.started_count() < 100);
+        assert_eq!(record.ports_scanned, scanner.started_count());
+        assert_eq!(scanner.probe_count(), scanner.started_count());
+    }
+
+    #[tokio::test]
+    async fn test_run_scan_in_batches() {
+        // Batches larger than the controller's floor, or than the
+        // concurrency without one, are cut down rather than waiting forever
+        for backoff in [0.5, 0.0] {
+            let scanner = Arc::new(MockScanner::default().with_open_ports([3, 150, 299]));
+            let config = ScanJobConfig::from_spec("1-300".parse().unwrap())
+                .with_concurrency(40)
+                .with_backoff(backoff, 0.0)
+                .with_batch_size(64);
+
+            let record = run_scan(scanner.clone(), config).await.unwrap();
+            assert_eq!(scanner.probe_count(), 300);
+            let open: Vec<u16> = record.results.iter().map(|r| r.port.as_u16()).collect();
+            assert_eq!(open, vec![3, 150, 299]);
+        }
+    }
+
+    #[test]
+    fn test_sample_size() {
+        assert_eq!(sample_size(1000, 10.0), 100);
+        assert_eq!(sample_size(1000, 100.0), 1000);
+        // Tiny samples still probe something
+        assert_eq!(sample_size(10, 0.1), 1);
+        assert_eq!(sample_size(0, 50.0), 0);
+    }
+
+    #[test]
+    fn test_rate_aware_eta() {
+        let optimistic = Duration::from_secs(2);
+        // 1000 ports at 100/s cannot finish in under 10 seconds
+        assert_eq!(rate_aware_eta(optimistic, 1000, 100), Duration::from_secs(10));
+        assert_eq!(rate_aware_eta(Duration::from_secs(30), 1000, 100), Duration::from_secs(30));
+        assert_eq!(rate_aware_eta(optimistic, 1000, 0), optimistic);
+    }
+
+    #[test]
+    fn test_loss_warning() {
+        let stats = |answered_ports, answered_port_probes| ProbeStats {
+            sent: answered_port_probes,
+            retransmissions: answered_port_probes - answered_ports,
+            answered_ports,
+            answered_port_probes,
+        };
+        // 20 of 100 SYNs to responsive ports needed a resend
+        let warning = loss_warning(&stats(80, 100)).unwrap();
+        assert!(warning.starts_with("estimated packet loss 20%: 20 of 100"));
+        assert_eq!(loss_warning(&stats(98, 100)), None);
+        // Too few responsive ports to judge
+        assert_eq!(loss_warning(&stats(5, 10)), None);
+        assert_eq!(loss_warning(&ProbeStats::default()), None);
+    }
 }
@@ -0,0 +1,70 @@
+//! Privilege detection for raw socket scans.
+//!
+//! Raw sockets need root on most platforms. On Linux the `CAP_NET_RAW`
+//! capability is enough, so a binary granted it with
+//! `setcap cap_net_raw+ep` can run SYN and UDP scans as a regular user.
+
+/// Capability number of `CAP_NET_RAW` (see `linux/capability.h`).
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+const CAP_NET_RAW: u32 = 13;
+
+/// Whether the process may open raw sockets, via root or `CAP_NET_RAW`.
+pub fn has_raw_socket_access() -> bool {
+    is_root() || has_cap_net_raw()
+}
+
+/// Check if running with root/admin privileges.
+pub fn is_root() -> bool {
+    #[cfg(unix)]
+    {
+        unsafe { libc::geteuid() == 0 }
+    }
+    #[cfg(windows)]
+    {
+        false
+    }
+    #[cfg(not(any(unix, windows)))]
+    {
+        false
+    }
+}
+
+/// Whether `CAP_NET_RAW` is in the process's effective capability set.
+pub fn has_cap_net_raw() -> bool {
+    #[cfg(target_os = "linux")]
+    {
+        std::fs::read_to_string("/proc/self/status")
+            .map(|status| effective_capability(&status, CAP_NET_RAW))
+            .unwrap_or(false)
+    }
+    #[cfg(not(target_os = "linux"))]
+    {
+        false
+    }
+}
+
+/// Test `cap` against the `CapEff` bitmask of a `/proc/<pid>/status` file.
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn effective_capability(status: &str, cap: u32) -> bool {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))
+        .and_then(|mask| u64::from_str_radix(mask.trim(), 16).ok())
+        .is_some_and(|mask| mask & (1 << cap) != 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_effective_capability() {
+        let status = "Name:\tscuttle\nCapInh:\t0000000000000000\nCapEff:\t0000000000002000\n";
+        assert!(effective_capability(status, CAP_NET_RAW));
+        assert!(!effective_capability(status, 12));
+
+        let unprivileged = "CapEff:\t0000000000000000\n";
+        assert!(!effective_capability(unprivileged, CAP_NET_RAW));
+        assert!(!effective_capability("Name:\tscuttle\n", CAP_NET_RAW));
+    }
+}
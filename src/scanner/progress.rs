@@ -0,0 +1,79 @@
+//! Shared progress reporting for multi-host scans.
+//!
+//! When scanning a range, each host's `run_scan` reports into one shared
+//! handle instead of owning its own bar, so the user sees a single overall
+//! bar (hosts × ports) with a spinner per host currently being scanned.
+
+use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Progress handle shared by concurrently running host scans.
+#[derive(Debug, Clone)]
+pub struct ScanProgress {
+    bars: MultiProgress,
+    overall: ProgressBar,
+    completed_hosts: Arc<AtomicUsize>,
+    total_hosts: usize,
+}
+
+impl ScanProgress {
+    /// Create a progress display for `total_hosts` hosts of `ports_per_host` ports each.
+    pub fn new(total_hosts: usize, ports_per_host: usize) -> Self {
+        let bars = MultiProgress::new();
+        let overall = bars.add(ProgressBar::new((total_hosts * ports_per_host) as u64));
+        overall.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {pos}/{len} ({percent}%) | {msg}")
+                .unwrap()
+                .progress_chars("=>-"),
+        );
+        overall.set_message(format!("0/{} hosts complete", total_hosts));
+
+        Self {
+            bars,
+            overall,
+            completed_hosts: Arc::new(AtomicUsize::new(0)),
+            total_hosts,
+        }
+    }
+
+    /// Add a spinner for a host that is starting its scan.
+    pub fn host_bar(&self, target: IpAddr, ports: usize) -> ProgressBar {
+        let bar = self.bars.add(ProgressBar::new(ports as u64));
+        bar.set_style(
+            ProgressStyle::default_spinner()
+                .template("  {spinner:.green} {prefix}: {pos}/{len} {msg}")
+                .unwrap(),
+        );
+        bar.set_prefix(target.to_string());
+        bar
+    }
+
+    /// Record that one port probe finished.
+    pub fn inc(&self) {
+        self.overall.inc(1);
+    }
+
+    /// Remove a host's spinner and count it as complete.
+    pub fn host_complete(&self, bar: &ProgressBar) {
+        bar.finish_and_clear();
+        self.bars.remove(bar);
+
+        let done = self.completed_hosts.fetch_add(1, Ordering::Relaxed) + 1;
+        self.overall
+            .set_message(format!("{}/{} hosts complete", done, self.total_hosts));
+    }
+
+    /// Finish the overall bar.
+    pub fn finish(&self) {
+        self.overall
+            .finish_with_message(format!("Scan complete - {} hosts", self.total_hosts));
+    }
+
+    /// Run a closure with the bars hidden, e.g. to print results cleanly.
+    pub fn suspend<F: FnOnce() -> R, R>(&self, f: F) -> R {
+        self.bars.suspend(f)
+    }
+}
@@ -1,9 +1,11 @@
 //! Rate limiting for network scans.
 //!
 //! Provides token bucket rate limiting to control the pace of scanning
-//! and prevent network flooding.
+//! and prevent network flooding, overall and for each target.
 
-use governor::{Quota, RateLimiter as GovLimiter};
+use crate::error::{ScanError, ScanResult};
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter as GovLimiter};
+use std::net::IpAddr;
 use std::num::NonZeroU32;
 use std::sync::Arc;
 
@@ -14,8 +16,23 @@ use std::sync::Arc;
 /// - Avoiding network congestion
 /// - Evading intrusion detection systems
 /// - Being a good network citizen
+///
+/// An [`unlimited`](Self::unlimited) limiter never waits, so callers can
+/// treat a rate of 0 ("no limit") the same as any other rate.
+#[derive(Debug, Clone)]
 pub struct RateLimiter {
-    limiter: Arc<GovLimiter<governor::state::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock>>,
+    bucket: Bucket,
+}
+
+type DirectLimiter = GovLimiter<governor::state::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock>;
+
+/// Token bucket backing a [`RateLimiter`]; clones share state.
+#[derive(Debug, Clone)]
+enum Bucket {
+    /// No limit: every wait returns immediately.
+    Unlimited,
+    /// Governed by a shared token bucket.
+    Limited(Arc<DirectLimiter>),
 }
 
 impl RateLimiter {
@@ -24,54 +41,138 @@ impl RateLimiter {
     /// # Arguments
     /// * `rate` - Maximum number of operations per second
     ///
-    /// # Panics
-    /// Panics if rate is 0. Use Option<RateLimiter> for optional rate limiting.
-    pub fn new(rate: u32) -> Self {
-        let rate = NonZeroU32::new(rate).expect("rate must be > 0");
-        let quota = Quota::per_second(rate);
-        let limiter = GovLimiter::direct(quota);
+    /// # Errors
+    /// Returns [`ScanError::InvalidConfig`] if rate is 0; use
+    /// [`from_limit`](Self::from_limit) to treat 0 as unlimited.
+    pub fn new(rate: u32) -> ScanResult<Self> {
+        let rate = non_zero(rate, "rate")?;
+        Ok(Self::from_quota(Quota::per_second(rate)))
+    }
 
+    /// Create a limiter that never waits.
+    pub const fn unlimited() -> Self {
         Self {
-            limiter: Arc::new(limiter),
+            bucket: Bucket::Unlimited,
         }
     }
 
+    /// Create a limiter from a CLI-style limit, where 0 means unlimited.
+    pub fn from_limit(rate: u32) -> Self {
+        Self::new(rate).unwrap_or_else(|_| Self::unlimited())
+    }
+
+    /// Check if this limiter never waits.
+    pub fn is_unlimited(&self) -> bool {
+        matches!(self.bucket, Bucket::Unlimited)
+    }
+
     /// Wait until a token is available.
     ///
     /// This method blocks (async) until the rate limit allows another operation.
     pub async fn wait(&self) {
         // Wait until we can proceed
-        self.limiter.until_ready().await;
+        if let Bucket::Limited(ref limiter) = self.bucket {
+            limiter.until_ready().await;
+        }
     }
 
     /// Try to acquire a token without waiting.
     ///
     /// Returns `true` if a token was available, `false` otherwise.
     pub fn try_acquire(&self) -> bool {
-        self.limiter.check().is_ok()
+        match self.bucket {
+            Bucket::Unlimited => true,
+            Bucket::Limited(ref limiter) => limiter.check().is_ok(),
+        }
     }
 
     /// Create a rate limiter with burst capacity.
     ///
     /// Allows a burst of operations up to `burst` before rate limiting kicks in.
-    pub fn with_burst(rate: u32, burst: u32) -> Self {
-        let rate = NonZeroU32::new(rate).expect("rate must be > 0");
-        let burst = NonZeroU32::new(burst).expect("burst must be > 0");
-        let quota = Quota::per_second(rate).allow_burst(burst);
-        let limiter = GovLimiter::direct(quota);
+    ///
+    /// # Errors
+    /// Returns [`ScanError::InvalidConfig`] if rate or burst is 0.
+    pub fn with_burst(rate: u32, burst: u32) -> ScanResult<Self> {
+        let rate = non_zero(rate, "rate")?;
+        let burst = non_zero(burst, "burst")?;
+        Ok(Self::from_quota(Quota::per_second(rate).allow_burst(burst)))
+    }
 
+    fn from_quota(quota: Quota) -> Self {
         Self {
-            limiter: Arc::new(limiter),
+            bucket: Bucket::Limited(Arc::new(GovLimiter::direct(quota))),
         }
     }
 }
 
-impl Clone for RateLimiter {
-    fn clone(&self) -> Self {
+/// A rate limiter with a separate token bucket for each target.
+///
+/// Caps how fast any one host is probed, so scanning several hosts at once
+/// neither floods one of them nor lets a slow host hold up the others.
+/// Clones share buckets. Like [`RateLimiter`], an unlimited keyed limiter
+/// never waits.
+#[derive(Debug, Clone)]
+pub struct KeyedRateLimiter {
+    rate: u32,
+    buckets: Option<Arc<DefaultKeyedRateLimiter<IpAddr>>>,
+}
+
+impl KeyedRateLimiter {
+    /// Create a limiter allowing `rate` packets per second to each target.
+    ///
+    /// # Errors
+    /// Returns [`ScanError::InvalidConfig`] if rate is 0; use
+    /// [`from_limit`](Self::from_limit) to treat 0 as unlimited.
+    pub fn new(rate: u32) -> ScanResult<Self> {
+        let quota = Quota::per_second(non_zero(rate, "rate")?);
+        Ok(Self {
+            rate,
+            buckets: Some(Arc::new(GovLimiter::keyed(quota))),
+        })
+    }
+
+    /// Create a limiter that never waits.
+    pub const fn unlimited() -> Self {
         Self {
-            limiter: Arc::clone(&self.limiter),
+            rate: 0,
+            buckets: None,
+        }
+    }
+
+    /// Create a limiter from a CLI-style limit, where 0 means unlimited.
+    pub fn from_limit(rate: u32) -> Self {
+        Self::new(rate).unwrap_or_else(|_| Self::unlimited())
+    }
+
+    /// Packets per second allowed to each target (0 = unlimited).
+    pub fn rate(&self) -> u32 {
+        self.rate
+    }
+
+    /// Check if this limiter never waits.
+    pub fn is_unlimited(&self) -> bool {
+        self.buckets.is_none()
+    }
+
+    /// Wait until `target`'s bucket has a token.
+    pub async fn wait(&self, target: IpAddr) {
+        if let Some(ref buckets) = self.buckets {
+            buckets.until_key_ready(&target).await;
         }
     }
+
+    /// Try to take a token from `target`'s bucket without waiting.
+    pub fn try_acquire(&self, target: IpAddr) -> bool {
+        self.buckets
+            .as_ref()
+            .is_none_or(|buckets| buckets.check_key(&target).is_ok())
+    }
+}
+
+/// Validate that a limiter parameter is positive.
+fn non_zero(value: u32, name: &str) -> ScanResult<NonZeroU32> {
+    NonZeroU32::new(value)
+        .ok_or_else(|| ScanError::InvalidConfig(format!("{} must be greater than 0", name)))
 }
 
 #[cfg(test)]
@@ -80,21 +181,21 @@ mod tests {
 
     #[tokio::test]
     async fn test_rate_limiter_creation() {
-        let limiter = RateLimiter::new(100);
+        let limiter = RateLimiter::new(100).unwrap();
         // Should be able to acquire immediately
         assert!(limiter.try_acquire());
     }
 
     #[tokio::test]
     async fn test_rate_limiter_wait() {
-        let limiter = RateLimiter::new(1000);
+        let limiter = RateLimiter::new(1000).unwrap();
         // Wait should complete quickly with high rate
         limiter.wait().await;
     }
 
     #[test]
     fn test_rate_limiter_clone() {
-        let limiter1 = RateLimiter::new(100);
+        let limiter1 = RateLimiter::new(100).unwrap();
         let limiter2 = limiter1.clone();
 
         // Both should share the same internal state
@@ -103,4 +204,43 @@ mod tests {
         // (depends on timing, so we just verify it doesn't panic)
         let _ = limiter2.try_acquire();
     }
+
+    #[test]
+    fn test_rate_limiter_rejects_zero() {
+        assert!(matches!(
+            RateLimiter::new(0),
+            Err(ScanError::InvalidConfig(_))
+        ));
+        assert!(RateLimiter::with_burst(100, 0).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_unlimited() {
+        let limiter = RateLimiter::from_limit(0);
+        assert!(limiter.is_unlimited());
+        for _ in 0..1000 {
+            assert!(limiter.try_acquire());
+        }
+        limiter.wait().await;
+
+        assert!(!RateLimiter::from_limit(100).is_unlimited());
+    }
+
+    #[test]
+    fn test_keyed_rate_limiter() {
+        let a: IpAddr = "10.0.0.1".parse().unwrap();
+        let b: IpAddr = "10.0.0.2".parse().unwrap();
+
+        let limiter = KeyedRateLimiter::new(1).unwrap();
+        assert_eq!(limiter.rate(), 1);
+        assert!(limiter.try_acquire(a));
+        assert!(!limiter.clone().try_acquire(a));
+        // Each target has its own bucket
+        assert!(limiter.try_acquire(b));
+
+        let unlimited = KeyedRateLimiter::from_limit(0);
+        assert!(unlimited.is_unlimited());
+        assert!((0..1000).all(|_| unlimited.try_acquire(a)));
+        assert!(KeyedRateLimiter::new(0).is_err());
+    }
 }
@@ -4,15 +4,25 @@
 //! socket API. This is the most reliable scanning method but also
 //! the most detectable as it completes the full TCP handshake.
 
-use crate::banner::grab_banner_from_stream;
+use crate::banner::interact::{run_script, Script};
+#[cfg(feature = "tls")]
+use crate::banner::tls::AlpnProbe;
+use crate::banner::{grab_banner_from_stream, DEFAULT_BANNER_SIZE};
 use crate::error::{ScanError, ScanResult};
+use crate::scanner::syn::find_interface;
 use crate::scanner::traits::{PortResult, PortStatus, ScanType, Scanner};
-use crate::services::get_service_description;
-use crate::types::Port;
+use crate::services::{ServiceDetector, ServiceDetectors};
+use crate::types::{Port, PortRange};
 use async_trait::async_trait;
-use std::net::{IpAddr, SocketAddr};
+use rand::Rng;
+use socket2::SockRef;
+use std::future::Future;
+use std::io::ErrorKind;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
 use std::time::{Duration, Instant};
-use tokio::net::TcpStream;
+use tokio::net::{TcpSocket, TcpStream};
 use tokio::time::timeout;
 
 /// TCP Connect Scanner.
@@ -29,7 +39,88 @@ use tokio::time::timeout;
 pub struct TcpConnectScanner {
     target: IpAddr,
     timeout: Duration,
+    timeout_jitter: Duration,
     grab_banners: bool,
+    banner_timeout: Duration,
+    banner_size: usize,
+    interact: Option<Script>,
+    fast_close: bool,
+    fast_reset_threshold: Option<Duration>,
+    rst_retries: u32,
+    closed_services: bool,
+    #[cfg(feature = "tls")]
+    alpn: Option<AlpnProbe>,
+    services: ServiceDetectors,
+    source: Option<SourceBinding>,
+    source_ports: Option<SourcePorts>,
+    /// Local address of the first successful connection.
+    observed_source: OnceLock<IpAddr>,
+}
+
+/// Local interface and address that outgoing connections are bound to.
+#[derive(Debug, Clone)]
+struct SourceBinding {
+    interface: String,
+    ip: IpAddr,
+}
+
+/// Source ports handed out in turn to outgoing connections.
+#[derive(Debug)]
+struct SourcePorts {
+    range: PortRange,
+    next: AtomicUsize,
+}
+
+impl SourcePorts {
+    /// The next port of the range, wrapping around at its end.
+    fn next(&self) -> u16 {
+        let offset = self.next.fetch_add(1, Ordering::Relaxed) % self.range.len();
+        self.range.start().as_u16() + offset as u16
+    }
+}
+
+/// Source ports tried per connection when the chosen one is unusable.
+const MAX_BIND_ATTEMPTS: usize = 16;
+
+/// Retries of a connect that failed for want of a local port or socket.
+const TRANSIENT_RETRIES: u32 = 3;
+
+/// Pause before the first of those retries, doubled for each one after.
+const TRANSIENT_BACKOFF: Duration = Duration::from_millis(5);
+
+/// Pause before retrying a fast refusal.
+const RST_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// Refusals quicker than this are retried when no fast reset threshold is set.
+const DEFAULT_RST_RETRY_THRESHOLD: Duration = Duration::from_millis(10);
+
+/// Whether a connect error reflects momentary pressure on this host's
+/// sockets rather than anything the target did.
+fn is_transient(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        ErrorKind::AddrInUse | ErrorKind::AddrNotAvailable | ErrorKind::WouldBlock
+    )
+}
+
+/// Run `connect`, retrying with a short backoff while it fails transiently.
+async fn retry_transient<T, F, Fut>(mut connect: F) -> std::io::Result<T>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = std::io::Result<T>>,
+{
+    let mut backoff = TRANSIENT_BACKOFF;
+    for _ in 0..TRANSIENT_RETRIES {
+        match connect().await {
+            Err(e) if is_transient(&e) => {
+                tracing::trace!(error = %e, ?backoff, "transient connect failure, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+    connect().await
 }
 
 impl TcpConnectScanner {
@@ -39,98 +130,459 @@ impl TcpConnectScanner {
     /// * `target` - Target IP address to scan
     /// * `timeout` - Connection timeout per port
     /// * `grab_banners` - Whether to attempt banner grabbing on open ports
+    ///
+    /// Banner reads use `timeout` unless overridden with
+    /// [`with_banner_timeout`](Self::with_banner_timeout).
     pub fn new(target: IpAddr, timeout: Duration, grab_banners: bool) -> Self {
         Self {
             target,
             timeout,
+            timeout_jitter: Duration::ZERO,
             grab_banners,
+            banner_timeout: timeout,
+            banner_size: DEFAULT_BANNER_SIZE,
+            interact: None,
+            fast_close: false,
+            fast_reset_threshold: None,
+            rst_retries: 0,
+            closed_services: true,
+            #[cfg(feature = "tls")]
+            alpn: None,
+            services: ServiceDetectors::default(),
+            source: None,
+            source_ports: None,
+            observed_source: OnceLock::new(),
         }
     }
 
-    /// Attempt to connect to the target address.
-    async fn attempt_connect(&self, addr: SocketAddr) -> ScanResult<TcpStream> {
-        match timeout(self.timeout, TcpStream::connect(addr)).await {
-            Ok(Ok(stream)) => Ok(stream),
-            Ok(Err(e)) => {
-                let error_str = e.to_string().to_lowercase();
-                if error_str.contains("refused") {
-                    Err(ScanError::ConnectionRefused)
-                } else if error_str.contains("unreachable") {
-                    if error_str.contains("host") {
-                        Err(ScanError::HostUnreachable)
-                    } else {
-                        Err(ScanError::NetworkUnreachable(e.to_string()))
-                    }
-                } else {
-                    Err(ScanError::ConnectionFailed {
-                        target: self.target.to_string(),
-                        port: addr.port(),
-                        reason: e.to_string(),
-                    })
+    /// Send connections from the named interface instead of the default route.
+    ///
+    /// Sockets are bound to the interface's address for the target's family;
+    /// on Linux they are also pinned with `SO_BINDTODEVICE` when permitted.
+    ///
+    /// # Errors
+    /// Returns an error if the interface does not exist or has no address of
+    /// the target's family.
+    pub fn with_interface(mut self, name: &str) -> ScanResult<Self> {
+        let interface = find_interface(Some(name))?;
+        let ip = interface
+            .ips
+            .iter()
+            .map(|net| net.ip())
+            .find(|ip| ip.is_ipv4() == self.target.is_ipv4())
+            .ok_or_else(|| {
+                ScanError::InvalidConfig(format!(
+                    "Interface {} has no {} address",
+                    name,
+                    if self.target.is_ipv4() { "IPv4" } else { "IPv6" }
+                ))
+            })?;
+
+        self.source = Some(SourceBinding {
+            interface: interface.name,
+            ip,
+        });
+        Ok(self)
+    }
+
+    /// Bind connections to source ports from `range`, taken in turn from a
+    /// random starting point.
+    ///
+    /// Sockets set `SO_REUSEADDR`, so a port can serve connections to
+    /// different target ports at once and a port in TIME_WAIT is reused.
+    /// Large scans then work even where the OS's ephemeral range is small.
+    /// A port that cannot be used, such as one still connected to the same
+    /// target port, is skipped for the next one in the range.
+    pub fn with_source_ports(mut self, range: PortRange) -> Self {
+        self.source_ports = Some(SourcePorts {
+            range,
+            next: AtomicUsize::new(rand::thread_rng().gen_range(0..range.len())),
+        });
+        self
+    }
+
+    /// Set the timeout for each banner read.
+    pub fn with_banner_timeout(mut self, timeout: Duration) -> Self {
+        self.banner_timeout = timeout;
+        self
+    }
+
+    /// Randomize each connect's timeout within `jitter` of the configured one.
+    ///
+    /// Spreads out the timeouts of probes started together, so a large scan
+    /// does not see thousands of them expire at the same instant.
+    pub fn with_timeout_jitter(mut self, jitter: Duration) -> Self {
+        self.timeout_jitter = jitter;
+        self
+    }
+
+    /// Set the maximum number of bytes captured per banner.
+    pub fn with_banner_size(mut self, size: usize) -> Self {
+        self.banner_size = size;
+        self
+    }
+
+    /// Run `script` on each open port and record its transcript as the
+    /// banner, in place of the usual banner grab.
+    pub fn with_interact(mut self, script: Script) -> Self {
+        self.interact = Some(script);
+        self
+    }
+
+    /// Close connections with a RST instead of a FIN.
+    ///
+    /// Sets `SO_LINGER` to zero on each connection, so closing it aborts the
+    /// connection and the local port is free at once rather than sitting in
+    /// TIME_WAIT for a minute or more. Back-to-back large scans then no longer
+    /// run out of local ports. The target sees a reset instead of an orderly
+    /// close, which is more noticeable and can cut short data still in flight.
+    pub fn with_fast_close(mut self, fast_close: bool) -> Self {
+        self.fast_close = fast_close;
+        self
+    }
+
+    /// Flag refusals that arrive within `threshold` as fast resets.
+    ///
+    /// A closed port on a host answers with a RST from its TCP stack, while
+    /// some load balancers and middleboxes reset ports they are shedding
+    /// before the connection gets anywhere; those refusals come back
+    /// noticeably faster. The OS only reports "refused", so the response
+    /// time is the only signal, and the right threshold depends on the
+    /// path's round trip time.
+    pub fn with_fast_reset_threshold(mut self, threshold: Duration) -> Self {
+        self.fast_reset_threshold = Some(threshold);
+        self
+    }
+
+    /// Retry fast refusals up to `retries` times before calling a port closed.
+    ///
+    /// Some stateful firewalls reset the first connection to a port and let
+    /// the next one through. A refusal faster than the fast reset threshold,
+    /// or than 10ms without one, is retried after a short pause, and the
+    /// port is reported open if a retry connects; otherwise the first result
+    /// stands. Closed ports take longer to settle.
+    pub fn with_rst_retries(mut self, retries: u32) -> Self {
+        self.rst_retries = retries;
+        self
+    }
+
+    /// Whether to name the likely service of closed ports.
+    ///
+    /// On by default. A scan that only reports open ports can turn it off
+    /// to skip the lookup, and the allocation of a service name, for every
+    /// closed port; closed results then carry an empty service.
+    pub fn with_closed_services(mut self, closed_services: bool) -> Self {
+        self.closed_services = closed_services;
+        self
+    }
+
+    /// Record the ALPN protocol of open ports that speak TLS.
+    ///
+    /// Each open port gets a second connection for a TLS handshake offering
+    /// `h2` and `http/1.1`, within the banner timeout; `hostname` is sent as
+    /// the SNI name if it is not an address. Ports that do not speak TLS,
+    /// or select no protocol, record none.
+    #[cfg(feature = "tls")]
+    pub fn with_alpn(mut self, hostname: &str) -> Self {
+        self.alpn = Some(AlpnProbe::new(self.target, hostname));
+        self
+    }
+
+    /// Register a custom detector to refine service names on scanned ports.
+    ///
+    /// Detectors run after the built-in port map; see [`ServiceDetectors`].
+    pub fn with_service_detector(mut self, detector: impl ServiceDetector + 'static) -> Self {
+        self.services.register(detector);
+        self
+    }
+
+    /// Open a connection, from the bound interface and source ports if set.
+    async fn connect(&self, addr: SocketAddr) -> std::io::Result<TcpStream> {
+        let Some(ref ports) = self.source_ports else {
+            if self.source.is_none() {
+                return TcpStream::connect(addr).await;
+            }
+            return self.bound_socket(addr, 0)?.connect(addr).await;
+        };
+
+        let mut attempts = ports.range.len().min(MAX_BIND_ATTEMPTS);
+        loop {
+            let port = ports.next();
+            let result = match self.bound_socket(addr, port) {
+                Ok(socket) => socket.connect(addr).await,
+                Err(e) => Err(e),
+            };
+            // Binding fails while another socket holds the port exclusively,
+            // and connecting while the port is still tied to this target port
+            match result {
+                Err(e)
+                    if matches!(e.kind(), ErrorKind::AddrInUse | ErrorKind::AddrNotAvailable)
+                        && attempts > 1 =>
+                {
+                    tracing::trace!(port, error = %e, "source port unusable");
+                    attempts -= 1;
                 }
+                result => return result,
             }
-            Err(_) => Err(ScanError::Timeout),
         }
     }
-}
 
-#[async_trait]
-impl Scanner for TcpConnectScanner {
-    fn scan_type(&self) -> ScanType {
-        ScanType::Connect
+    /// A socket bound to the source interface's address, or the wildcard
+    /// address, and to `port` (0 lets the OS pick).
+    fn bound_socket(&self, addr: SocketAddr, port: u16) -> std::io::Result<TcpSocket> {
+        let socket = if addr.is_ipv4() {
+            TcpSocket::new_v4()?
+        } else {
+            TcpSocket::new_v6()?
+        };
+
+        let ip = match self.source {
+            Some(ref source) => {
+                // SO_BINDTODEVICE needs CAP_NET_RAW; the address binding still applies without it
+                #[cfg(target_os = "linux")]
+                if let Err(e) = socket.bind_device(Some(source.interface.as_bytes())) {
+                    tracing::trace!(interface = %source.interface, error = %e, "bind to device failed");
+                }
+                source.ip
+            }
+            None if addr.is_ipv4() => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            None => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+
+        if port != 0 {
+            socket.set_reuseaddr(true)?;
+        }
+        socket.bind(SocketAddr::new(ip, port))?;
+        Ok(socket)
     }
 
-    fn requires_privileges(&self) -> bool {
-        false
+    /// Timeout for one connect, jittered if configured; never below 1ms.
+    fn connect_timeout(&self) -> Duration {
+        if self.timeout_jitter.is_zero() {
+            return self.timeout;
+        }
+        let low = self.timeout.saturating_sub(self.timeout_jitter);
+        let high = self.timeout + self.timeout_jitter;
+        rand::thread_rng()
+            .gen_range(low..=high)
+            .max(Duration::from_millis(1))
     }
 
-    fn target(&self) -> IpAddr {
-        self.target
+    /// Whether a refusal that took `elapsed` is fast enough to retry.
+    fn retries_refusal(&self, elapsed: Duration) -> bool {
+        elapsed
+            < self
+                .fast_reset_threshold
+                .unwrap_or(DEFAULT_RST_RETRY_THRESHOLD)
     }
 
-    fn timeout(&self) -> Duration {
-        self.timeout
+    /// Connect, retrying fast refusals as set by
+    /// [`with_rst_retries`](Self::with_rst_retries).
+    ///
+    /// Returns the result to report and how long its attempt took.
+    async fn connect_retrying_rst(&self, addr: SocketAddr) -> (ScanResult<TcpStream>, Duration) {
+        let start = Instant::now();
+        let first = self.attempt_connect(addr).await;
+        let elapsed = start.elapsed();
+        if !matches!(first, Err(ScanError::ConnectionRefused)) || !self.retries_refusal(elapsed) {
+            return (first, elapsed);
+        }
+
+        for attempt in 1..=self.rst_retries {
+            tokio::time::sleep(RST_RETRY_DELAY).await;
+            let start = Instant::now();
+            match self.attempt_connect(addr).await {
+                Ok(stream) => {
+                    tracing::debug!(attempt, "connected after a fast reset");
+                    return (Ok(stream), start.elapsed());
+                }
+                Err(ScanError::ConnectionRefused) if self.retries_refusal(start.elapsed()) => {}
+                Err(_) => break,
+            }
+        }
+        (first, elapsed)
     }
 
-    async fn scan_port(&self, port: Port) -> PortResult {
+    /// Attempt to connect to the target address.
+    async fn attempt_connect(&self, addr: SocketAddr) -> ScanResult<TcpStream> {
+        let connect = retry_transient(|| self.connect(addr));
+        match timeout(self.connect_timeout(), connect).await {
+            Ok(Ok(stream)) => Ok(stream),
+            // Under heavy concurrency these say nothing about the port
+            Ok(Err(e)) if is_transient(&e) => Err(ScanError::LocalResources(e.to_string())),
+            // Classified by kind, so that the common refusal allocates nothing
+            Ok(Err(e)) => Err(match e.kind() {
+                ErrorKind::ConnectionRefused => ScanError::ConnectionRefused,
+                ErrorKind::HostUnreachable => ScanError::HostUnreachable,
+                ErrorKind::NetworkUnreachable => ScanError::NetworkUnreachable(e.to_string()),
+                _ => ScanError::ConnectionFailed {
+                    target: self.target.to_string(),
+                    port: addr.port(),
+                    reason: e.to_string(),
+                },
+            }),
+            Err(_) => Err(ScanError::Timeout),
+        }
+    }
+
+    /// Probe one port: connect, then grab a banner or run the script on it
+    /// if it is open.
+    async fn probe(&self, port: Port) -> PortResult {
         let port_num = port.as_u16();
         let addr = SocketAddr::new(self.target, port_num);
-        let service = get_service_description(port_num).to_string();
-        let start = Instant::now();
+        let (outcome, elapsed) = self.connect_retrying_rst(addr).await;
 
-        match self.attempt_connect(addr).await {
+        match outcome {
             Ok(stream) => {
-                let response_time = start.elapsed().as_millis() as u64;
+                let response_time = elapsed.as_millis() as u64;
+                tracing::trace!(response_time, "connected");
+                if let Ok(local) = stream.local_addr() {
+                    let _ = self.observed_source.set(local.ip());
+                }
+                if self.fast_close {
+                    // Linger only matters at close, so the stream can be set up as usual
+                    if let Err(e) = SockRef::from(&stream).set_linger(Some(Duration::ZERO)) {
+                        tracing::trace!(error = %e, "setting SO_LINGER failed");
+                    }
+                }
+                if let Some(ref script) = self.interact {
+                    let transcript =
+                        run_script(stream, script, self.banner_timeout, self.banner_size).await;
+                    tracing::debug!(%transcript, "interaction finished");
+                    // The transcript is not a greeting, so detectors only see the port
+                    let id = self.services.identify(port_num, None);
+                    return PortResult::new(port, PortStatus::Open, id.service)
+                        .with_service_source(id.source)
+                        .with_banner(Some(transcript))
+                        .with_response_time(response_time);
+                }
+
                 let banner = if self.grab_banners {
-                    grab_banner_from_stream(stream, port_num).await
+                    let banner = grab_banner_from_stream(
+                        stream,
+                        port_num,
+                        self.banner_timeout,
+                        self.banner_size,
+                    )
+                    .await;
+                    match banner {
+                        Some(ref banner) => tracing::debug!(%banner, "banner received"),
+                        None => tracing::trace!("no banner"),
+                    }
+                    banner
                 } else {
                     drop(stream);
                     None
                 };
 
-                PortResult::new(port, PortStatus::Open, service)
+                #[cfg(feature = "tls")]
+                let alpn = match self.alpn {
+                    Some(ref probe) => match self.attempt_connect(addr).await {
+                        Ok(stream) => probe.negotiate(stream, self.banner_timeout).await,
+                        Err(_) => None,
+                    },
+                    None => None,
+                };
+                #[cfg(not(feature = "tls"))]
+                let alpn = None;
+
+                let id = self.services.identify(port_num, banner.as_deref());
+                PortResult::new(port, PortStatus::Open, id.service)
+                    .with_service_source(id.source)
                     .with_banner(banner)
+                    .with_alpn(alpn)
                     .with_response_time(response_time)
             }
             Err(e) => {
+                match e {
+                    ScanError::ConnectionRefused => tracing::trace!(?elapsed, "connection refused"),
+                    ScanError::Timeout => tracing::debug!(timeout = ?self.timeout, "timed out"),
+                    _ => tracing::debug!(error = %e, "connect failed"),
+                }
                 let status = match e {
                     ScanError::ConnectionRefused => PortStatus::Closed,
                     ScanError::Timeout => PortStatus::Filtered,
                     ScanError::HostUnreachable | ScanError::NetworkUnreachable(_) => {
                         PortStatus::Filtered
                     }
+                    // The port was never probed, so it cannot be called closed
+                    ScanError::LocalResources(_) => PortStatus::Filtered,
                     _ => PortStatus::Closed,
                 };
 
-                PortResult::new(port, status, service)
+                let result = if status == PortStatus::Closed && !self.closed_services {
+                    PortResult::new(port, status, String::new())
+                } else {
+                    let id = self.services.identify(port_num, None);
+                    PortResult::new(port, status, id.service).with_service_source(id.source)
+                };
+                // Refusals, timeouts, and unreachables are the network's answer;
+                // anything else failed on this side
+                match e {
+                    ScanError::ConnectionFailed { reason, .. } => result.with_error(reason),
+                    ScanError::LocalResources(reason) => result.with_error(reason),
+                    ScanError::ConnectionRefused => result.with_fast_reset(
+                        self.fast_reset_threshold
+                            .is_some_and(|threshold| elapsed < threshold),
+                    ),
+                    _ => result,
+                }
             }
         }
     }
 }
 
+#[async_trait]
+impl Scanner for TcpConnectScanner {
+    fn scan_type(&self) -> ScanType {
+        ScanType::Connect
+    }
+
+    fn requires_privileges(&self) -> bool {
+        false
+    }
+
+    fn target(&self) -> IpAddr {
+        self.target
+    }
+
+    fn timeout(&self) -> Duration {
+        self.timeout
+    }
+
+    fn supports_banner(&self) -> bool {
+        true
+    }
+
+    fn grabs_banners(&self) -> bool {
+        self.grab_banners || self.interact.is_some()
+    }
+
+    fn source_ip(&self) -> Option<IpAddr> {
+        self.source
+            .as_ref()
+            .map(|source| source.ip)
+            .or_else(|| self.observed_source.get().copied())
+    }
+
+    fn interface(&self) -> Option<&str> {
+        self.source.as_ref().map(|source| source.interface.as_str())
+    }
+
+    async fn scan_port(&self, port: Port) -> PortResult {
+        self.probe(port).await
+    }
+
+    /// Probes the ports together without boxing each one's future.
+    async fn scan_ports(&self, ports: &[Port]) -> Vec<PortResult> {
+        futures::future::join_all(ports.iter().map(|&port| self.probe(port))).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::ServiceSource;
     use std::net::Ipv4Addr;
 
     #[test]
@@ -145,6 +597,284 @@ mod tests {
         assert_eq!(scanner.scan_type(), ScanType::Connect);
     }
 
+    #[test]
+    fn test_connect_timeout_jitter() {
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_millis(1000),
+            false,
+        );
+        assert_eq!(scanner.connect_timeout(), Duration::from_millis(1000));
+
+        let scanner = scanner.with_timeout_jitter(Duration::from_millis(200));
+        for _ in 0..100 {
+            let timeout = scanner.connect_timeout();
+            assert!((800..=1200).contains(&timeout.as_millis()), "{:?}", timeout);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn test_scan_with_interface() {
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_secs(1),
+            false,
+        )
+        .with_interface("lo")
+        .unwrap();
+        assert_eq!(
+            scanner.source.as_ref().map(|s| s.ip),
+            Some(IpAddr::V4(Ipv4Addr::LOCALHOST))
+        );
+
+        let result = scanner.scan_port(Port::new(1).unwrap()).await;
+        assert_eq!(result.status, PortStatus::Closed);
+        assert_eq!(scanner.interface(), Some("lo"));
+    }
+
+    #[tokio::test]
+    async fn test_source_ip_observed_on_connect() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = Port::new(listener.local_addr().unwrap().port()).unwrap();
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_secs(1),
+            false,
+        );
+        assert_eq!(scanner.source_ip(), None);
+
+        let result = scanner.scan_port(port).await;
+        assert_eq!(result.status, PortStatus::Open);
+        assert_eq!(scanner.source_ip(), Some(IpAddr::V4(Ipv4Addr::LOCALHOST)));
+        assert_eq!(scanner.interface(), None);
+    }
+
+    #[tokio::test]
+    async fn test_source_ports_round_robin() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = Port::new(listener.local_addr().unwrap().port()).unwrap();
+        let range: PortRange = "47310-47312".parse().unwrap();
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_secs(1),
+            false,
+        )
+        .with_source_ports(range);
+
+        for _ in 0..3 {
+            let result = scanner.scan_port(port).await;
+            assert_eq!(result.status, PortStatus::Open);
+        }
+        let mut used = Vec::new();
+        for _ in 0..3 {
+            let (_, peer) = listener.accept().await.unwrap();
+            used.push(peer.port());
+        }
+        used.sort_unstable();
+        assert_eq!(used, [47310, 47311, 47312]);
+
+        // Handed out in turn, wrapping at the end of the range
+        let ports = SourcePorts {
+            range,
+            next: AtomicUsize::new(2),
+        };
+        let turns: Vec<u16> = (0..4).map(|_| ports.next()).collect();
+        assert_eq!(turns, [47312, 47310, 47311, 47312]);
+    }
+
+    #[tokio::test]
+    async fn test_fast_close_resets_connection() {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = Port::new(listener.local_addr().unwrap().port()).unwrap();
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_secs(1),
+            false,
+        )
+        .with_fast_close(true);
+
+        let result = scanner.scan_port(port).await;
+        assert_eq!(result.status, PortStatus::Open);
+
+        // The scanner's close arrives as a reset rather than end of stream
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let err = socket.read(&mut [0u8; 1]).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::ConnectionReset);
+    }
+
+    #[tokio::test]
+    async fn test_fast_reset_flagging() {
+        // Bind and drop a listener for a port that refuses connections
+        let port = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            Port::new(listener.local_addr().unwrap().port()).unwrap()
+        };
+        let scanner = |threshold: Option<Duration>| {
+            let scanner = TcpConnectScanner::new(
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                Duration::from_secs(1),
+                false,
+            );
+            match threshold {
+                Some(threshold) => scanner.with_fast_reset_threshold(threshold),
+                None => scanner,
+            }
+        };
+
+        // Loopback refusals come back well within a second
+        let result = scanner(Some(Duration::from_secs(1))).scan_port(port).await;
+        assert_eq!(result.status, PortStatus::Closed);
+        assert!(result.fast_reset);
+
+        let result = scanner(Some(Duration::ZERO)).scan_port(port).await;
+        assert!(!result.fast_reset);
+        let result = scanner(None).scan_port(port).await;
+        assert!(!result.fast_reset);
+    }
+
+    #[tokio::test]
+    async fn test_rst_retries() {
+        let port = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            Port::new(listener.local_addr().unwrap().port()).unwrap()
+        };
+        let scanner = |retries: u32| {
+            TcpConnectScanner::new(
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                Duration::from_secs(1),
+                false,
+            )
+            .with_rst_retries(retries)
+        };
+
+        // Nothing ever listens, so retries leave the port closed
+        let result = scanner(2).scan_port(port).await;
+        assert_eq!(result.status, PortStatus::Closed);
+
+        // A listener that appears after the first refusal is caught by a retry
+        let listen = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            tokio::net::TcpListener::bind(("127.0.0.1", port.as_u16()))
+                .await
+                .unwrap()
+        });
+        tokio::time::sleep(Duration::from_millis(1)).await;
+        let result = scanner(1).scan_port(port).await;
+        let _listener = listen.await.unwrap();
+        assert_eq!(result.status, PortStatus::Open);
+
+        // Only quick refusals are retried, 10ms unless a threshold is set
+        let retrying = scanner(1);
+        assert!(retrying.retries_refusal(Duration::from_millis(1)));
+        assert!(!retrying.retries_refusal(Duration::from_millis(40)));
+        let retrying = retrying.with_fast_reset_threshold(Duration::from_millis(50));
+        assert!(retrying.retries_refusal(Duration::from_millis(40)));
+    }
+
+    #[tokio::test]
+    async fn test_retry_transient() {
+        use std::io::Error;
+
+        // Transient failures are retried until the connect goes through
+        let mut calls = 0;
+        let result = retry_transient(|| {
+            calls += 1;
+            let attempt = calls;
+            async move {
+                match attempt {
+                    1 => Err(Error::from(ErrorKind::AddrInUse)),
+                    2 => Err(Error::from(ErrorKind::WouldBlock)),
+                    _ => Ok(attempt),
+                }
+            }
+        })
+        .await;
+        assert_eq!(result.unwrap(), 3);
+
+        // ...up to a bound, and other errors are returned at once
+        let mut calls = 0;
+        let result: std::io::Result<()> = retry_transient(|| {
+            calls += 1;
+            async { Err(Error::from(ErrorKind::AddrNotAvailable)) }
+        })
+        .await;
+        assert!(is_transient(&result.unwrap_err()));
+        assert_eq!(calls, TRANSIENT_RETRIES + 1);
+
+        let mut calls = 0;
+        let result: std::io::Result<()> = retry_transient(|| {
+            calls += 1;
+            async { Err(Error::from(ErrorKind::ConnectionRefused)) }
+        })
+        .await;
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[tokio::test]
+    async fn test_custom_service_detector() {
+        struct Internal;
+
+        impl ServiceDetector for Internal {
+            fn detect(&self, _port: u16, banner: Option<&str>) -> Option<String> {
+                banner
+                    .filter(|b| b.starts_with("ACME"))
+                    .map(|_| "acme-rpc".to_string())
+            }
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = Port::new(listener.local_addr().unwrap().port()).unwrap();
+        tokio::spawn(async move {
+            use tokio::io::AsyncWriteExt;
+            let (mut socket, _) = listener.accept().await.unwrap();
+            socket.write_all(b"ACME service ready\r\n").await.unwrap();
+        });
+
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_secs(1),
+            true,
+        )
+        .with_service_detector(Internal);
+
+        let result = scanner.scan_port(port).await;
+        assert_eq!(result.status, PortStatus::Open);
+        assert_eq!(result.service, "acme-rpc");
+        assert_eq!(result.service_source, ServiceSource::Guessed);
+    }
+
+    #[test]
+    fn test_with_unknown_interface() {
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_secs(1),
+            false,
+        );
+        assert!(matches!(
+            scanner.with_interface("no-such-iface0"),
+            Err(ScanError::InterfaceNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_scanner_describe() {
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_secs(1),
+            false,
+        );
+        let info = scanner.describe();
+        assert_eq!(info.scan_type, ScanType::Connect);
+        assert_eq!(info.protocol, "tcp");
+        assert!(!info.requires_privileges);
+        assert!(info.supports_banner);
+        assert!(!info.description.is_empty());
+    }
+
     #[tokio::test]
     async fn test_scan_closed_port() {
         let scanner = TcpConnectScanner::new(
@@ -163,4 +893,29 @@ mod tests {
             PortStatus::Closed | PortStatus::Filtered
         ));
     }
+
+    #[tokio::test]
+    async fn test_scan_ports_without_closed_services() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let open = Port::new(listener.local_addr().unwrap().port()).unwrap();
+        let closed = {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            Port::new(listener.local_addr().unwrap().port()).unwrap()
+        };
+        let scanner = TcpConnectScanner::new(
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+            Duration::from_secs(1),
+            false,
+        )
+        .with_closed_services(false);
+
+        let results = scanner.scan_ports(&[closed, open]).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].port, closed);
+        assert_eq!(results[0].status, PortStatus::Closed);
+        assert_eq!(results[0].service, "");
+        assert_eq!(results[1].port, open);
+        assert_eq!(results[1].status, PortStatus::Open);
+        assert!(!results[1].service.is_empty());
+    }
 }
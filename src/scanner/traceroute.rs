@@ -0,0 +1,249 @@
+//! Traceroute: the routers between us and a target.
+//!
+//! ICMP echo requests go out with TTLs 1 through [`MAX_HOPS`], all at once,
+//! each carrying its TTL as the sequence number. A router that drops one
+//! whose TTL ran out answers with time exceeded, quoting the request, which
+//! names it as that hop; the target answers the requests that reach it with
+//! echo replies. When every port of a host comes back filtered, the last hop
+//! that answered shows where probes stop: at the host itself, which is then
+//! filtering them, or at a router short of it.
+//!
+//! Requires raw socket access, and IPv4 targets.
+
+use super::discovery::{icmp_request, map_socket_error, IcmpProbe};
+use crate::error::{ScanError, ScanResult};
+use pnet::packet::icmp::{IcmpType, IcmpTypes};
+use pnet::packet::ip::IpNextHeaderProtocols;
+use pnet::packet::ipv4::Ipv4Packet;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io::Read;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::{Duration, Instant};
+
+/// Longest route traced; routers further away are not reported.
+pub const MAX_HOPS: u8 = 30;
+
+/// Traces the route to targets with TTL-limited ICMP echo requests.
+#[derive(Debug, Clone)]
+pub struct Traceroute {
+    wait: Duration,
+}
+
+impl Traceroute {
+    /// Create a tracer that waits up to `wait` for the answers to a trace.
+    pub fn new(wait: Duration) -> Self {
+        Self { wait }
+    }
+
+    /// Trace the route to `target`.
+    ///
+    /// Returns the addresses that answered, nearest first, ending with the
+    /// target if it was reached. Hops that stayed silent are left out.
+    pub async fn trace(&self, target: IpAddr) -> ScanResult<Vec<IpAddr>> {
+        let IpAddr::V4(target) = target else {
+            return Err(ScanError::InvalidConfig(
+                "traceroute supports IPv4 targets only".to_string(),
+            ));
+        };
+        let wait = self.wait;
+        tokio::task::spawn_blocking(move || trace_route(target, wait))
+            .await
+            .map_err(|e| ScanError::RawSocketError(e.to_string()))?
+    }
+}
+
+/// An answer to one traceroute probe.
+#[derive(Debug, PartialEq, Eq)]
+struct Answer {
+    /// TTL the answered probe was sent with.
+    ttl: u8,
+    /// Address that answered.
+    from: Ipv4Addr,
+    /// Whether the route ends here: the target replied, or the answering
+    /// router could not deliver the probe.
+    last: bool,
+}
+
+fn trace_route(target: Ipv4Addr, wait: Duration) -> ScanResult<Vec<IpAddr>> {
+    let socket =
+        Socket::new(Domain::IPV4, Type::RAW, Some(Protocol::ICMPV4)).map_err(map_socket_error)?;
+    let identifier: u16 = rand::random();
+    let addr = SocketAddr::new(IpAddr::V4(target), 0).into();
+    for ttl in 1..=MAX_HOPS {
+        socket.set_ttl(u32::from(ttl))?;
+        let request = icmp_request(IcmpProbe::Echo, identifier, u16::from(ttl))?;
+        socket.send_to(&request, &addr)?;
+    }
+
+    let mut hops = [None; MAX_HOPS as usize];
+    let mut end: Option<u8> = None;
+    let deadline = Instant::now() + wait;
+    let mut recv_buf = [0u8; 1500];
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        if remaining.is_zero() {
+            break;
+        }
+        socket.set_read_timeout(Some(remaining))?;
+
+        let n = match (&socket).read(&mut recv_buf) {
+            Ok(n) => n,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                break
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let Some(answer) = parse_answer(&recv_buf[..n], target, identifier) else {
+            continue;
+        };
+        if !(1..=MAX_HOPS).contains(&answer.ttl) {
+            continue;
+        }
+        hops[usize::from(answer.ttl) - 1].get_or_insert(answer.from);
+        if answer.last {
+            end = Some(end.map_or(answer.ttl, |end| end.min(answer.ttl)));
+        }
+
+        // Done once the end is known and every hop before it answered
+        if let Some(end) = end {
+            if hops[..usize::from(end)].iter().all(Option::is_some) {
+                break;
+            }
+        }
+    }
+
+    let end = usize::from(end.unwrap_or(MAX_HOPS));
+    Ok(hops[..end]
+        .iter()
+        .flatten()
+        .map(|&ip| IpAddr::V4(ip))
+        .collect())
+}
+
+/// Match a received IP packet to one of our probes to `target`.
+///
+/// Echo replies come from the target itself; time exceeded and destination
+/// unreachable messages quote the IP header and first 8 bytes of the
+/// request, which hold our identifier and the probe's TTL.
+fn parse_answer(packet: &[u8], target: Ipv4Addr, identifier: u16) -> Option<Answer> {
+    let ip = Ipv4Packet::new(packet)?;
+    if ip.get_next_level_protocol() != IpNextHeaderProtocols::Icmp {
+        return None;
+    }
+    let from = ip.get_source();
+    let message = packet.get(ip.get_header_length() as usize * 4..)?;
+    let kind = IcmpType(*message.first()?);
+
+    let echo = if kind == IcmpTypes::EchoReply {
+        if from != target {
+            return None;
+        }
+        message
+    } else if kind == IcmpTypes::TimeExceeded || kind == IcmpTypes::DestinationUnreachable {
+        let quoted = message.get(8..)?;
+        let quoted_ip = Ipv4Packet::new(quoted)?;
+        if quoted_ip.get_destination() != target {
+            return None;
+        }
+        let request = quoted.get(quoted_ip.get_header_length() as usize * 4..)?;
+        if request.first() != Some(&IcmpTypes::EchoRequest.0) {
+            return None;
+        }
+        request
+    } else {
+        return None;
+    };
+
+    if echo.len() < 8 || u16::from_be_bytes([echo[4], echo[5]]) != identifier {
+        return None;
+    }
+    let ttl = u8::try_from(u16::from_be_bytes([echo[6], echo[7]])).ok()?;
+    Some(Answer {
+        ttl,
+        from,
+        last: kind != IcmpTypes::TimeExceeded,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: Ipv4Addr = Ipv4Addr::new(203, 0, 113, 9);
+    const ROUTER: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 1);
+    const LOCAL: Ipv4Addr = Ipv4Addr::new(192, 168, 1, 20);
+
+    /// An IPv4 packet carrying the ICMP message `payload`.
+    fn ipv4(source: Ipv4Addr, destination: Ipv4Addr, payload: &[u8]) -> Vec<u8> {
+        let mut packet = vec![0u8; 20];
+        packet[0] = 0x45;
+        packet[9] = IpNextHeaderProtocols::Icmp.0;
+        packet[12..16].copy_from_slice(&source.octets());
+        packet[16..20].copy_from_slice(&destination.octets());
+        packet.extend_from_slice(payload);
+        packet
+    }
+
+    /// An ICMP error of `kind` from `from`, quoting our probe with `ttl`.
+    fn icmp_error(kind: IcmpType, from: Ipv4Addr, to: Ipv4Addr, ttl: u16) -> Vec<u8> {
+        let request = icmp_request(IcmpProbe::Echo, 0x1234, ttl).unwrap();
+        let mut message = vec![kind.0, 0, 0, 0, 0, 0, 0, 0];
+        message.extend(ipv4(LOCAL, to, &request));
+        ipv4(from, LOCAL, &message)
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        let exceeded = icmp_error(IcmpTypes::TimeExceeded, ROUTER, TARGET, 2);
+        assert_eq!(
+            parse_answer(&exceeded, TARGET, 0x1234),
+            Some(Answer {
+                ttl: 2,
+                from: ROUTER,
+                last: false
+            })
+        );
+        assert_eq!(parse_answer(&exceeded, TARGET, 0x4321), None);
+        assert_eq!(parse_answer(&exceeded, ROUTER, 0x1234), None);
+
+        let unreachable = icmp_error(IcmpTypes::DestinationUnreachable, ROUTER, TARGET, 5);
+        let answer = parse_answer(&unreachable, TARGET, 0x1234).unwrap();
+        assert_eq!((answer.ttl, answer.last), (5, true));
+
+        let mut reply = icmp_request(IcmpProbe::Echo, 0x1234, 7).unwrap();
+        reply[0] = IcmpTypes::EchoReply.0;
+        assert_eq!(
+            parse_answer(&ipv4(TARGET, LOCAL, &reply), TARGET, 0x1234),
+            Some(Answer {
+                ttl: 7,
+                from: TARGET,
+                last: true
+            })
+        );
+        assert_eq!(
+            parse_answer(&ipv4(ROUTER, LOCAL, &reply), TARGET, 0x1234),
+            None
+        );
+
+        // Our own request, as seen on loopback
+        let request = icmp_request(IcmpProbe::Echo, 0x1234, 7).unwrap();
+        assert_eq!(
+            parse_answer(&ipv4(LOCAL, TARGET, &request), TARGET, 0x1234),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_trace_ipv6_unsupported() {
+        let tracer = Traceroute::new(Duration::from_millis(100));
+        assert!(matches!(
+            tracer.trace("::1".parse().unwrap()).await,
+            Err(ScanError::InvalidConfig(_))
+        ));
+    }
+}
@@ -3,7 +3,9 @@
 //! Defines a common interface for all scanner implementations,
 //! enabling polymorphism and easier testing.
 
-use crate::types::Port;
+use crate::banner::interact::Script;
+use crate::services::ServiceSource;
+use crate::types::{Port, PortRange};
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use std::fmt;
@@ -21,10 +23,28 @@ pub enum PortStatus {
     /// Port is filtered (no response, possibly by firewall).
     Filtered,
     /// Port is either open or filtered (UDP-specific ambiguity).
-    #[serde(rename = "open|filtered")]
+    ///
+    /// Serialized as `open|filtered`; `open_filtered` is also accepted.
+    #[serde(rename = "open|filtered", alias = "open_filtered")]
     OpenFiltered,
 }
 
+impl PortStatus {
+    /// A token of letters and underscores only, for column names, metric
+    /// labels, and other places a `|` gets in the way.
+    ///
+    /// The same as the display form except for `open_filtered`; both parse
+    /// back with [`FromStr`](std::str::FromStr).
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Open => "open",
+            Self::Closed => "closed",
+            Self::Filtered => "filtered",
+            Self::OpenFiltered => "open_filtered",
+        }
+    }
+}
+
 impl fmt::Display for PortStatus {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -36,21 +56,58 @@ impl fmt::Display for PortStatus {
     }
 }
 
+impl std::str::FromStr for PortStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Tolerate padding and case from other tools' exports
+        match s.trim().to_lowercase().as_str() {
+            "open" => Ok(Self::Open),
+            "closed" => Ok(Self::Closed),
+            "filtered" => Ok(Self::Filtered),
+            "open|filtered" | "open_filtered" => Ok(Self::OpenFiltered),
+            _ => Err(format!(
+                "unknown port status: {} (expected open, closed, filtered, or open|filtered)",
+                s
+            )),
+        }
+    }
+}
+
 /// Result of scanning a single port.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PortResult {
     /// The port number that was scanned.
     pub port: Port,
+    /// Transport protocol ("tcp" or "udp"), set on results merged from
+    /// scans of different types.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<String>,
     /// Status determined by the scan.
     pub status: PortStatus,
     /// Detected or inferred service name.
     pub service: String,
+    /// How the service name was identified.
+    #[serde(default)]
+    pub service_source: ServiceSource,
     /// Banner captured from the service (if any).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub banner: Option<String>,
+    /// Application protocol selected in a TLS handshake offering `h2` and
+    /// `http/1.1`, with `--alpn` (connect scans).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alpn: Option<String>,
     /// Response time in milliseconds.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub response_time_ms: Option<u64>,
+    /// The connection was refused faster than the configured threshold,
+    /// as when a load balancer resets ports it is shedding (connect scans).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub fast_reset: bool,
+    /// Why the scanner could not probe the port, when the status reflects a
+    /// scanner-side failure rather than the target's behavior.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
 }
 
 impl PortResult {
@@ -58,13 +115,30 @@ impl PortResult {
     pub fn new(port: Port, status: PortStatus, service: impl Into<String>) -> Self {
         Self {
             port,
+            protocol: None,
             status,
             service: service.into(),
+            service_source: ServiceSource::Guessed,
             banner: None,
+            alpn: None,
             response_time_ms: None,
+            fast_reset: false,
+            error: None,
         }
     }
 
+    /// Set how the service name was identified.
+    pub fn with_service_source(mut self, source: ServiceSource) -> Self {
+        self.service_source = source;
+        self
+    }
+
+    /// Tag the result with its transport protocol.
+    pub fn with_protocol(mut self, protocol: impl Into<String>) -> Self {
+        self.protocol = Some(protocol.into());
+        self
+    }
+
     /// Set the banner.
     pub fn with_banner(mut self, banner: Option<String>) -> Self {
         self.banner = banner;
@@ -77,6 +151,24 @@ impl PortResult {
         self
     }
 
+    /// Set the application protocol negotiated over TLS.
+    pub fn with_alpn(mut self, alpn: Option<String>) -> Self {
+        self.alpn = alpn;
+        self
+    }
+
+    /// Mark a refusal as suspiciously fast.
+    pub fn with_fast_reset(mut self, fast_reset: bool) -> Self {
+        self.fast_reset = fast_reset;
+        self
+    }
+
+    /// Record the scanner-side error that kept the port from being probed.
+    pub fn with_error(mut self, error: impl Into<String>) -> Self {
+        self.error = Some(error.into());
+        self
+    }
+
     /// Check if the port is open.
     pub fn is_open(&self) -> bool {
         matches!(self.status, PortStatus::Open | PortStatus::OpenFiltered)
@@ -84,10 +176,11 @@ impl PortResult {
 }
 
 /// Available scan types.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, clap::ValueEnum)]
 #[serde(rename_all = "lowercase")]
 pub enum ScanType {
     /// TCP connect scan (default, no special privileges required).
+    #[default]
     Connect,
     /// SYN stealth scan (requires root/admin privileges).
     Syn,
@@ -95,9 +188,71 @@ pub enum ScanType {
     Udp,
 }
 
-impl Default for ScanType {
-    fn default() -> Self {
-        Self::Connect
+impl ScanType {
+    /// Machine-readable name, as used by serde, the CLI, and [`FromStr`].
+    ///
+    /// [`Display`](fmt::Display) gives the human-readable name instead
+    /// ("TCP Connect"); both parse back with `FromStr`.
+    ///
+    /// [`FromStr`]: std::str::FromStr
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Connect => "connect",
+            Self::Syn => "syn",
+            Self::Udp => "udp",
+        }
+    }
+
+    /// Transport protocol probed by this scan type.
+    pub const fn protocol(self) -> &'static str {
+        match self {
+            Self::Connect | Self::Syn => "tcp",
+            Self::Udp => "udp",
+        }
+    }
+
+    /// Check if this scan type needs elevated privileges.
+    pub const fn requires_privileges(self) -> bool {
+        !matches!(self, Self::Connect)
+    }
+
+    /// Address families this scan type can target.
+    pub const fn address_families(self) -> &'static [&'static str] {
+        match self {
+            Self::Connect | Self::Udp => &["IPv4", "IPv6"],
+            // Packets are built with an IPv4 header
+            Self::Syn => &["IPv4"],
+        }
+    }
+
+    /// Check if scanners of this type can grab banners.
+    ///
+    /// Only a connect scan completes the handshake, leaving a stream to read.
+    pub const fn supports_banner(self) -> bool {
+        matches!(self, Self::Connect)
+    }
+
+    /// Concurrency used when none is configured.
+    ///
+    /// Connect scans are cheap to run in parallel. SYN probes share one raw
+    /// socket and reply queue, so fewer are kept in flight. UDP needs to go
+    /// slowly: hosts rate-limit the ICMP errors that mark a port closed, and
+    /// errors dropped under load make closed ports look open|filtered.
+    pub const fn default_concurrency(self) -> usize {
+        match self {
+            Self::Connect => 500,
+            Self::Syn => 200,
+            Self::Udp => 20,
+        }
+    }
+
+    /// One-line description of how this scan type works.
+    pub const fn description(self) -> &'static str {
+        match self {
+            Self::Connect => "Completes the full TCP handshake using the OS socket API",
+            Self::Syn => "Sends raw SYN packets and reads replies without completing the handshake",
+            Self::Udp => "Sends protocol-specific UDP probes and waits for replies or ICMP errors",
+        }
     }
 }
 
@@ -116,14 +271,83 @@ impl std::str::FromStr for ScanType {
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
-            "connect" | "tcp" => Ok(Self::Connect),
-            "syn" | "stealth" => Ok(Self::Syn),
+            "connect" | "tcp" | "tcp connect" => Ok(Self::Connect),
+            "syn" | "stealth" | "syn stealth" => Ok(Self::Syn),
             "udp" => Ok(Self::Udp),
             _ => Err(format!("unknown scan type: {}", s)),
         }
     }
 }
 
+/// Self-description of a scanner, for listing and presenting scan options.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ScannerInfo {
+    /// Scan type implemented.
+    pub scan_type: ScanType,
+    /// Transport protocol probed ("tcp" or "udp").
+    pub protocol: &'static str,
+    /// Whether elevated privileges are required.
+    pub requires_privileges: bool,
+    /// Address families that can be targeted.
+    pub address_families: &'static [&'static str],
+    /// Whether open ports can be banner-grabbed.
+    pub supports_banner: bool,
+    /// Concurrency used when none is configured.
+    pub default_concurrency: usize,
+    /// Human-readable description.
+    pub description: &'static str,
+}
+
+impl ScannerInfo {
+    /// Describe a scan type with the given privilege requirement.
+    pub fn new(scan_type: ScanType, requires_privileges: bool) -> Self {
+        Self {
+            scan_type,
+            protocol: scan_type.protocol(),
+            requires_privileges,
+            address_families: scan_type.address_families(),
+            supports_banner: scan_type.supports_banner(),
+            default_concurrency: scan_type.default_concurrency(),
+            description: scan_type.description(),
+        }
+    }
+
+    /// Describe every available scan type without constructing scanners.
+    pub fn all() -> Vec<Self> {
+        [ScanType::Connect, ScanType::Syn, ScanType::Udp]
+            .into_iter()
+            .map(|scan_type| Self::new(scan_type, scan_type.requires_privileges()))
+            .collect()
+    }
+}
+
+/// Probe counts from a scanner that retransmits unanswered probes.
+///
+/// Only ports that answered say anything about loss: a port that needed
+/// two probes lost the first. Silent ports could be filtered or lost, which
+/// is why the estimate matters for reading "filtered" results.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProbeStats {
+    /// Probes sent, retransmissions included.
+    pub sent: u64,
+    /// Probes that repeated an unanswered one.
+    pub retransmissions: u64,
+    /// Ports that answered at all.
+    pub answered_ports: u64,
+    /// Probes sent to the ports that answered.
+    pub answered_port_probes: u64,
+}
+
+impl ProbeStats {
+    /// Estimated percentage of probes lost, or `None` before any port answered.
+    pub fn loss_percent(&self) -> Option<f64> {
+        (self.answered_port_probes > 0).then(|| {
+            let lost = self.answered_port_probes - self.answered_ports;
+            lost as f64 * 100.0 / self.answered_port_probes as f64
+        })
+    }
+}
+
 /// Configuration for a scan operation.
 #[derive(Debug, Clone)]
 pub struct ScanConfig {
@@ -133,10 +357,44 @@ pub struct ScanConfig {
     pub target_hostname: String,
     /// Connection/response timeout.
     pub timeout: Duration,
+    /// Spread applied to each connect's timeout, up to this much either way
+    /// (connect scans).
+    pub timeout_jitter: Duration,
     /// Whether to attempt banner grabbing.
     pub grab_banners: bool,
-    /// Network interface to use (for raw socket scans).
+    /// Banner read timeout (defaults to `timeout`).
+    pub banner_timeout: Option<Duration>,
+    /// Maximum bytes captured per banner.
+    pub banner_size: usize,
+    /// Send/expect script run on open ports instead of a banner grab
+    /// (connect scans).
+    pub interact: Option<Script>,
+    /// Reset connections on close instead of leaving them in TIME_WAIT
+    /// (connect scans).
+    pub fast_close: bool,
+    /// Negotiate TLS on open ports to record the ALPN protocol (connect
+    /// scans, `tls` feature).
+    pub alpn: bool,
+    /// Source ports to bind connections to, in turn (connect scans).
+    pub source_ports: Option<PortRange>,
+    /// Refusals answered faster than this are flagged as fast resets
+    /// (connect scans).
+    pub fast_reset_threshold: Option<Duration>,
+    /// Retries of a fast refusal before the port counts as closed
+    /// (connect scans).
+    pub rst_retries: u32,
+    /// Name the likely service of closed ports too (connect scans); scans
+    /// that drop closed ports can skip the lookup.
+    pub closed_services: bool,
+    /// Network interface to send probes from (connect and SYN scans).
     pub interface: Option<String>,
+    /// Fragment probe packets (SYN scans).
+    pub fragment: bool,
+    /// Times an unanswered probe is resent before the port counts as
+    /// filtered (SYN scans).
+    pub syn_retries: u32,
+    /// Operating system whose TCP options probes imitate (SYN scans).
+    pub os_profile: super::OsProfile,
 }
 
 impl ScanConfig {
@@ -146,8 +404,21 @@ impl ScanConfig {
             target,
             target_hostname: target.to_string(),
             timeout: Duration::from_secs(3),
+            timeout_jitter: Duration::ZERO,
             grab_banners: false,
+            banner_timeout: None,
+            banner_size: crate::banner::DEFAULT_BANNER_SIZE,
+            interact: None,
+            fast_close: false,
+            alpn: false,
+            source_ports: None,
+            fast_reset_threshold: None,
+            rst_retries: 0,
+            closed_services: true,
             interface: None,
+            fragment: false,
+            syn_retries: super::syn::DEFAULT_SYN_RETRIES,
+            os_profile: super::OsProfile::default(),
         }
     }
 
@@ -163,17 +434,104 @@ impl ScanConfig {
         self
     }
 
+    /// Randomize each connect's timeout by up to `jitter` either way (connect scans only).
+    pub fn with_timeout_jitter(mut self, jitter: Duration) -> Self {
+        self.timeout_jitter = jitter;
+        self
+    }
+
     /// Enable banner grabbing.
     pub fn with_banners(mut self) -> Self {
         self.grab_banners = true;
         self
     }
 
+    /// Set the banner read timeout, independent of the connect timeout.
+    pub fn with_banner_timeout(mut self, timeout: Duration) -> Self {
+        self.banner_timeout = Some(timeout);
+        self
+    }
+
+    /// Set the maximum number of bytes captured per banner.
+    pub fn with_banner_size(mut self, size: usize) -> Self {
+        self.banner_size = size;
+        self
+    }
+
+    /// Run `script` on open ports and keep the transcript as the banner
+    /// (connect scans only).
+    pub fn with_interact(mut self, script: Script) -> Self {
+        self.interact = Some(script);
+        self
+    }
+
+    /// Get the effective banner read timeout.
+    pub fn banner_timeout(&self) -> Duration {
+        self.banner_timeout.unwrap_or(self.timeout)
+    }
+
     /// Set the network interface.
     pub fn with_interface(mut self, interface: impl Into<String>) -> Self {
         self.interface = Some(interface.into());
         self
     }
+
+    /// Resend unanswered probes up to `retries` times (SYN scans only).
+    pub fn with_syn_retries(mut self, retries: u32) -> Self {
+        self.syn_retries = retries;
+        self
+    }
+
+    /// Set the operating system whose TCP options probes imitate (SYN scans only).
+    pub fn with_os_profile(mut self, profile: super::OsProfile) -> Self {
+        self.os_profile = profile;
+        self
+    }
+
+    /// Fragment probe packets (SYN scans only).
+    pub fn with_fragmentation(mut self) -> Self {
+        self.fragment = true;
+        self
+    }
+
+    /// Bind connections to source ports from `range` (connect scans only).
+    pub fn with_source_ports(mut self, range: PortRange) -> Self {
+        self.source_ports = Some(range);
+        self
+    }
+
+    /// Close connections with a RST so they skip TIME_WAIT (connect scans only).
+    pub fn with_fast_close(mut self) -> Self {
+        self.fast_close = true;
+        self
+    }
+
+    /// Record the ALPN protocol of TLS services (connect scans only).
+    pub fn with_alpn(mut self) -> Self {
+        self.alpn = true;
+        self
+    }
+
+    /// Flag refusals answered within `threshold` (connect scans only).
+    pub fn with_fast_reset_threshold(mut self, threshold: Duration) -> Self {
+        self.fast_reset_threshold = Some(threshold);
+        self
+    }
+
+    /// Retry fast refusals up to `retries` times (connect scans only).
+    ///
+    /// Refusals under the fast reset threshold, or under 10ms without one,
+    /// are retried.
+    pub fn with_rst_retries(mut self, retries: u32) -> Self {
+        self.rst_retries = retries;
+        self
+    }
+
+    /// Leave the service of closed ports unnamed (connect scans only).
+    pub fn without_closed_services(mut self) -> Self {
+        self.closed_services = false;
+        self
+    }
 }
 
 /// Trait for port scanner implementations.
@@ -203,14 +561,11 @@ pub trait Scanner: Send + Sync {
 
     /// Scan multiple ports concurrently.
     ///
-    /// Default implementation scans each port individually.
+    /// Default implementation scans each port individually, all at once,
+    /// returning results in the order of `ports`.
     /// Implementations may override for batch optimizations.
     async fn scan_ports(&self, ports: &[Port]) -> Vec<PortResult> {
-        let mut results = Vec::with_capacity(ports.len());
-        for &port in ports {
-            results.push(self.scan_port(port).await);
-        }
-        results
+        futures::future::join_all(ports.iter().map(|&port| self.scan_port(port))).await
     }
 
     /// Get the target IP address.
@@ -218,6 +573,51 @@ pub trait Scanner: Send + Sync {
 
     /// Get the configured timeout.
     fn timeout(&self) -> Duration;
+
+    /// Get the local address probes are sent from, once known.
+    fn source_ip(&self) -> Option<IpAddr> {
+        None
+    }
+
+    /// Get the network interface probes are sent from, if bound to one.
+    fn interface(&self) -> Option<&str> {
+        None
+    }
+
+    /// Get probe and retransmission counts, for scanners that keep them.
+    fn probe_stats(&self) -> Option<ProbeStats> {
+        None
+    }
+
+    /// Get how many times an unanswered probe is resent, for scanners
+    /// that resend.
+    fn retries(&self) -> Option<u32> {
+        None
+    }
+
+    /// Get the target's MAC address, if it is on the local link and
+    /// replied to a probe.
+    fn target_mac(&self) -> Option<String> {
+        None
+    }
+
+    /// Check if this scanner can grab banners at all.
+    fn supports_banner(&self) -> bool {
+        false
+    }
+
+    /// Check if this scanner grabs banners from open ports.
+    fn grabs_banners(&self) -> bool {
+        false
+    }
+
+    /// Describe this scanner's type, protocol, and requirements.
+    fn describe(&self) -> ScannerInfo {
+        ScannerInfo {
+            supports_banner: self.supports_banner(),
+            ..ScannerInfo::new(self.scan_type(), self.requires_privileges())
+        }
+    }
 }
 
 /// A boxed scanner for dynamic dispatch.
@@ -235,6 +635,81 @@ mod tests {
         assert_eq!(PortStatus::OpenFiltered.to_string(), "open|filtered");
     }
 
+    #[test]
+    fn test_port_status_round_trip() {
+        let all = [
+            PortStatus::Open,
+            PortStatus::Closed,
+            PortStatus::Filtered,
+            PortStatus::OpenFiltered,
+        ];
+        for status in all {
+            assert_eq!(status.to_string().parse(), Ok(status));
+            assert_eq!(status.as_str().parse(), Ok(status));
+
+            let json = serde_json::to_string(&status).unwrap();
+            assert_eq!(json, format!("\"{}\"", status));
+            assert_eq!(serde_json::from_str::<PortStatus>(&json).unwrap(), status);
+        }
+
+        assert_eq!(PortStatus::OpenFiltered.as_str(), "open_filtered");
+        assert_eq!(
+            serde_json::from_str::<PortStatus>("\"open_filtered\"").unwrap(),
+            PortStatus::OpenFiltered
+        );
+        assert_eq!("Closed".parse(), Ok(PortStatus::Closed));
+        assert_eq!(" open|filtered ".parse(), Ok(PortStatus::OpenFiltered));
+        for garbage in ["", "shut", "open|closed", "unfiltered"] {
+            let err = garbage.parse::<PortStatus>().unwrap_err();
+            assert!(err.contains("expected open, closed"), "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_scan_config_banner_timeout() {
+        let config = ScanConfig::new(IpAddr::from([127, 0, 0, 1]))
+            .with_timeout(Duration::from_millis(500));
+        assert_eq!(config.banner_timeout(), Duration::from_millis(500));
+
+        let config = config.with_banner_timeout(Duration::from_secs(5));
+        assert_eq!(config.banner_timeout(), Duration::from_secs(5));
+        assert_eq!(config.timeout, Duration::from_millis(500));
+    }
+
+    #[test]
+    fn test_scan_type_round_trip() {
+        use clap::ValueEnum;
+
+        for &scan_type in ScanType::value_variants() {
+            let name = scan_type.as_str();
+            let json = serde_json::to_string(&scan_type).unwrap();
+            assert_eq!(json, format!("\"{}\"", name));
+            assert_eq!(serde_json::from_str::<ScanType>(&json).unwrap(), scan_type);
+            assert_eq!(name.parse::<ScanType>().unwrap(), scan_type);
+            let value = scan_type.to_possible_value().unwrap();
+            assert_eq!(value.get_name(), name);
+        }
+    }
+
+    #[test]
+    fn test_scan_type_protocol() {
+        assert_eq!(ScanType::Connect.protocol(), "tcp");
+        assert_eq!(ScanType::Syn.protocol(), "tcp");
+        assert_eq!(ScanType::Udp.protocol(), "udp");
+    }
+
+    #[test]
+    fn test_scanner_info_all() {
+        let infos = ScannerInfo::all();
+        assert_eq!(infos.len(), 3);
+        assert!(!infos[0].requires_privileges);
+        assert!(infos[1].requires_privileges);
+        assert_eq!(infos[1].address_families, ["IPv4"]);
+        assert_eq!(infos[2].protocol, "udp");
+        let banners: Vec<bool> = infos.iter().map(|info| info.supports_banner).collect();
+        assert_eq!(banners, [true, false, false]);
+    }
+
     #[test]
     fn test_scan_type_from_str() {
         assert_eq!("connect".parse::<ScanType>().unwrap(), ScanType::Connect);
@@ -242,6 +717,13 @@ mod tests {
         assert_eq!("udp".parse::<ScanType>().unwrap(), ScanType::Udp);
     }
 
+    #[test]
+    fn test_scan_type_display_roundtrip() {
+        for scan_type in [ScanType::Connect, ScanType::Syn, ScanType::Udp] {
+            assert_eq!(scan_type.to_string().parse::<ScanType>().unwrap(), scan_type);
+        }
+    }
+
     #[test]
     fn test_port_result() {
         let port = Port::new(80).unwrap();
@@ -253,4 +735,20 @@ mod tests {
         assert_eq!(result.banner, Some("Apache/2.4".to_string()));
         assert_eq!(result.response_time_ms, Some(15));
     }
+
+    #[test]
+    fn test_port_result_error() {
+        let port = Port::new(53).unwrap();
+        let filtered = PortResult::new(port, PortStatus::Filtered, "dns");
+        let json = serde_json::to_string(&filtered).unwrap();
+        assert!(!json.contains("error"));
+
+        let failed = filtered.with_error("Operation not permitted (os error 1)");
+        let json = serde_json::to_string(&failed).unwrap();
+        let parsed: PortResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            parsed.error.as_deref(),
+            Some("Operation not permitted (os error 1)")
+        );
+    }
 }
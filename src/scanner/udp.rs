@@ -130,18 +130,22 @@ impl UdpScanner {
             match timeout(self.timeout, socket.recv(&mut buf)).await {
                 Ok(Ok(n)) if n > 0 => {
                     // Got a response - port is open
+                    tracing::trace!(bytes = n, attempt, "response received");
                     return Ok(PortStatus::Open);
                 }
                 Ok(Err(e)) => {
                     let err_str = e.to_string().to_lowercase();
                     if err_str.contains("refused") || err_str.contains("unreachable") {
                         // ICMP error - port is closed
+                        tracing::trace!(error = %e, "port unreachable");
                         return Ok(PortStatus::Closed);
                     }
+                    tracing::debug!(error = %e, attempt, "receive failed");
                 }
                 Err(_) => {
                     // Timeout - might be open or filtered
                     // Continue to next retry
+                    tracing::trace!(attempt, "timed out");
                 }
                 _ => {}
             }
@@ -179,12 +183,13 @@ impl Scanner for UdpScanner {
         let port_num = port.as_u16();
         let service = get_service_description(port_num).to_string();
 
-        let status = match self.probe_port(port_num).await {
-            Ok(status) => status,
-            Err(_) => PortStatus::Filtered,
-        };
-
-        PortResult::new(port, status, service)
+        match self.probe_port(port_num).await {
+            Ok(status) => PortResult::new(port, status, service),
+            Err(e) => {
+                tracing::debug!(error = %e, "probe failed");
+                PortResult::new(port, PortStatus::Filtered, service).with_error(e.to_string())
+            }
+        }
     }
 }
 
@@ -0,0 +1,231 @@
+//! Unix domain socket scanning.
+//!
+//! Enumerates socket files under a directory and tries to connect to each,
+//! for auditing local services that never listen on a TCP port. Results are
+//! keyed by path and kept apart from network scan records.
+
+use crate::banner::grab_banner_from_stream;
+use crate::scanner::traits::PortStatus;
+use crate::types::ScanId;
+use chrono::{DateTime, Utc};
+use futures::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::net::UnixStream;
+use tokio::time::timeout;
+
+/// Sockets probed at once.
+const CONCURRENCY: usize = 64;
+
+/// Result of probing a single socket file.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketResult {
+    /// Path of the socket file.
+    pub path: PathBuf,
+    /// Open if a connection was accepted, closed if refused (a stale
+    /// socket), filtered if access was denied or the connect timed out.
+    pub status: PortStatus,
+    /// Banner sent by the listener, if grabbed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub banner: Option<String>,
+    /// Time to connect in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_time_ms: Option<u64>,
+    /// Why the connection failed, if it did.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl SocketResult {
+    /// Check if the socket accepted a connection.
+    pub fn is_open(&self) -> bool {
+        self.status == PortStatus::Open
+    }
+}
+
+/// Record of a Unix socket scan, the path-keyed counterpart of a
+/// [`ScanRecord`](crate::storage::ScanRecord).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SocketScanRecord {
+    /// Unique identifier for this scan.
+    pub id: ScanId,
+    /// When the scan was started.
+    pub started_at: DateTime<Utc>,
+    /// When the scan completed.
+    pub completed_at: DateTime<Utc>,
+    /// Directory that was searched for sockets.
+    pub directory: PathBuf,
+    /// Number of socket files found.
+    pub sockets_found: usize,
+    /// Number of sockets that accepted a connection.
+    pub accepting: usize,
+    /// Total scan duration in milliseconds.
+    pub duration_ms: u64,
+    /// Per-socket results, sorted by path.
+    pub results: Vec<SocketResult>,
+}
+
+/// Scanner that connects to Unix domain sockets.
+#[derive(Debug, Clone)]
+pub struct UnixSocketScanner {
+    timeout: Duration,
+    grab_banners: bool,
+    banner_size: usize,
+}
+
+impl UnixSocketScanner {
+    /// Create a scanner with the given connect timeout.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            grab_banners: false,
+            banner_size: crate::banner::DEFAULT_BANNER_SIZE,
+        }
+    }
+
+    /// Read a banner from sockets that accept a connection.
+    pub fn with_banners(mut self) -> Self {
+        self.grab_banners = true;
+        self
+    }
+
+    /// Set the number of banner bytes to read.
+    pub fn with_banner_size(mut self, size: usize) -> Self {
+        self.banner_size = size;
+        self
+    }
+
+    /// Probe every socket file under `dir`.
+    pub async fn scan_dir(&self, dir: &Path) -> io::Result<SocketScanRecord> {
+        let started_at = Utc::now();
+        let start = Instant::now();
+
+        let paths = find_sockets(dir)?;
+        let mut results: Vec<SocketResult> = stream::iter(&paths)
+            .map(|path| self.scan_path(path))
+            .buffer_unordered(CONCURRENCY)
+            .collect()
+            .await;
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(SocketScanRecord {
+            id: ScanId::new(),
+            started_at,
+            completed_at: Utc::now(),
+            directory: dir.to_path_buf(),
+            sockets_found: results.len(),
+            accepting: results.iter().filter(|r| r.is_open()).count(),
+            duration_ms: start.elapsed().as_millis() as u64,
+            results,
+        })
+    }
+
+    /// Try to connect to the socket at `path`.
+    pub async fn scan_path(&self, path: &Path) -> SocketResult {
+        let start = Instant::now();
+        let mut result = SocketResult {
+            path: path.to_path_buf(),
+            status: PortStatus::Filtered,
+            banner: None,
+            response_time_ms: None,
+            error: None,
+        };
+
+        match timeout(self.timeout, UnixStream::connect(path)).await {
+            Ok(Ok(stream)) => {
+                result.status = PortStatus::Open;
+                result.response_time_ms = Some(start.elapsed().as_millis() as u64);
+                if self.grab_banners {
+                    result.banner =
+                        grab_banner_from_stream(stream, 0, self.timeout, self.banner_size).await;
+                }
+            }
+            Ok(Err(e)) => {
+                if e.kind() != io::ErrorKind::PermissionDenied {
+                    result.status = PortStatus::Closed;
+                }
+                result.error = Some(e.to_string());
+            }
+            Err(_) => result.error = Some("connect timed out".to_string()),
+        }
+
+        result
+    }
+}
+
+/// Find socket files under `dir`, descending into subdirectories.
+///
+/// Symlinks are not followed and unreadable subdirectories are skipped;
+/// only an unreadable `dir` itself is an error.
+pub fn find_sockets(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut sockets = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    let mut first = true;
+
+    while let Some(current) = pending.pop() {
+        let entries = match std::fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(e) if first => return Err(e),
+            Err(_) => continue,
+        };
+        first = false;
+
+        for entry in entries.flatten() {
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_socket() {
+                sockets.push(entry.path());
+            } else if file_type.is_dir() {
+                pending.push(entry.path());
+            }
+        }
+    }
+
+    sockets.sort();
+    Ok(sockets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixListener;
+
+    #[tokio::test]
+    async fn test_scan_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("nested")).unwrap();
+        std::fs::write(dir.path().join("regular.txt"), "not a socket").unwrap();
+
+        let listener = UnixListener::bind(dir.path().join("nested/live.sock")).unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            socket.write_all(b"220 hello\r\n").await.unwrap();
+            let _ = socket.readable().await;
+        });
+        // Binding leaves the file behind once the listener is gone
+        drop(UnixListener::bind(dir.path().join("stale.sock")).unwrap());
+
+        let scanner = UnixSocketScanner::new(Duration::from_secs(1)).with_banners();
+        let record = scanner.scan_dir(dir.path()).await.unwrap();
+
+        assert_eq!(record.sockets_found, 2);
+        assert_eq!(record.accepting, 1);
+        let live = &record.results[0];
+        assert!(live.path.ends_with("nested/live.sock"));
+        assert_eq!(live.status, PortStatus::Open);
+        assert_eq!(live.banner.as_deref(), Some("220 hello"));
+        let stale = &record.results[1];
+        assert_eq!(stale.status, PortStatus::Closed);
+        assert!(stale.error.is_some());
+    }
+
+    #[test]
+    fn test_find_sockets_missing_dir() {
+        assert!(find_sockets(Path::new("/nonexistent/scuttle-sockets")).is_err());
+    }
+}
@@ -0,0 +1,453 @@
+//! HTTP API for driving scans from other services.
+//!
+//! `scuttle serve` exposes three endpoints:
+//!
+//! - `POST /scans` starts a scan and answers `202` with its ID
+//! - `GET /scans/{id}` returns the saved record (ID or prefix), or the job
+//!   state while the scan is running or if it failed
+//! - `GET /scans?limit=N` lists saved scans, most recent first
+//!
+//! Scans go through [`run_scan`] and are saved to the same [`ScanStore`] as
+//! CLI scans, so `scuttle history` and `scuttle export` see them too. The
+//! server caps concurrency, rate, and port count whatever a request asks for,
+//! and how many scans run at once; the rate cap holds across all of them.
+
+use crate::error::StorageError;
+use crate::scanner::{create_scanner, has_raw_socket_access, run_scan, ScanConfig, ScanJobConfig};
+use crate::scanner::{KeyedRateLimiter, RateLimiter, ScanType, Scanner};
+use crate::storage::{ScanRecord, ScanStore};
+use crate::types::{PortSpec, ScanId, ScanTarget, TargetSpec};
+use axum::extract::{Path, Query, State};
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::Semaphore;
+
+/// How long a failed scan's error stays available before it is forgotten.
+const FAILED_JOB_TTL: Duration = Duration::from_secs(600);
+
+/// Limits the server enforces on every scan request.
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    /// Highest concurrency a request may use. Requests that set none get
+    /// their scan type's default, capped at this.
+    pub max_concurrency: usize,
+    /// Highest rate in probes per second (0 = no cap), shared by all
+    /// running scans.
+    pub max_rate: u32,
+    /// Most ports a single request may scan.
+    pub max_ports: usize,
+    /// Most scans running at once; further requests are refused with `429`.
+    pub max_scans: usize,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 500,
+            max_rate: 0,
+            max_ports: 65535,
+            max_scans: 4,
+        }
+    }
+}
+
+/// Body of a `POST /scans` request.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ScanRequest {
+    /// Host to scan (IP or hostname); ranges are not accepted.
+    pub target: String,
+    /// Port specification, as on the command line.
+    #[serde(default = "default_ports")]
+    pub ports: String,
+    #[serde(default)]
+    pub scan_type: ScanType,
+    pub concurrency: Option<usize>,
+    /// Probes per second; capped by the server's `max_rate`.
+    pub rate_limit: Option<u32>,
+    pub timeout_ms: Option<u64>,
+    #[serde(default)]
+    pub banner: bool,
+}
+
+fn default_ports() -> String {
+    "1-1000".to_string()
+}
+
+/// Short form of a saved scan for `GET /scans`.
+#[derive(Debug, Serialize)]
+struct ScanSummary {
+    id: ScanId,
+    target: String,
+    ip_address: String,
+    scan_type: String,
+    started_at: DateTime<Utc>,
+    ports_scanned: usize,
+    open_ports: usize,
+    duration_ms: u64,
+}
+
+impl From<&ScanRecord> for ScanSummary {
+    fn from(record: &ScanRecord) -> Self {
+        Self {
+            id: record.id,
+            target: record.target.clone(),
+            ip_address: record.ip_address.clone(),
+            scan_type: record.scan_type.clone(),
+            started_at: record.started_at,
+            ports_scanned: record.ports_scanned,
+            open_ports: record.open_ports,
+            duration_ms: record.duration_ms,
+        }
+    }
+}
+
+/// Scans that have not been saved yet.
+#[derive(Debug, Clone)]
+enum Job {
+    Running,
+    Failed(String),
+}
+
+#[derive(Clone)]
+struct AppState {
+    config: Arc<ServerConfig>,
+    jobs: Arc<Mutex<HashMap<ScanId, Job>>>,
+    /// A permit per scan that may run.
+    slots: Arc<Semaphore>,
+    /// Token bucket for `max_rate`, drawn from by every scan.
+    rate_limiter: RateLimiter,
+}
+
+/// An error answered as `{"error": "..."}` with a status code.
+#[derive(Debug)]
+struct ApiError(StatusCode, String);
+
+impl ApiError {
+    fn bad_request(message: impl Into<String>) -> Self {
+        Self(StatusCode::BAD_REQUEST, message.into())
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        (self.0, Json(json!({ "error": self.1 }))).into_response()
+    }
+}
+
+impl From<StorageError> for ApiError {
+    fn from(e: StorageError) -> Self {
+        let status = match e {
+            StorageError::ScanNotFound(_) => StatusCode::NOT_FOUND,
+            StorageError::InvalidScanId(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        };
+        Self(status, e.to_string())
+    }
+}
+
+/// Build the API router with the given limits.
+pub fn router(config: ServerConfig) -> Router {
+    let state = AppState {
+        slots: Arc::new(Semaphore::new(config.max_scans.max(1))),
+        rate_limiter: RateLimiter::from_limit(config.max_rate),
+        config: Arc::new(config),
+        jobs: Arc::new(Mutex::new(HashMap::new())),
+    };
+
+    Router::new()
+        .route("/scans", get(list_scans).post(start_scan))
+        .route("/scans/:id", get(get_scan))
+        .with_state(state)
+}
+
+/// Serve the API on `addr` until Ctrl-C.
+pub async fn serve(addr: SocketAddr, config: ServerConfig) -> io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    axum::serve(listener, router(config))
+        .with_graceful_shutdown(async {
+            let _ = tokio::signal::ctrl_c().await;
+        })
+        .await
+}
+
+/// A validated request, ready to run.
+struct PreparedScan {
+    target: ScanTarget,
+    job: ScanJobConfig,
+    scanner: Arc<dyn Scanner>,
+}
+
+/// Validate `request` against the server limits and build its scanner.
+///
+/// The scan draws from `rate_limiter`, which enforces `max_rate` across
+/// every scan.
+async fn prepare(
+    config: &ServerConfig,
+    rate_limiter: &RateLimiter,
+    request: &ScanRequest,
+) -> Result<PreparedScan, ApiError> {
+    let spec: PortSpec = request
+        .ports
+        .parse()
+        .map_err(|e| ApiError::bad_request(format!("invalid ports: {}", e)))?;
+    if spec.count() > config.max_ports {
+        return Err(ApiError::bad_request(format!(
+            "{} ports requested, the server allows at most {}",
+            spec.count(),
+            config.max_ports
+        )));
+    }
+
+    if request.scan_type.requires_privileges() && !has_raw_socket_access() {
+        return Err(ApiError::bad_request(format!(
+            "{} scans need raw socket access, which the server does not have",
+            request.scan_type
+        )));
+    }
+
+    let target_spec =
+        TargetSpec::parse(&request.target).map_err(|e| ApiError::bad_request(e.to_string()))?;
+    if target_spec.estimated_host_count() > 1 {
+        return Err(ApiError::bad_request(
+            "ranges are not supported; submit one host per request",
+        ));
+    }
+    let target = target_spec
+        .resolve()
+        .await
+        .map_err(|e| ApiError::bad_request(e.to_string()))?
+        .into_iter()
+        .next()
+        .ok_or_else(|| ApiError::bad_request("target did not resolve"))?;
+
+    let scan_config = ScanConfig::new(target.ip)
+        .with_hostname(&target.original)
+        .with_timeout(Duration::from_millis(request.timeout_ms.unwrap_or(3000)))
+        .without_closed_services();
+    let scan_config = if request.banner {
+        scan_config.with_banners()
+    } else {
+        scan_config
+    };
+    let scanner = create_scanner(request.scan_type, scan_config)
+        .map_err(|e| ApiError::bad_request(e.to_string()))?;
+
+    let concurrency = request
+        .concurrency
+        .unwrap_or_else(|| request.scan_type.default_concurrency())
+        .clamp(1, config.max_concurrency.max(1));
+    let rate_limit = match (request.rate_limit.unwrap_or(0), config.max_rate) {
+        (rate, 0) => rate,
+        (0, cap) => cap,
+        (rate, cap) => rate.min(cap),
+    };
+    let job = ScanJobConfig::from_spec(spec)
+        .with_concurrency(concurrency)
+        .with_rate_limit(rate_limit)
+        .with_rate_limiter(rate_limiter.clone());
+    // A request scans one host, so a rate below the cap goes on its bucket
+    let job = if rate_limit > 0 && (config.max_rate == 0 || rate_limit < config.max_rate) {
+        job.with_host_rate_limiter(KeyedRateLimiter::from_limit(rate_limit))
+    } else {
+        job
+    };
+
+    Ok(PreparedScan {
+        target,
+        job,
+        scanner,
+    })
+}
+
+async fn start_scan(
+    State(state): State<AppState>,
+    Json(request): Json<ScanRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let prepared = prepare(&state.config, &state.rate_limiter, &request).await?;
+    let slot = Arc::clone(&state.slots).try_acquire_owned().map_err(|_| {
+        ApiError(
+            StatusCode::TOO_MANY_REQUESTS,
+            format!(
+                "{} scans are already running; try again later",
+                state.config.max_scans.max(1)
+            ),
+        )
+    })?;
+    let store = ScanStore::new()?;
+
+    let id = ScanId::new();
+    state.jobs.lock().unwrap().insert(id, Job::Running);
+
+    let jobs = Arc::clone(&state.jobs);
+    tokio::spawn(async move {
+        let outcome = match run_scan(prepared.scanner, prepared.job).await {
+            Ok(mut record) => {
+                record.id = id;
+                record.target = prepared.target.original;
+                store.save(&record).map_err(|e| e.to_string())
+            }
+            Err(e) => Err(e.to_string()),
+        };
+        drop(slot);
+        finish_job(&jobs, id, outcome).await;
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(json!({ "id": id, "status": "running" })),
+    ))
+}
+
+/// Retire a job once its scan has finished.
+///
+/// A saved scan is served from the store from then on. A failed one saved
+/// nothing, so its error is kept for [`FAILED_JOB_TTL`] before it goes too.
+async fn finish_job(jobs: &Mutex<HashMap<ScanId, Job>>, id: ScanId, outcome: Result<(), String>) {
+    if let Err(e) = outcome {
+        jobs.lock().unwrap().insert(id, Job::Failed(e));
+        tokio::time::sleep(FAILED_JOB_TTL).await;
+    }
+    jobs.lock().unwrap().remove(&id);
+}
+
+async fn get_scan(
+    State(state): State<AppState>,
+    Path(id): Path<String>,
+) -> Result<Response, ApiError> {
+    if let Ok(scan_id) = id.parse::<ScanId>() {
+        match state.jobs.lock().unwrap().get(&scan_id) {
+            Some(Job::Running) => {
+                let body = json!({ "id": scan_id, "status": "running" });
+                return Ok((StatusCode::ACCEPTED, Json(body)).into_response());
+            }
+            Some(Job::Failed(e)) => {
+                let body = json!({ "id": scan_id, "status": "failed", "error": e });
+                return Ok(Json(body).into_response());
+            }
+            None => {}
+        }
+    }
+
+    let record = ScanStore::new()?.find(&id)?;
+    Ok(Json(record).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+struct ListQuery {
+    limit: Option<usize>,
+}
+
+async fn list_scans(Query(query): Query<ListQuery>) -> Result<impl IntoResponse, ApiError> {
+    let records = ScanStore::new()?.list_recent(query.limit.unwrap_or(20))?;
+    let summaries: Vec<ScanSummary> = records.iter().map(ScanSummary::from).collect();
+    Ok(Json(summaries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(body: serde_json::Value) -> ScanRequest {
+        serde_json::from_value(body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_prepare_enforces_limits() {
+        let config = ServerConfig {
+            max_concurrency: 100,
+            max_rate: 50,
+            max_ports: 1000,
+            max_scans: 4,
+        };
+        let limiter = RateLimiter::from_limit(config.max_rate);
+
+        let prepared = prepare(
+            &config,
+            &limiter,
+            &request(json!({ "target": "127.0.0.1", "concurrency": 5000, "rate_limit": 500 })),
+        )
+        .await
+        .unwrap();
+        assert_eq!(prepared.job.concurrency, Some(100));
+        assert_eq!(prepared.job.rate_limit, 50);
+        assert_eq!(prepared.job.ports.len(), 1000);
+        assert!(prepared.job.rate_limiter.is_some());
+        assert!(prepared.job.host_rate_limiter.is_none());
+
+        // A rate under the cap is held on the scan's own host
+        let slower = request(json!({ "target": "127.0.0.1", "rate_limit": 10 }));
+        let prepared = prepare(&config, &limiter, &slower).await.unwrap();
+        assert_eq!(prepared.job.host_rate_limiter.unwrap().rate(), 10);
+
+        let default_rate = request(json!({ "target": "127.0.0.1" }));
+        let prepared = prepare(&config, &limiter, &default_rate).await.unwrap();
+        assert_eq!(prepared.job.rate_limit, 50);
+
+        let too_many = request(json!({ "target": "127.0.0.1", "ports": "1-2000" }));
+        let err = prepare(&config, &limiter, &too_many).await.err().unwrap();
+        assert_eq!(err.0, StatusCode::BAD_REQUEST);
+
+        let range = request(json!({ "target": "10.0.0.0/24" }));
+        let err = prepare(&config, &limiter, &range).await.err().unwrap();
+        assert!(err.1.contains("one host"));
+    }
+
+    #[tokio::test]
+    async fn test_start_scan_refused_when_full() {
+        let state = AppState {
+            config: Arc::new(ServerConfig {
+                max_scans: 1,
+                ..Default::default()
+            }),
+            jobs: Arc::new(Mutex::new(HashMap::new())),
+            slots: Arc::new(Semaphore::new(1)),
+            rate_limiter: RateLimiter::unlimited(),
+        };
+        // Stands in for a scan already running
+        let _running = Arc::clone(&state.slots).try_acquire_owned().unwrap();
+
+        let body = request(json!({ "target": "127.0.0.1", "ports": "80" }));
+        let err = start_scan(State(state.clone()), Json(body))
+            .await
+            .err()
+            .unwrap();
+        assert_eq!(err.0, StatusCode::TOO_MANY_REQUESTS);
+        assert!(state.jobs.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_finished_jobs_are_evicted() {
+        let jobs = Arc::new(Mutex::new(HashMap::new()));
+        let (saved, failed) = (ScanId::new(), ScanId::new());
+        jobs.lock().unwrap().insert(saved, Job::Running);
+        jobs.lock().unwrap().insert(failed, Job::Running);
+
+        finish_job(&jobs, saved, Ok(())).await;
+        assert!(!jobs.lock().unwrap().contains_key(&saved));
+
+        let finishing = tokio::spawn({
+            let jobs = Arc::clone(&jobs);
+            async move { finish_job(&jobs, failed, Err("boom".to_string())).await }
+        });
+        tokio::task::yield_now().await;
+        assert!(matches!(
+            jobs.lock().unwrap().get(&failed),
+            Some(Job::Failed(e)) if e == "boom"
+        ));
+
+        tokio::time::advance(FAILED_JOB_TTL).await;
+        finishing.await.unwrap();
+        assert!(jobs.lock().unwrap().is_empty());
+    }
+}
@@ -1,9 +1,23 @@
 //! Service detection based on well-known port numbers.
 //!
-//! Provides mapping from port numbers to likely service names.
+//! Provides mapping from port numbers to likely service names, and the
+//! [`ServiceDetector`] trait for plugging in custom identification logic
+//! (e.g. internal services on nonstandard ports).
+//!
+//! The built-in map can be supplemented from a user file, either a JSON
+//! object (`{"8443": "internal-api"}`) or nmap's `nmap-services` format.
+//! Entries loaded with [`install_user_services`] take precedence.
 
+use crate::error::{ConfigError, ConfigResult};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::sync::LazyLock;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, LazyLock, OnceLock};
+
+/// User-supplied port names, merged over the built-in map.
+static USER_SERVICES: OnceLock<HashMap<u16, String>> = OnceLock::new();
 
 /// Static map of well-known ports to service names.
 static PORT_SERVICES: LazyLock<HashMap<u16, &'static str>> = LazyLock::new(|| {
@@ -130,9 +144,87 @@ static PORT_SERVICES: LazyLock<HashMap<u16, &'static str>> = LazyLock::new(|| {
 
 /// Look up the probable service name for a given port.
 ///
-/// Returns `None` if the port is not in the well-known services database.
+/// User-supplied entries win over the built-in map. Returns `None` if the
+/// port is in neither.
 pub fn get_service_name(port: u16) -> Option<&'static str> {
-    PORT_SERVICES.get(&port).copied()
+    USER_SERVICES
+        .get()
+        .and_then(|services| services.get(&port))
+        .map(String::as_str)
+        .or_else(|| PORT_SERVICES.get(&port).copied())
+}
+
+/// Install user-supplied service names for the rest of the process.
+///
+/// Returns `false` if services were already installed.
+pub fn install_user_services(services: HashMap<u16, String>) -> bool {
+    USER_SERVICES.set(services).is_ok()
+}
+
+/// Load a service file; `.json` files are parsed as JSON, anything else
+/// as `nmap-services`.
+pub fn load_service_file(path: &Path) -> ConfigResult<HashMap<u16, String>> {
+    let content = fs::read_to_string(path).map_err(|e| ConfigError::ReadFailed {
+        path: path.to_path_buf(),
+        reason: e.to_string(),
+    })?;
+
+    if path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+    {
+        parse_service_json(&content)
+    } else {
+        parse_nmap_services(&content)
+    }
+}
+
+/// Parse a JSON object mapping port numbers to service names.
+fn parse_service_json(content: &str) -> ConfigResult<HashMap<u16, String>> {
+    let entries: HashMap<String, String> = serde_json::from_str(content)?;
+    entries
+        .into_iter()
+        .map(|(port, name)| {
+            port.parse::<u16>()
+                .map(|port| (port, name))
+                .map_err(|_| ConfigError::InvalidFormat(format!("invalid port '{}'", port)))
+        })
+        .collect()
+}
+
+/// Parse nmap-services lines (`name port/protocol [frequency] [# comment]`).
+///
+/// TCP entries take precedence over UDP ones; the first entry for a port wins.
+fn parse_nmap_services(content: &str) -> ConfigResult<HashMap<u16, String>> {
+    let mut tcp = HashMap::new();
+    let mut udp = HashMap::new();
+
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let invalid = || ConfigError::InvalidFormat(format!("line {}: '{}'", number + 1, line));
+        let mut fields = line.split_whitespace();
+        let (Some(name), Some(port_proto)) = (fields.next(), fields.next()) else {
+            return Err(invalid());
+        };
+        let (port, protocol) = port_proto.split_once('/').ok_or_else(invalid)?;
+        let port: u16 = port.parse().map_err(|_| invalid())?;
+
+        let map = match protocol {
+            "tcp" => &mut tcp,
+            "udp" => &mut udp,
+            _ => continue,
+        };
+        map.entry(port).or_insert_with(|| name.to_string());
+    }
+
+    for (port, name) in udp {
+        tcp.entry(port).or_insert(name);
+    }
+    Ok(tcp)
 }
 
 /// Get a descriptive string for the service on a port.
@@ -142,10 +234,306 @@ pub fn get_service_description(port: u16) -> &'static str {
     get_service_name(port).unwrap_or("unknown")
 }
 
+/// Find the ports mapped to a service name (case-insensitive), sorted.
+///
+/// Uses the same precedence as [`get_service_name`], so a built-in port that
+/// a user entry renamed no longer matches its old name.
+pub fn ports_for_service(name: &str) -> Vec<u16> {
+    let user_ports = USER_SERVICES.get().into_iter().flat_map(|m| m.keys());
+    let mut ports: Vec<u16> = PORT_SERVICES
+        .keys()
+        .chain(user_ports)
+        .copied()
+        .filter(|&port| get_service_name(port).is_some_and(|s| s.eq_ignore_ascii_case(name)))
+        .collect();
+    ports.sort_unstable();
+    ports.dedup();
+    ports
+}
+
+/// How a service name was identified.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceSource {
+    /// Inferred from the port number alone.
+    #[default]
+    Guessed,
+    /// Matched against the service's banner.
+    BannerMatched,
+    /// Banner identified the product and its version.
+    VersionDetected,
+}
+
+impl ServiceSource {
+    /// Rough confidence in the identification, as a percentage.
+    pub const fn confidence(self) -> u8 {
+        match self {
+            Self::Guessed => 30,
+            Self::BannerMatched => 80,
+            Self::VersionDetected => 95,
+        }
+    }
+}
+
+impl fmt::Display for ServiceSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Guessed => write!(f, "guessed"),
+            Self::BannerMatched => write!(f, "banner"),
+            Self::VersionDetected => write!(f, "version"),
+        }
+    }
+}
+
+/// Identifies the service behind a port, optionally using its banner.
+pub trait ServiceDetector: Send + Sync {
+    /// Return a service name, or `None` if this detector does not recognize the port.
+    fn detect(&self, port: u16, banner: Option<&str>) -> Option<String>;
+
+    /// How this detector's answers are derived; port-based guesses by default.
+    fn source(&self) -> ServiceSource {
+        ServiceSource::Guessed
+    }
+}
+
+/// Default detector backed by the static well-known port map.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PortMapDetector;
+
+impl ServiceDetector for PortMapDetector {
+    fn detect(&self, port: u16, _banner: Option<&str>) -> Option<String> {
+        get_service_name(port).map(str::to_string)
+    }
+}
+
+/// Recognizes common protocols from their greeting or response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BannerDetector;
+
+impl ServiceDetector for BannerDetector {
+    fn detect(&self, _port: u16, banner: Option<&str>) -> Option<String> {
+        let banner = banner?;
+        let lower = banner.to_ascii_lowercase();
+        let service = if banner.starts_with("SSH-") {
+            "ssh"
+        } else if banner.starts_with("HTTP/") {
+            "http"
+        } else if banner.starts_with("220") && lower.contains("ftp") {
+            "ftp"
+        } else if banner.starts_with("220") && lower.contains("smtp") {
+            "smtp"
+        } else if banner.starts_with("+OK") {
+            "pop3"
+        } else if banner.starts_with("* OK") {
+            "imap"
+        } else if banner.starts_with("RFB ") {
+            "vnc"
+        } else {
+            return None;
+        };
+        Some(service.to_string())
+    }
+
+    fn source(&self) -> ServiceSource {
+        ServiceSource::BannerMatched
+    }
+}
+
+/// Recognizes banners that name the server software and its version,
+/// such as `SSH-2.0-OpenSSH_9.6` or an HTTP `Server: nginx/1.24.0` header.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct VersionDetector;
+
+impl ServiceDetector for VersionDetector {
+    fn detect(&self, _port: u16, banner: Option<&str>) -> Option<String> {
+        let banner = banner?;
+        let has_version = |product: &str| product.chars().any(|c| c.is_ascii_digit());
+
+        if let Some(rest) = banner.strip_prefix("SSH-") {
+            let software = rest.split_once('-')?.1;
+            return has_version(software).then(|| "ssh".to_string());
+        }
+        if banner.starts_with("HTTP/") {
+            let server = banner.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                name.eq_ignore_ascii_case("server").then_some(value.trim())
+            })?;
+            return server
+                .split_once('/')
+                .filter(|(_, version)| has_version(version))
+                .map(|_| "http".to_string());
+        }
+        None
+    }
+
+    fn source(&self) -> ServiceSource {
+        ServiceSource::VersionDetected
+    }
+}
+
+/// A service name and how it was identified.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Identification {
+    /// Service name, or "unknown".
+    pub service: String,
+    /// Detector kind that produced the name.
+    pub source: ServiceSource,
+}
+
+/// Ordered chain of service detectors.
+///
+/// Starts with [`PortMapDetector`], [`BannerDetector`] and
+/// [`VersionDetector`]; detectors registered later refine the result, so
+/// the last one that recognizes a port wins.
+#[derive(Clone)]
+pub struct ServiceDetectors {
+    detectors: Vec<Arc<dyn ServiceDetector>>,
+}
+
+impl ServiceDetectors {
+    /// Add a detector that runs after those already registered.
+    pub fn register(&mut self, detector: impl ServiceDetector + 'static) {
+        self.detectors.push(Arc::new(detector));
+    }
+
+    /// Identify the service on a port, or "unknown" if no detector recognizes it.
+    pub fn identify(&self, port: u16, banner: Option<&str>) -> Identification {
+        self.detectors
+            .iter()
+            .rev()
+            .find_map(|detector| {
+                detector.detect(port, banner).map(|service| Identification {
+                    service,
+                    source: detector.source(),
+                })
+            })
+            .unwrap_or_else(|| Identification {
+                service: "unknown".to_string(),
+                source: ServiceSource::Guessed,
+            })
+    }
+}
+
+impl Default for ServiceDetectors {
+    fn default() -> Self {
+        Self {
+            detectors: vec![
+                Arc::new(PortMapDetector),
+                Arc::new(BannerDetector),
+                Arc::new(VersionDetector),
+            ],
+        }
+    }
+}
+
+impl fmt::Debug for ServiceDetectors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ServiceDetectors")
+            .field("detectors", &self.detectors.len())
+            .finish()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    struct InternalDetector;
+
+    impl ServiceDetector for InternalDetector {
+        fn detect(&self, port: u16, banner: Option<&str>) -> Option<String> {
+            match (port, banner) {
+                (_, Some(b)) if b.starts_with("ACME") => Some("acme-rpc".to_string()),
+                (4444, _) => Some("acme-admin".to_string()),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_nmap_services() {
+        let content = "# comment\n\
+                       acme-admin\t4444/tcp\t0.000010\t# internal\n\
+                       acme-sync\t4444/udp\n\
+                       acme-beacon\t4445/udp\n\
+                       sctp-thing\t4446/sctp\n";
+        let services = parse_nmap_services(content).unwrap();
+        assert_eq!(services.get(&4444).map(String::as_str), Some("acme-admin"));
+        assert_eq!(services.get(&4445).map(String::as_str), Some("acme-beacon"));
+        assert_eq!(services.len(), 2);
+
+        assert!(parse_nmap_services("broken-line\n").is_err());
+        assert!(parse_nmap_services("svc 99999/tcp\n").is_err());
+    }
+
+    #[test]
+    fn test_parse_service_json() {
+        let services = parse_service_json(r#"{"8443": "internal-api"}"#).unwrap();
+        assert_eq!(
+            services.get(&8443).map(String::as_str),
+            Some("internal-api")
+        );
+        assert!(parse_service_json(r#"{"http": "web"}"#).is_err());
+    }
+
+    #[test]
+    fn test_service_detectors() {
+        let mut detectors = ServiceDetectors::default();
+        assert_eq!(detectors.identify(22, None).service, "ssh");
+        assert_eq!(detectors.identify(4444, None).service, "unknown");
+
+        detectors.register(InternalDetector);
+        assert_eq!(detectors.identify(4444, None).service, "acme-admin");
+        assert_eq!(
+            detectors.identify(8080, Some("ACME v2 ready")).service,
+            "acme-rpc"
+        );
+        // Unrecognized ports keep the static name
+        assert_eq!(
+            detectors.identify(22, Some("SSH-2.0-OpenSSH")).service,
+            "ssh"
+        );
+    }
+
+    #[test]
+    fn test_service_source() {
+        let detectors = ServiceDetectors::default();
+        let identify = |port, banner| {
+            let id = detectors.identify(port, banner);
+            (id.service, id.source)
+        };
+
+        assert_eq!(
+            identify(80, None),
+            ("http".to_string(), ServiceSource::Guessed)
+        );
+        assert_eq!(
+            identify(2222, Some("SSH-2.0-OpenSSH_9.6p1 Ubuntu-3")),
+            ("ssh".to_string(), ServiceSource::VersionDetected)
+        );
+        assert_eq!(
+            identify(
+                8765,
+                Some("HTTP/1.0 200 OK\nServer: SimpleHTTP/0.6 Python/3.12.3")
+            ),
+            ("http".to_string(), ServiceSource::VersionDetected)
+        );
+        assert_eq!(
+            identify(8080, Some("HTTP/1.1 404 Not Found\nServer: nginx")),
+            ("http".to_string(), ServiceSource::BannerMatched)
+        );
+        assert_eq!(
+            identify(2121, Some("220 ProFTPD Server ready.")),
+            ("ftp".to_string(), ServiceSource::BannerMatched)
+        );
+        // An unrecognized banner falls back to the port guess
+        assert_eq!(
+            identify(22, Some("hello")),
+            ("ssh".to_string(), ServiceSource::Guessed)
+        );
+        assert!(ServiceSource::VersionDetected.confidence() > ServiceSource::Guessed.confidence());
+    }
+
     #[test]
     fn test_common_ports() {
         assert_eq!(get_service_name(22), Some("ssh"));
@@ -154,6 +542,13 @@ mod tests {
         assert_eq!(get_service_name(3306), Some("mysql"));
     }
 
+    #[test]
+    fn test_ports_for_service() {
+        assert_eq!(ports_for_service("SSH"), vec![22]);
+        assert!(ports_for_service("http").contains(&80));
+        assert!(ports_for_service("no-such-service").is_empty());
+    }
+
     #[test]
     fn test_unknown_port() {
         assert_eq!(get_service_name(12345), None);
@@ -0,0 +1,350 @@
+//! Comparison of two scans of the same host, or of the same range.
+//!
+//! A [`ScanDiff`] lists ports that opened or closed between a baseline and
+//! a newer scan, and banners that changed on ports open in both. Ports only
+//! one of the scans covered are not compared.
+//!
+//! A [`SessionDiff`] matches the hosts of two range scans by IP address and
+//! reports hosts that appeared or disappeared, and a [`ScanDiff`] for each
+//! host seen in both.
+
+use crate::scanner::traits::{PortResult, PortStatus};
+use crate::scanner::HostState;
+use crate::storage::ScanRecord;
+use crate::types::{Port, PortSpec};
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::net::IpAddr;
+
+/// A port whose status changed between two scans.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StatusChange {
+    pub port: Port,
+    pub before: PortStatus,
+    pub after: PortStatus,
+    /// Service name from the newer scan.
+    pub service: String,
+}
+
+/// An open port whose banner changed between two scans.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BannerChange {
+    pub port: Port,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+/// Differences between a baseline scan and a newer scan.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ScanDiff {
+    /// Ports open now that were not open in the baseline.
+    pub newly_open: Vec<StatusChange>,
+    /// Ports open in the baseline that are no longer open.
+    pub newly_closed: Vec<StatusChange>,
+    /// Ports open in both scans whose banner changed.
+    pub banner_changes: Vec<BannerChange>,
+}
+
+impl ScanDiff {
+    /// Compare `current` against `baseline`.
+    pub fn between(baseline: &ScanRecord, current: &ScanRecord) -> Self {
+        let mut diff = Self::default();
+
+        let ports: BTreeSet<Port> = baseline
+            .results
+            .iter()
+            .chain(&current.results)
+            .map(|r| r.port)
+            .filter(|port| covers(baseline, *port) && covers(current, *port))
+            .collect();
+
+        for port in ports {
+            let before = find(baseline, port);
+            let after = find(current, port);
+            let was_open = before.is_some_and(PortResult::is_open);
+            let is_open = after.is_some_and(PortResult::is_open);
+
+            let change = || StatusChange {
+                port,
+                before: status(before),
+                after: status(after),
+                service: after
+                    .or(before)
+                    .map(|r| r.service.clone())
+                    .unwrap_or_default(),
+            };
+
+            match (was_open, is_open) {
+                (false, true) => diff.newly_open.push(change()),
+                (true, false) => diff.newly_closed.push(change()),
+                (true, true) => {
+                    let before = before.and_then(|r| r.banner.clone());
+                    let after = after.and_then(|r| r.banner.clone());
+                    if before != after {
+                        diff.banner_changes.push(BannerChange {
+                            port,
+                            before,
+                            after,
+                        });
+                    }
+                }
+                (false, false) => {}
+            }
+        }
+
+        diff
+    }
+
+    /// Check if the scans showed no differences.
+    pub fn is_empty(&self) -> bool {
+        self.newly_open.is_empty() && self.newly_closed.is_empty() && self.banner_changes.is_empty()
+    }
+}
+
+/// A host seen by only one of two range scans.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HostChange {
+    /// IP address of the host.
+    pub ip: String,
+    /// Reverse DNS name of the host, with `--resolve-ptr`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Ports open on the host in the scan that saw it.
+    pub open_ports: Vec<Port>,
+}
+
+impl HostChange {
+    fn of(record: &ScanRecord) -> Self {
+        Self {
+            ip: record.ip_address.clone(),
+            hostname: record.hostname.clone(),
+            open_ports: record
+                .results
+                .iter()
+                .filter(|r| r.is_open())
+                .map(|r| r.port)
+                .collect(),
+        }
+    }
+}
+
+/// Port changes on a host seen by both range scans.
+#[derive(Debug, Clone, Serialize)]
+pub struct HostDiff {
+    /// IP address of the host.
+    pub ip: String,
+    /// Port changes between the two scans of the host.
+    #[serde(flatten)]
+    pub diff: ScanDiff,
+}
+
+/// Differences between two scans of a range, host by host.
+///
+/// A host counts as seen when it answered host discovery or, with discovery
+/// skipped, when any port answered. Hosts that only one of the scans
+/// covered, such as after the range was widened, are listed as not compared
+/// rather than as new or gone.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SessionDiff {
+    /// Hosts seen now that the baseline scanned without seeing.
+    pub new_hosts: Vec<HostChange>,
+    /// Hosts seen in the baseline that the newer scan did not see.
+    pub gone_hosts: Vec<HostChange>,
+    /// Hosts seen in both scans whose ports changed.
+    pub changed_hosts: Vec<HostDiff>,
+    /// Addresses scanned by only one of the two scans.
+    pub not_compared: Vec<String>,
+}
+
+impl SessionDiff {
+    /// Compare the host records of `current` against those of `baseline`.
+    pub fn between(baseline: &[ScanRecord], current: &[ScanRecord]) -> Self {
+        let mut diff = Self::default();
+
+        let before = by_host(baseline);
+        let after = by_host(current);
+        let hosts: BTreeSet<&HostKey> = before.keys().chain(after.keys()).collect();
+
+        for host in hosts {
+            let (before, after) = match (before.get(host), after.get(host)) {
+                (Some(before), Some(after)) => (*before, *after),
+                (Some(record), None) | (None, Some(record)) => {
+                    diff.not_compared.push(record.ip_address.clone());
+                    continue;
+                }
+                (None, None) => unreachable!("host comes from one of the maps"),
+            };
+
+            match (seen(before), seen(after)) {
+                (false, true) => diff.new_hosts.push(HostChange::of(after)),
+                (true, false) => diff.gone_hosts.push(HostChange::of(before)),
+                (true, true) => {
+                    let ports = ScanDiff::between(before, after);
+                    if !ports.is_empty() {
+                        diff.changed_hosts.push(HostDiff {
+                            ip: after.ip_address.clone(),
+                            diff: ports,
+                        });
+                    }
+                }
+                (false, false) => {}
+            }
+        }
+
+        diff
+    }
+
+    /// Check if the scans showed no differences between the hosts both covered.
+    pub fn is_empty(&self) -> bool {
+        self.new_hosts.is_empty() && self.gone_hosts.is_empty() && self.changed_hosts.is_empty()
+    }
+}
+
+/// Sort key for a host: addresses in numeric order, then anything unparsable.
+type HostKey = (Option<IpAddr>, String);
+
+fn by_host(records: &[ScanRecord]) -> BTreeMap<HostKey, &ScanRecord> {
+    records
+        .iter()
+        .map(|r| ((r.ip_address.parse().ok(), r.ip_address.clone()), r))
+        .collect()
+}
+
+/// Whether a scan saw the host: it answered discovery, or discovery did not
+/// run and a port answered with an open or closed status.
+fn seen(record: &ScanRecord) -> bool {
+    match record.host_state {
+        HostState::Up => true,
+        HostState::Down => false,
+        HostState::Unknown => record
+            .results
+            .iter()
+            .any(|r| matches!(r.status, PortStatus::Open | PortStatus::Closed)),
+    }
+}
+
+/// Result for `port` in `record`, if it was reported.
+fn find(record: &ScanRecord, port: Port) -> Option<&PortResult> {
+    record.results.iter().find(|r| r.port == port)
+}
+
+/// Status of a result; unreported ports were closed, since only closed
+/// ports are left out of records.
+fn status(result: Option<&PortResult>) -> PortStatus {
+    result.map_or(PortStatus::Closed, |r| r.status)
+}
+
+/// Whether `record` scanned `port`. Older records without a port
+/// specification are assumed to cover every port.
+fn covers(record: &ScanRecord, port: Port) -> bool {
+    record.port_spec.is_empty()
+        || record
+            .port_spec
+            .parse::<PortSpec>()
+            .map_or(true, |spec| spec.contains(port))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::ScanType;
+
+    fn record(spec: &str, results: Vec<PortResult>) -> ScanRecord {
+        let mut record =
+            ScanRecord::new("10.0.0.1", "10.0.0.1", ScanType::Connect).finalize(results, 0);
+        record.port_spec = spec.to_string();
+        record
+    }
+
+    fn result(port: u16, status: PortStatus, banner: Option<&str>) -> PortResult {
+        PortResult::new(Port::new(port).unwrap(), status, "svc")
+            .with_banner(banner.map(str::to_string))
+    }
+
+    #[test]
+    fn test_scan_diff() {
+        let baseline = record(
+            "1-1000",
+            vec![
+                result(22, PortStatus::Open, Some("SSH-2.0-OpenSSH_8.9")),
+                result(80, PortStatus::Open, None),
+                result(443, PortStatus::Filtered, None),
+            ],
+        );
+        let current = record(
+            "1-1000,8080",
+            vec![
+                result(22, PortStatus::Open, Some("SSH-2.0-OpenSSH_9.6")),
+                result(443, PortStatus::Open, None),
+                result(8080, PortStatus::Open, None),
+            ],
+        );
+
+        let diff = ScanDiff::between(&baseline, &current);
+        let ports =
+            |changes: &[StatusChange]| changes.iter().map(|c| c.port.as_u16()).collect::<Vec<_>>();
+        // 8080 was not in the baseline's range, so it is not "new"
+        assert_eq!(ports(&diff.newly_open), vec![443]);
+        assert_eq!(diff.newly_open[0].before, PortStatus::Filtered);
+        assert_eq!(ports(&diff.newly_closed), vec![80]);
+        assert_eq!(diff.newly_closed[0].after, PortStatus::Closed);
+        assert_eq!(diff.banner_changes.len(), 1);
+        assert_eq!(
+            diff.banner_changes[0].after.as_deref(),
+            Some("SSH-2.0-OpenSSH_9.6")
+        );
+        assert!(!diff.is_empty());
+    }
+
+    #[test]
+    fn test_scan_diff_identical() {
+        let scan = record("22,80", vec![result(22, PortStatus::Open, None)]);
+        assert!(ScanDiff::between(&scan, &scan).is_empty());
+    }
+
+    #[test]
+    fn test_session_diff() {
+        let host = |ip: &str, state: HostState, open: &[u16]| {
+            let results = open
+                .iter()
+                .map(|&port| result(port, PortStatus::Open, None))
+                .collect();
+            let mut record = record("22,80", results);
+            record.ip_address = ip.to_string();
+            record.host_state = state;
+            record
+        };
+        use HostState::{Down, Unknown, Up};
+
+        let baseline = [
+            host("10.0.0.1", Up, &[22]),
+            host("10.0.0.2", Up, &[80]),
+            host("10.0.0.10", Down, &[]),
+            host("10.0.0.3", Unknown, &[22]),
+            host("10.0.0.4", Up, &[22]),
+        ];
+        let current = [
+            host("10.0.0.1", Up, &[22, 80]),
+            host("10.0.0.2", Down, &[]),
+            host("10.0.0.10", Up, &[443]),
+            // Discovery skipped and nothing answered
+            host("10.0.0.3", Unknown, &[]),
+            host("10.0.0.4", Up, &[22]),
+            host("10.0.0.5", Up, &[22]),
+        ];
+
+        let diff = SessionDiff::between(&baseline, &current);
+        let ips = |hosts: &[HostChange]| hosts.iter().map(|h| h.ip.clone()).collect::<Vec<_>>();
+        assert_eq!(ips(&diff.new_hosts), ["10.0.0.10"]);
+        assert_eq!(diff.new_hosts[0].open_ports, [Port::new(443).unwrap()]);
+        // Hosts are listed in address order, not string order
+        assert_eq!(ips(&diff.gone_hosts), ["10.0.0.2", "10.0.0.3"]);
+        assert_eq!(diff.changed_hosts.len(), 1);
+        assert_eq!(diff.changed_hosts[0].ip, "10.0.0.1");
+        assert_eq!(diff.changed_hosts[0].diff.newly_open[0].port.as_u16(), 80);
+        assert_eq!(diff.not_compared, ["10.0.0.5"]);
+        assert!(!diff.is_empty());
+
+        assert!(SessionDiff::between(&current, &current).is_empty());
+    }
+}
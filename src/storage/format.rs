@@ -0,0 +1,221 @@
+//! On-disk encodings for scan records.
+//!
+//! Records are JSON by default. MessagePack stores the same fields, by name,
+//! in about half the space, for users keeping a long history, and either
+//! can be gzipped on top (`.json.gz`, `.msgpack.gz`). Loading detects the
+//! encoding from the file extension, or failing that from the first byte,
+//! and compression from the gzip header, so stores can mix them all.
+
+use crate::error::{StorageError, StorageResult};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::Path;
+
+/// Extension added to the format's own by gzip compression.
+pub const GZIP_EXTENSION: &str = "gz";
+
+/// First bytes of every gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Encoding of a stored scan record.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// Pretty-printed JSON, readable and editable by hand.
+    #[default]
+    Json,
+    /// MessagePack, a compact binary encoding of the same fields.
+    #[serde(alias = "messagepack")]
+    Msgpack,
+}
+
+impl StorageFormat {
+    /// Every format, in the order files are looked up.
+    pub const ALL: [StorageFormat; 2] = [StorageFormat::Json, StorageFormat::Msgpack];
+
+    /// File extension of this format, without the dot.
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Msgpack => "msgpack",
+        }
+    }
+
+    /// File name of record `id` stored in this format.
+    pub fn file_name(self, id: impl std::fmt::Display, compressed: bool) -> String {
+        if compressed {
+            format!("{}.{}.{}", id, self.extension(), GZIP_EXTENSION)
+        } else {
+            format!("{}.{}", id, self.extension())
+        }
+    }
+
+    /// Format of the file at `path`, from its extension, looking past a
+    /// `.gz` one.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let path = if is_gzip_path(path) {
+            Path::new(path.file_stem()?)
+        } else {
+            path
+        };
+        let ext = path.extension()?;
+        Self::ALL.into_iter().find(|f| ext == f.extension())
+    }
+
+    /// Format of `content`, from its first byte.
+    ///
+    /// JSON documents start with `{` or whitespace; a MessagePack map never
+    /// does.
+    pub fn detect(content: &[u8]) -> Self {
+        match content.first() {
+            Some(b'{' | b'[' | b' ' | b'\t' | b'\r' | b'\n') | None => Self::Json,
+            Some(_) => Self::Msgpack,
+        }
+    }
+
+    /// Encode `value` in this format.
+    pub fn encode<T: Serialize>(self, value: &T) -> StorageResult<Vec<u8>> {
+        match self {
+            Self::Json => Ok(serde_json::to_vec_pretty(value)?),
+            // Named fields, so that optional fields can be left out and
+            // records from other versions still load
+            Self::Msgpack => {
+                rmp_serde::to_vec_named(value).map_err(|e| StorageError::Encoding(e.to_string()))
+            }
+        }
+    }
+
+    /// Decode a value in this format.
+    pub fn decode<T: DeserializeOwned>(self, content: &[u8]) -> StorageResult<T> {
+        match self {
+            Self::Json => Ok(serde_json::from_slice(content)?),
+            Self::Msgpack => {
+                rmp_serde::from_slice(content).map_err(|e| StorageError::Encoding(e.to_string()))
+            }
+        }
+    }
+
+    /// Decode the file at `path`, in the format its extension names or,
+    /// without a known extension, the one its content starts with.
+    /// Gzipped files are decompressed first.
+    pub fn load<T: DeserializeOwned>(path: &Path) -> StorageResult<T> {
+        Self::read(path).map_err(|e| StorageError::LoadFailed(format!("{}: {}", path.display(), e)))
+    }
+
+    /// [`load`](Self::load), with errors that do not name the file.
+    pub fn read<T: DeserializeOwned>(path: &Path) -> StorageResult<T> {
+        let mut content = fs::read(path)?;
+        if content.starts_with(&GZIP_MAGIC) {
+            content = gunzip(&content)?;
+        }
+        Self::from_path(path)
+            .unwrap_or_else(|| Self::detect(&content))
+            .decode(&content)
+    }
+}
+
+/// Check if `path` names a gzipped file.
+fn is_gzip_path(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext == GZIP_EXTENSION)
+}
+
+/// Compress `content` with gzip.
+pub fn gzip(content: &[u8]) -> io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(content)?;
+    encoder.finish()
+}
+
+/// Decompress a gzip stream.
+fn gunzip(content: &[u8]) -> io::Result<Vec<u8>> {
+    let mut decompressed = Vec::new();
+    GzDecoder::new(content).read_to_end(&mut decompressed)?;
+    Ok(decompressed)
+}
+
+impl std::fmt::Display for StorageFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.extension())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::{PortResult, PortStatus, ScanType};
+    use crate::storage::ScanRecord;
+    use crate::types::Port;
+
+    #[test]
+    fn test_record_round_trip() {
+        let mut record = ScanRecord::new("example.com", "93.184.216.34", ScanType::Connect)
+            .finalize_with_warnings(
+                vec![
+                    PortResult::new(Port::new(443).unwrap(), PortStatus::Open, "https")
+                        .with_banner(Some("HTTP/1.1 200 OK".to_string())),
+                ],
+                120,
+                vec!["sampled".to_string()],
+            );
+        record.sampled = Some(25.0);
+
+        let json = StorageFormat::Json.encode(&record).unwrap();
+        let packed = StorageFormat::Msgpack.encode(&record).unwrap();
+        assert!(packed.len() < json.len());
+        assert_eq!(StorageFormat::detect(&json), StorageFormat::Json);
+        assert_eq!(StorageFormat::detect(&packed), StorageFormat::Msgpack);
+
+        let loaded: ScanRecord = StorageFormat::Msgpack.decode(&packed).unwrap();
+        assert_eq!(
+            serde_json::to_value(&loaded).unwrap(),
+            serde_json::to_value(&record).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_load_detects_format() {
+        let dir = tempfile::tempdir().unwrap();
+        let record = ScanRecord::new("10.0.0.1", "10.0.0.1", ScanType::Syn);
+
+        for (name, format) in [
+            ("scan.msgpack", StorageFormat::Msgpack),
+            ("scan.json", StorageFormat::Json),
+            ("scan.bin", StorageFormat::Msgpack),
+            ("scan", StorageFormat::Json),
+        ] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, format.encode(&record).unwrap()).unwrap();
+            let loaded: ScanRecord = StorageFormat::load(&path).unwrap();
+            assert_eq!(loaded.id, record.id, "{}", name);
+        }
+
+        for (name, format) in [
+            ("scan.json.gz", StorageFormat::Json),
+            ("scan.msgpack.gz", StorageFormat::Msgpack),
+            ("scan.gz", StorageFormat::Msgpack),
+        ] {
+            let path = dir.path().join(name);
+            let content = gzip(&format.encode(&record).unwrap()).unwrap();
+            std::fs::write(&path, content).unwrap();
+            assert_eq!(
+                StorageFormat::from_path(&path),
+                (name != "scan.gz").then_some(format)
+            );
+            let loaded: ScanRecord = StorageFormat::load(&path).unwrap();
+            assert_eq!(loaded.id, record.id, "{}", name);
+        }
+
+        let path = dir.path().join("truncated.msgpack");
+        let packed = StorageFormat::Msgpack.encode(&record).unwrap();
+        std::fs::write(&path, &packed[..packed.len() / 2]).unwrap();
+        assert!(matches!(
+            StorageFormat::load::<ScanRecord>(&path),
+            Err(StorageError::LoadFailed(_))
+        ));
+    }
+}
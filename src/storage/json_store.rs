@@ -1,16 +1,26 @@
 //! JSON-based scan result storage.
 //!
 //! Stores each scan as a separate JSON file for simplicity and durability.
-//! Supports listing, querying, and exporting scan results.
+//! Supports listing, querying, and exporting scan results. Records may
+//! instead be saved as MessagePack (see [`StorageFormat`]), and either
+//! format gzipped; all of them load alike.
 
-use crate::config::Paths;
+use crate::config::{AppSettings, Paths};
 use crate::error::{StorageError, StorageResult};
-use crate::scanner::traits::{PortResult, ScanType};
-use crate::types::ScanId;
+use crate::scanner::traits::{PortResult, ProbeStats, ScanType};
+use crate::scanner::HostState;
+use crate::storage::format::gzip;
+use crate::storage::{ScanSession, StorageFormat};
+use crate::types::{ScanId, ScanIdError};
 use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
 use std::fs;
-use std::path::PathBuf;
+use std::io::Write;
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
 /// A persisted scan record.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,10 +35,61 @@ pub struct ScanRecord {
     pub target: String,
     /// Resolved IP address.
     pub ip_address: String,
+    /// Whether the address belongs to the machine that ran the scan.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub local_host: bool,
     /// Type of scan performed.
     pub scan_type: String,
+    /// Local address probes were sent from, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source_ip: Option<String>,
+    /// Network interface probes were sent from, when known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub interface: Option<String>,
+    /// Reverse DNS name of the host, with `--resolve-ptr`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// Autonomous system number of the host, with `--asn-db`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub asn: Option<u32>,
+    /// Organization owning the autonomous system, with `--asn-db`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub org: Option<String>,
+    /// MAC address of an on-link host, as `aa:bb:cc:dd:ee:ff`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mac: Option<String>,
+    /// Hardware vendor registered for the MAC address's OUI.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+    /// Addresses that answered a `--traceroute`, nearest first; hops that
+    /// stayed silent are left out. Ends with the host itself when the
+    /// trace reached it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub route: Vec<IpAddr>,
+    /// Port specification that was scanned (empty for older records).
+    #[serde(default, alias = "ports_spec")]
+    pub port_spec: String,
     /// Number of ports scanned.
     pub ports_scanned: usize,
+    /// Percentage of the ports probed when the scan was sampled; results
+    /// then cover only part of the port specification.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sampled: Option<f64>,
+    /// Whether the scan was cancelled before every port was probed; results
+    /// then cover only the ports probed by then.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Whether ports may have been missed for another reason: the scan or
+    /// host discovery fell back to a method that needs no privileges, a
+    /// privileged scan was forced without them, or many probes were lost.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub partial: bool,
+    /// SYNs sent and answered, for SYN scans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub probe_stats: Option<ProbeStats>,
+    /// Estimated percentage of probes lost in transit, for SYN scans.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub packet_loss: Option<f64>,
     /// Number of open ports found.
     pub open_ports: usize,
     /// Number of closed ports found.
@@ -37,6 +98,20 @@ pub struct ScanRecord {
     pub filtered_ports: usize,
     /// Total scan duration in milliseconds.
     pub duration_ms: u64,
+    /// Whether the host answered discovery probes; down hosts are not
+    /// port-scanned. Down hosts also get the `host_down: true` that older
+    /// records stored instead, for readers of the older schema.
+    #[serde(flatten, with = "host_state_fields")]
+    pub host_state: HostState,
+    /// Effective settings the scan ran with (absent for older records).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub config: Option<ScanSettings>,
+    /// Non-fatal problems encountered while scanning.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<String>,
+    /// Session this record belongs to, for hosts of a range scan.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<ScanId>,
     /// Individual port results.
     pub results: Vec<PortResult>,
 }
@@ -50,23 +125,79 @@ impl ScanRecord {
             completed_at: Utc::now(),
             target: target.into(),
             ip_address: ip.into(),
+            local_host: false,
             scan_type: scan_type.to_string(),
+            source_ip: None,
+            interface: None,
+            hostname: None,
+            asn: None,
+            org: None,
+            mac: None,
+            vendor: None,
+            route: Vec::new(),
+            port_spec: String::new(),
             ports_scanned: 0,
+            sampled: None,
+            cancelled: false,
+            partial: false,
+            probe_stats: None,
+            packet_loss: None,
             open_ports: 0,
             closed_ports: 0,
             filtered_ports: 0,
             duration_ms: 0,
+            host_state: HostState::Unknown,
+            config: None,
+            warnings: Vec::new(),
+            session_id: None,
             results: Vec::new(),
         }
     }
 
+    /// Create a minimal record for a host that failed host discovery.
+    pub fn host_down(
+        target: impl Into<String>,
+        ip: impl Into<String>,
+        scan_type: ScanType,
+    ) -> Self {
+        let mut record = Self::new(target, ip, scan_type);
+        record.host_state = HostState::Down;
+        record
+    }
+
+    /// Check if the host failed discovery and was not port-scanned.
+    pub fn is_down(&self) -> bool {
+        self.host_state == HostState::Down
+    }
+
     /// Finalize the scan record with results.
-    pub fn finalize(mut self, results: Vec<PortResult>, duration_ms: u64) -> Self {
+    pub fn finalize(self, results: Vec<PortResult>, duration_ms: u64) -> Self {
+        self.finalize_with_warnings(results, duration_ms, Vec::new())
+    }
+
+    /// Finalize the scan record with results and any warnings raised on the way.
+    pub fn finalize_with_warnings(
+        mut self,
+        results: Vec<PortResult>,
+        duration_ms: u64,
+        warnings: Vec<String>,
+    ) -> Self {
         self.completed_at = Utc::now();
         self.duration_ms = duration_ms;
-        self.ports_scanned = results.len();
+        self.warnings = warnings;
+        self.results = results;
+        self.update_counts();
+        self
+    }
 
-        for result in &results {
+    /// Recompute the port counters from the results.
+    fn update_counts(&mut self) {
+        self.ports_scanned = self.results.len();
+        self.open_ports = 0;
+        self.closed_ports = 0;
+        self.filtered_ports = 0;
+
+        for result in &self.results {
             match result.status {
                 crate::scanner::traits::PortStatus::Open
                 | crate::scanner::traits::PortStatus::OpenFiltered => {
@@ -80,13 +211,115 @@ impl ScanRecord {
                 }
             }
         }
+    }
 
-        self.results = results;
-        self
+    /// Combine this record with another scan of the same host, such as a UDP
+    /// scan run separately from a TCP one.
+    ///
+    /// Results are tagged with their protocol and unioned; where both scans
+    /// probed the same port, the later scan's result wins. The merged record
+    /// gets a new ID, spans the earliest start to the latest completion, and
+    /// its duration is the time spent in both scans.
+    pub fn merge(self, other: ScanRecord) -> StorageResult<ScanRecord> {
+        if self.ip_address != other.ip_address {
+            return Err(StorageError::HostMismatch(
+                self.ip_address,
+                other.ip_address,
+            ));
+        }
+
+        let (first, second) = if other.started_at < self.started_at {
+            (other, self)
+        } else {
+            (self, other)
+        };
+
+        let mut results = BTreeMap::new();
+        for record in [&first, &second] {
+            let protocol = record
+                .scan_type
+                .parse::<ScanType>()
+                .ok()
+                .map(ScanType::protocol);
+            for result in &record.results {
+                let mut result = result.clone();
+                if result.protocol.is_none() {
+                    result.protocol = protocol.map(str::to_string);
+                }
+                results.insert((result.protocol.clone(), result.port), result);
+            }
+        }
+
+        let combine = |a: &str, b: &str, separator: &str| match (a, b) {
+            (a, b) if a == b || b.is_empty() => a.to_string(),
+            ("", b) => b.to_string(),
+            (a, b) => format!("{}{}{}", a, separator, b),
+        };
+        // One port list while the scan type is shared, one per type otherwise
+        let port_separator = if first.scan_type == second.scan_type {
+            ","
+        } else {
+            " + "
+        };
+
+        let mut warnings = first.warnings;
+        for warning in &second.warnings {
+            if !warnings.contains(warning) {
+                warnings.push(warning.clone());
+            }
+        }
+
+        let mut merged = ScanRecord {
+            id: ScanId::new(),
+            started_at: first.started_at,
+            completed_at: first.completed_at.max(second.completed_at),
+            scan_type: combine(&first.scan_type, &second.scan_type, " + "),
+            port_spec: combine(&first.port_spec, &second.port_spec, port_separator),
+            duration_ms: first.duration_ms + second.duration_ms,
+            sampled: first.sampled.or(second.sampled),
+            cancelled: first.cancelled || second.cancelled,
+            partial: first.partial || second.partial,
+            local_host: first.local_host || second.local_host,
+            probe_stats: first.probe_stats.or(second.probe_stats),
+            packet_loss: first.packet_loss.or(second.packet_loss),
+            host_state: first.host_state.combine(second.host_state),
+            config: first.config.filter(|c| Some(c) == second.config.as_ref()),
+            session_id: first.session_id.filter(|&id| Some(id) == second.session_id),
+            source_ip: first.source_ip.or(second.source_ip),
+            interface: first.interface.or(second.interface),
+            hostname: first.hostname.or(second.hostname),
+            asn: first.asn.or(second.asn),
+            org: first.org.or(second.org),
+            mac: first.mac.or(second.mac),
+            vendor: first.vendor.or(second.vendor),
+            route: if second.route.is_empty() {
+                first.route
+            } else {
+                second.route
+            },
+            warnings,
+            results: results.into_values().collect(),
+            ..first
+        };
+        merged.update_counts();
+        Ok(merged)
+    }
+
+    /// Last address that answered the traceroute, when it was not the host.
+    ///
+    /// With every port filtered, this tells an upstream block (probes died
+    /// past this hop) from filtering on the host itself.
+    pub fn last_hop(&self) -> Option<IpAddr> {
+        let last = *self.route.last()?;
+        (self.ip_address.parse() != Ok(last)).then_some(last)
     }
 
     /// Get a short summary of the scan.
     pub fn summary(&self) -> String {
+        if self.is_down() {
+            return format!("{} ({}) - host down", self.target, self.ip_address);
+        }
+
         format!(
             "{} ({}) - {} open, {} closed, {} filtered [{:.2}s]",
             self.target,
@@ -99,29 +332,172 @@ impl ScanRecord {
     }
 }
 
+/// A record's host state as stored: `host_state`, plus the `host_down`
+/// flag that older records carried alone.
+mod host_state_fields {
+    use crate::scanner::HostState;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Serialize, Deserialize)]
+    struct Fields {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        host_state: Option<HostState>,
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        host_down: bool,
+    }
+
+    pub fn serialize<S: Serializer>(state: &HostState, serializer: S) -> Result<S::Ok, S::Error> {
+        Fields {
+            host_state: (!state.is_unknown()).then_some(*state),
+            host_down: *state == HostState::Down,
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<HostState, D::Error> {
+        let fields = Fields::deserialize(deserializer)?;
+        Ok(match fields.host_state {
+            Some(state) => state,
+            None if fields.host_down => HostState::Down,
+            None => HostState::Unknown,
+        })
+    }
+}
+
+/// Scan settings recorded alongside results so a scan can be reproduced.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScanSettings {
+    /// Type of scan performed.
+    pub scan_type: String,
+    /// Port specification that was scanned.
+    pub port_spec: String,
+    /// Per-probe timeout in milliseconds.
+    pub timeout_ms: u64,
+    /// Maximum concurrent probes.
+    pub concurrency: usize,
+    /// Rate limit in probes per second (0 = unlimited).
+    pub rate_limit: u32,
+    /// Minimum sustained rate in probes per second (0 = disabled).
+    #[serde(default)]
+    pub min_rate: u32,
+    /// Whether banners were grabbed from open ports.
+    pub banner_grab: bool,
+    /// Resends of an unanswered SYN, for SYN scans (absent for older records).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub syn_retries: Option<u32>,
+}
+
+impl std::fmt::Display for ScanSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "concurrency {}, timeout {}ms, rate ",
+            self.concurrency, self.timeout_ms
+        )?;
+        if self.rate_limit > 0 {
+            write!(f, "{}/s", self.rate_limit)?;
+        } else {
+            write!(f, "unlimited")?;
+        }
+        if self.min_rate > 0 {
+            write!(f, ", min rate {}/s", self.min_rate)?;
+        }
+        if let Some(retries) = self.syn_retries {
+            write!(f, ", SYN retries {}", retries)?;
+        }
+        write!(f, ", banners {}", if self.banner_grab { "on" } else { "off" })
+    }
+}
+
+/// Length of a full scan ID in its hyphenated form.
+const FULL_ID_LEN: usize = 36;
+
+/// A session as written to disk: its records are stored as ordinary scans
+/// and referenced by ID.
+#[derive(Serialize, Deserialize)]
+struct StoredSession {
+    #[serde(flatten)]
+    session: ScanSession,
+    record_ids: Vec<ScanId>,
+}
+
+/// A stored file that [`ScanStore::repair`] found unreadable.
+#[derive(Debug, Clone)]
+pub struct CorruptFile {
+    /// Path of the file.
+    pub path: PathBuf,
+    /// Why it could not be read.
+    pub reason: String,
+}
+
 /// JSON file-based scan storage.
 pub struct ScanStore {
     scans_dir: PathBuf,
+    sessions_dir: PathBuf,
+    format: StorageFormat,
+    compress: bool,
 }
 
 impl ScanStore {
     /// Create a new scan store.
+    ///
+    /// Records are saved in the `storage_format` of the settings file, and
+    /// gzipped with its `compress_scans`; as plain JSON if the settings
+    /// cannot be read.
     pub fn new() -> StorageResult<Self> {
         let paths = Paths::get();
         let scans_dir = paths.scans_dir();
+        let sessions_dir = paths.sessions_dir();
+
+        for dir in [&scans_dir, &sessions_dir] {
+            fs::create_dir_all(dir).map_err(|e| StorageError::DirectoryError(e.to_string()))?;
+        }
+
+        let settings = AppSettings::load().unwrap_or_default();
+
+        Ok(Self {
+            scans_dir,
+            sessions_dir,
+            format: settings.storage_format,
+            compress: settings.compress_scans,
+        })
+    }
 
-        fs::create_dir_all(&scans_dir)
-            .map_err(|e| StorageError::DirectoryError(e.to_string()))?;
+    /// Save records in `format` from now on. Records already stored keep
+    /// their format until saved again.
+    pub fn with_format(mut self, format: StorageFormat) -> Self {
+        self.format = format;
+        self
+    }
 
-        Ok(Self { scans_dir })
+    /// Gzip records saved from now on, or stop doing so.
+    pub fn with_compression(mut self, compress: bool) -> Self {
+        self.compress = compress;
+        self
     }
 
     /// Save a scan record.
+    ///
+    /// The file is written under a temporary name and renamed into place,
+    /// so a crash mid-save never leaves a truncated record behind.
     pub fn save(&self, record: &ScanRecord) -> StorageResult<()> {
-        let file = self.scan_file(&record.id);
-        let content = serde_json::to_string_pretty(record)?;
+        let file = record_file(&self.scans_dir, &record.id, self.format, self.compress);
+        let content = self.format.encode(record)?;
+        let content = if self.compress {
+            gzip(&content).map_err(|e| StorageError::SaveFailed(e.to_string()))?
+        } else {
+            content
+        };
+
+        write_atomic(&file, &content).map_err(|e| StorageError::SaveFailed(e.to_string()))?;
 
-        fs::write(&file, content).map_err(|e| StorageError::SaveFailed(e.to_string()))
+        // A record saved again in another format drops its old copy
+        for old in record_files(&self.scans_dir, &record.id).filter(|old| *old != file) {
+            if old.exists() {
+                fs::remove_file(&old).map_err(|e| StorageError::SaveFailed(e.to_string()))?;
+            }
+        }
+        Ok(())
     }
 
     /// Load a scan record by ID.
@@ -132,51 +508,110 @@ impl ScanStore {
             return Err(StorageError::ScanNotFound(id.to_string()));
         }
 
-        let content =
-            fs::read_to_string(&file).map_err(|e| StorageError::LoadFailed(e.to_string()))?;
+        StorageFormat::load(&file)
+    }
+
+    /// Load a scan by full ID or by a prefix of any length.
+    pub fn find(&self, id: &str) -> StorageResult<ScanRecord> {
+        self.load(&self.resolve(id)?)
+    }
 
-        serde_json::from_str(&content).map_err(|e| StorageError::LoadFailed(e.to_string()))
+    /// Resolve a full scan ID or an ID prefix to the ID of a stored scan.
+    ///
+    /// Anything shorter than a full UUID is treated as a prefix and must
+    /// match exactly one scan; a full UUID must name a stored scan.
+    pub fn resolve(&self, id: &str) -> StorageResult<ScanId> {
+        resolve_in(&self.scans_dir, id)
     }
 
     /// Find a scan by short ID prefix.
     pub fn find_by_prefix(&self, prefix: &str) -> StorageResult<ScanRecord> {
-        let matches: Vec<_> = self
-            .list_ids()?
-            .into_iter()
-            .filter(|id| id.to_string().starts_with(prefix))
-            .collect();
-
-        match matches.len() {
-            0 => Err(StorageError::ScanNotFound(prefix.to_string())),
-            1 => self.load(&matches[0]),
-            _ => Err(StorageError::LoadFailed(format!(
-                "ambiguous prefix '{}': {} matches",
-                prefix,
-                matches.len()
-            ))),
-        }
+        self.load(&match_prefix(self.list_ids()?, prefix)?)
     }
 
     /// List all scan IDs.
     pub fn list_ids(&self) -> StorageResult<Vec<ScanId>> {
-        let mut ids = Vec::new();
+        list_ids_in(&self.scans_dir)
+    }
 
-        for entry in
-            fs::read_dir(&self.scans_dir).map_err(|e| StorageError::DirectoryError(e.to_string()))?
-        {
-            let entry = entry.map_err(|e| StorageError::DirectoryError(e.to_string()))?;
-            let path = entry.path();
-
-            if path.extension().map_or(false, |ext| ext == "json") {
-                if let Some(stem) = path.file_stem() {
-                    if let Ok(id) = stem.to_string_lossy().parse::<ScanId>() {
-                        ids.push(id);
-                    }
-                }
-            }
+    /// Save a session and every record in it.
+    pub fn save_session(&self, session: &ScanSession) -> StorageResult<()> {
+        for record in &session.records {
+            self.save(record)?;
+        }
+
+        let stored = StoredSession {
+            session: ScanSession {
+                records: Vec::new(),
+                ..session.clone()
+            },
+            record_ids: session.records.iter().map(|r| r.id).collect(),
+        };
+        let content = serde_json::to_string_pretty(&stored)?;
+
+        write_atomic(&self.session_file(&session.id), &content)
+            .map_err(|e| StorageError::SaveFailed(e.to_string()))
+    }
+
+    /// Load a session by ID, with the records that are still stored.
+    pub fn load_session(&self, id: &ScanId) -> StorageResult<ScanSession> {
+        let file = self.session_file(id);
+
+        if !file.exists() {
+            return Err(StorageError::ScanNotFound(id.to_string()));
+        }
+
+        let content =
+            fs::read_to_string(&file).map_err(|e| StorageError::LoadFailed(e.to_string()))?;
+        let stored: StoredSession = serde_json::from_str(&content)
+            .map_err(|e| StorageError::LoadFailed(format!("{}: {}", file.display(), e)))?;
+
+        // Records pruned or deleted since the session was saved are skipped
+        let mut session = stored.session;
+        session.records = stored
+            .record_ids
+            .iter()
+            .filter_map(|id| self.load(id).ok())
+            .collect();
+        Ok(session)
+    }
+
+    /// Load a session by full ID or by a prefix of any length.
+    pub fn find_session(&self, id: &str) -> StorageResult<ScanSession> {
+        self.load_session(&self.resolve_session(id)?)
+    }
+
+    /// Resolve a full session ID or an ID prefix, like [`resolve`](Self::resolve).
+    pub fn resolve_session(&self, id: &str) -> StorageResult<ScanId> {
+        resolve_in(&self.sessions_dir, id)
+    }
+
+    /// List all session IDs.
+    pub fn list_session_ids(&self) -> StorageResult<Vec<ScanId>> {
+        list_ids_in(&self.sessions_dir)
+    }
+
+    /// List all sessions, most recent first.
+    pub fn list_sessions(&self) -> StorageResult<Vec<ScanSession>> {
+        let mut sessions: Vec<ScanSession> = self
+            .list_session_ids()?
+            .iter()
+            .filter_map(|id| skip_unreadable(self.load_session(id)))
+            .collect();
+
+        sessions.sort_by_key(|s| std::cmp::Reverse(s.started_at));
+        Ok(sessions)
+    }
+
+    /// Delete a session. Its records are kept.
+    pub fn delete_session(&self, id: &ScanId) -> StorageResult<()> {
+        let file = self.session_file(id);
+
+        if !file.exists() {
+            return Err(StorageError::ScanNotFound(id.to_string()));
         }
 
-        Ok(ids)
+        fs::remove_file(&file).map_err(|e| StorageError::SaveFailed(e.to_string()))
     }
 
     /// List all scan records (metadata only, results truncated).
@@ -185,13 +620,13 @@ impl ScanStore {
         let mut records = Vec::new();
 
         for id in ids {
-            if let Ok(record) = self.load(&id) {
+            if let Some(record) = skip_unreadable(self.load(&id)) {
                 records.push(record);
             }
         }
 
         // Sort by date, most recent first
-        records.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        records.sort_by_key(|r| std::cmp::Reverse(r.started_at));
 
         Ok(records)
     }
@@ -214,7 +649,8 @@ impl ScanStore {
         fs::remove_file(&file).map_err(|e| StorageError::SaveFailed(e.to_string()))
     }
 
-    /// Delete scans older than a given duration.
+    /// Delete scans older than a given duration, along with sessions
+    /// started before the cutoff.
     pub fn cleanup(&self, max_age: chrono::Duration) -> StorageResult<usize> {
         let cutoff = Utc::now() - max_age;
         let mut deleted = 0;
@@ -226,12 +662,45 @@ impl ScanStore {
             }
         }
 
+        for session in self.list_sessions()? {
+            if session.started_at < cutoff {
+                self.delete_session(&session.id)?;
+            }
+        }
+
         Ok(deleted)
     }
 
-    /// Get the file path for a scan.
+    /// Find stored files that cannot be read back: records and sessions
+    /// that fail to parse, and temporary files left by interrupted saves.
+    /// Temporary files younger than ten minutes may belong to a save still
+    /// in progress and are left alone.
+    ///
+    /// With `remove`, the files are also deleted.
+    pub fn repair(&self, remove: bool) -> StorageResult<Vec<CorruptFile>> {
+        let mut corrupt = find_corrupt::<ScanRecord>(&self.scans_dir)?;
+        corrupt.extend(find_corrupt::<StoredSession>(&self.sessions_dir)?);
+
+        if remove {
+            for file in &corrupt {
+                fs::remove_file(&file.path).map_err(|e| StorageError::SaveFailed(e.to_string()))?;
+            }
+        }
+
+        Ok(corrupt)
+    }
+
+    /// Get the file path for a scan: the stored file in whichever format
+    /// it was saved, or where a new one would go.
     fn scan_file(&self, id: &ScanId) -> PathBuf {
-        self.scans_dir.join(format!("{}.json", id))
+        record_files(&self.scans_dir, id)
+            .find(|file| file.exists())
+            .unwrap_or_else(|| record_file(&self.scans_dir, id, self.format, self.compress))
+    }
+
+    /// Get the file path for a session.
+    fn session_file(&self, id: &ScanId) -> PathBuf {
+        self.sessions_dir.join(format!("{}.json", id))
     }
 
     /// Get storage statistics.
@@ -244,12 +713,9 @@ impl ScanStore {
             .map(|m| m.len())
             .sum();
 
-        Ok(StorageStats {
-            scan_count: records.len(),
-            total_size_bytes: total_size,
-            oldest_scan: records.last().map(|r| r.started_at),
-            newest_scan: records.first().map(|r| r.started_at),
-        })
+        let mut stats = StorageStats::of_records(&records, total_size);
+        stats.session_count = self.list_session_ids()?.len();
+        Ok(stats)
     }
 }
 
@@ -259,17 +725,190 @@ impl Default for ScanStore {
     }
 }
 
+/// Path of the file storing `id` in `format` under `dir`.
+fn record_file(dir: &Path, id: &ScanId, format: StorageFormat, compressed: bool) -> PathBuf {
+    dir.join(format.file_name(id, compressed))
+}
+
+/// Every path `id` could be stored at under `dir`.
+fn record_files<'a>(dir: &'a Path, id: &'a ScanId) -> impl Iterator<Item = PathBuf> + 'a {
+    StorageFormat::ALL.into_iter().flat_map(move |format| {
+        [false, true].map(|compressed| record_file(dir, id, format, compressed))
+    })
+}
+
+/// IDs of the stored files in `dir`, such as `<id>.json` or
+/// `<id>.msgpack.gz`.
+fn list_ids_in(dir: &Path) -> StorageResult<Vec<ScanId>> {
+    let mut ids = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(|e| StorageError::DirectoryError(e.to_string()))? {
+        let entry = entry.map_err(|e| StorageError::DirectoryError(e.to_string()))?;
+        let path = entry.path();
+
+        if StorageFormat::from_path(&path).is_some() {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if let Some(Ok(id)) = name.split('.').next().map(str::parse::<ScanId>) {
+                ids.push(id);
+            }
+        }
+    }
+
+    // Both formats of one record exist only if a save was interrupted
+    let mut seen = HashSet::new();
+    ids.retain(|id| seen.insert(*id));
+    Ok(ids)
+}
+
+/// Write `content` to `path` through a temporary file in the same directory,
+/// so readers see either the old file or the complete new one.
+///
+/// The temporary file is named for this process and a random suffix, so
+/// concurrent saves of the same path never write to the same file.
+fn write_atomic(path: &Path, content: impl AsRef<[u8]>) -> std::io::Result<()> {
+    let tmp = path.with_extension(format!(
+        "{}.{:08x}.tmp",
+        std::process::id(),
+        rand::random::<u32>()
+    ));
+    let written = fs::File::create(&tmp).and_then(|mut file| {
+        file.write_all(content.as_ref())?;
+        file.sync_all()
+    });
+    match written.and_then(|()| fs::rename(&tmp, path)) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp);
+            Err(e)
+        }
+    }
+}
+
+/// Age after which a `.tmp` file cannot belong to a save still in progress.
+const STALE_TEMP_AGE: Duration = Duration::from_secs(600);
+
+/// List the stored files in `dir` that do not load as a `T`, and any `.tmp`
+/// files older than [`STALE_TEMP_AGE`].
+fn find_corrupt<T: DeserializeOwned>(dir: &Path) -> StorageResult<Vec<CorruptFile>> {
+    let mut corrupt = Vec::new();
+
+    for entry in fs::read_dir(dir).map_err(|e| StorageError::DirectoryError(e.to_string()))? {
+        let path = entry
+            .map_err(|e| StorageError::DirectoryError(e.to_string()))?
+            .path();
+        let reason = if path.extension().is_some_and(|ext| ext == "tmp") {
+            // A recent one may be a save another process is still writing
+            let age = fs::metadata(&path)
+                .and_then(|meta| meta.modified())
+                .ok()
+                .and_then(|modified| modified.elapsed().ok());
+            if age.is_none_or(|age| age < STALE_TEMP_AGE) {
+                continue;
+            }
+            "left over from an interrupted save".to_string()
+        } else if StorageFormat::from_path(&path).is_some() {
+            match StorageFormat::read::<T>(&path) {
+                Ok(_) => continue,
+                Err(e) => e.to_string(),
+            }
+        } else {
+            continue;
+        };
+        corrupt.push(CorruptFile { path, reason });
+    }
+
+    corrupt.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(corrupt)
+}
+
+/// Pass a loaded item through, logging and dropping it if it was unreadable.
+fn skip_unreadable<T>(loaded: StorageResult<T>) -> Option<T> {
+    match loaded {
+        Ok(item) => Some(item),
+        Err(e) => {
+            tracing::warn!("skipping {} (`scuttle history repair` removes it)", e);
+            None
+        }
+    }
+}
+
+/// Resolve a full ID or prefix against the `<id>.json` files in `dir`.
+fn resolve_in(dir: &Path, id: &str) -> StorageResult<ScanId> {
+    let id = id.trim().to_ascii_lowercase();
+
+    if id.len() < FULL_ID_LEN {
+        if id.is_empty() || !id.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
+            return Err(ScanIdError::InvalidFormat(id).into());
+        }
+        return match_prefix(list_ids_in(dir)?, &id);
+    }
+
+    let id: ScanId = id.parse()?;
+    if record_files(dir, &id).any(|file| file.exists()) {
+        Ok(id)
+    } else {
+        Err(StorageError::ScanNotFound(id.to_string()))
+    }
+}
+
+/// The one ID in `ids` starting with `prefix`.
+fn match_prefix(ids: Vec<ScanId>, prefix: &str) -> StorageResult<ScanId> {
+    let matches: Vec<_> = ids
+        .into_iter()
+        .filter(|id| id.to_string().starts_with(prefix))
+        .collect();
+
+    match matches.len() {
+        0 => Err(StorageError::ScanNotFound(prefix.to_string())),
+        1 => Ok(matches[0]),
+        _ => Err(StorageError::LoadFailed(format!(
+            "ambiguous prefix '{}': {} matches",
+            prefix,
+            matches.len()
+        ))),
+    }
+}
+
 /// Storage statistics.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize)]
 pub struct StorageStats {
     /// Number of stored scans.
     pub scan_count: usize,
+    /// Number of stored range scan sessions.
+    pub session_count: usize,
     /// Total size in bytes.
     pub total_size_bytes: u64,
     /// Oldest scan timestamp.
     pub oldest_scan: Option<DateTime<Utc>>,
     /// Newest scan timestamp.
     pub newest_scan: Option<DateTime<Utc>>,
+    /// Number of distinct IP addresses scanned.
+    pub host_count: usize,
+    /// Port results across every stored scan; closed ports count only
+    /// where they were kept, as with `--show-closed`.
+    pub total_ports_scanned: u64,
+    /// Open ports found across every stored scan.
+    pub total_open_ports: u64,
+}
+
+impl StorageStats {
+    /// Statistics of `records`, sorted most recent first, stored in
+    /// `total_size_bytes`. Sessions are not counted.
+    pub fn of_records(records: &[ScanRecord], total_size_bytes: u64) -> Self {
+        let hosts: HashSet<&str> = records.iter().map(|r| r.ip_address.as_str()).collect();
+
+        Self {
+            scan_count: records.len(),
+            session_count: 0,
+            total_size_bytes,
+            oldest_scan: records.last().map(|r| r.started_at),
+            newest_scan: records.first().map(|r| r.started_at),
+            host_count: hosts.len(),
+            total_ports_scanned: records.iter().map(|r| r.ports_scanned as u64).sum(),
+            total_open_ports: records.iter().map(|r| r.open_ports as u64).sum(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -278,6 +917,57 @@ mod tests {
     use crate::scanner::traits::PortStatus;
     use crate::types::Port;
 
+    #[test]
+    fn test_scan_settings_round_trip() {
+        let mut record = ScanRecord::new("example.com", "93.184.216.34", ScanType::Connect);
+        record.config = Some(ScanSettings {
+            scan_type: record.scan_type.clone(),
+            port_spec: "1-1000".to_string(),
+            timeout_ms: 3000,
+            concurrency: 500,
+            rate_limit: 0,
+            min_rate: 0,
+            banner_grab: true,
+            syn_retries: None,
+        });
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains("\"concurrency\":500"));
+        let parsed: ScanRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.config, record.config);
+
+        let settings = parsed.config.unwrap().to_string();
+        assert_eq!(
+            settings,
+            "concurrency 500, timeout 3000ms, rate unlimited, banners on"
+        );
+    }
+
+    #[test]
+    fn test_scan_settings_syn_retries() {
+        let mut settings = ScanSettings {
+            scan_type: "syn".to_string(),
+            port_spec: "1-1000".to_string(),
+            timeout_ms: 3000,
+            concurrency: 500,
+            rate_limit: 0,
+            min_rate: 0,
+            banner_grab: false,
+            syn_retries: Some(2),
+        };
+        assert_eq!(
+            settings.to_string(),
+            "concurrency 500, timeout 3000ms, rate unlimited, SYN retries 2, banners off"
+        );
+        let parsed: ScanSettings =
+            serde_json::from_str(&serde_json::to_string(&settings).unwrap()).unwrap();
+        assert_eq!(parsed.syn_retries, Some(2));
+
+        settings.syn_retries = None;
+        let json = serde_json::to_string(&settings).unwrap();
+        assert!(!json.contains("syn_retries"));
+    }
+
     #[test]
     fn test_scan_record_creation() {
         let record = ScanRecord::new("192.168.1.1", "192.168.1.1", ScanType::Connect);
@@ -300,11 +990,323 @@ mod tests {
         assert_eq!(finalized.closed_ports, 1);
     }
 
+    #[test]
+    fn test_scan_record_merge() {
+        let result = |port, status| PortResult::new(Port::new(port).unwrap(), status, "svc");
+        let tcp = ScanRecord::new("host", "10.0.0.1", ScanType::Connect).finalize(
+            vec![result(53, PortStatus::Closed), result(80, PortStatus::Open)],
+            1000,
+        );
+        let mut udp = ScanRecord::new("host", "10.0.0.1", ScanType::Udp)
+            .finalize(vec![result(53, PortStatus::Open)], 500);
+        // The UDP scan ran an hour before the TCP one
+        udp.started_at = tcp.started_at - chrono::Duration::hours(1);
+        udp.completed_at = tcp.completed_at - chrono::Duration::hours(1);
+
+        let merged = udp.merge(tcp.clone()).unwrap();
+        assert_eq!(
+            merged.started_at,
+            tcp.started_at - chrono::Duration::hours(1)
+        );
+        assert_eq!(merged.completed_at, tcp.completed_at);
+        assert_eq!(merged.scan_type, "UDP + TCP Connect");
+        assert_eq!(merged.duration_ms, 1500);
+        assert_eq!(merged.ports_scanned, 3);
+        assert_eq!(merged.open_ports, 2);
+        assert_eq!(merged.closed_ports, 1);
+        let tagged: Vec<_> = merged
+            .results
+            .iter()
+            .map(|r| (r.port.as_u16(), r.protocol.as_deref().unwrap()))
+            .collect();
+        assert_eq!(tagged, vec![(53, "tcp"), (80, "tcp"), (53, "udp")]);
+
+        let other = ScanRecord::new("other", "10.0.0.2", ScanType::Connect);
+        assert!(matches!(
+            tcp.merge(other),
+            Err(StorageError::HostMismatch(_, _))
+        ));
+    }
+
     #[test]
     fn test_scan_record_serialization() {
         let record = ScanRecord::new("test", "127.0.0.1", ScanType::Connect);
         let json = serde_json::to_string(&record).unwrap();
         let parsed: ScanRecord = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.target, record.target);
+        assert!(!json.contains("host_state"));
+        assert!(!json.contains("warnings"));
+    }
+
+    #[test]
+    fn test_scan_record_warnings() {
+        let warning = "concurrency 5000 was clamped to 1000".to_string();
+        let record = ScanRecord::new("test", "127.0.0.1", ScanType::Connect)
+            .finalize_with_warnings(Vec::new(), 0, vec![warning.clone()]);
+        let json = serde_json::to_string(&record).unwrap();
+        let parsed: ScanRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.warnings, vec![warning]);
+    }
+
+    #[test]
+    fn test_stored_session_keeps_record_ids() {
+        let mut record = ScanRecord::new("10.0.0.0/30", "10.0.0.1", ScanType::Connect);
+        let session = ScanSession::new("10.0.0.0/30", ScanType::Connect);
+        record.session_id = Some(session.id);
+        let stored = StoredSession {
+            record_ids: vec![record.id],
+            session: session.finalize(Vec::new()),
+        };
+
+        let json = serde_json::to_string(&stored).unwrap();
+        assert!(json.contains("\"target\":\"10.0.0.0/30\""));
+        let parsed: StoredSession = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.session.id, stored.session.id);
+        assert_eq!(parsed.record_ids, vec![record.id]);
+
+        let json = serde_json::to_string(&record).unwrap();
+        let parsed: ScanRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.session_id, Some(stored.session.id));
+    }
+
+    #[test]
+    fn test_resolve_in() {
+        let dir = tempfile::tempdir().unwrap();
+        let ids: Vec<ScanId> = [
+            "3f2a9c1e-0000-4000-8000-000000000001",
+            "3f2a9c1e-5000-4000-8000-000000000002",
+            "8ac1304b-0000-4000-8000-000000000003",
+        ]
+        .iter()
+        .map(|id| id.parse().unwrap())
+        .collect();
+        for id in &ids {
+            fs::write(dir.path().join(format!("{}.json", id)), "{}").unwrap();
+        }
+
+        assert_eq!(resolve_in(dir.path(), "8ac1").unwrap(), ids[2]);
+        assert_eq!(resolve_in(dir.path(), "3F2A9C1E-5").unwrap(), ids[1]);
+        assert_eq!(resolve_in(dir.path(), &ids[0].to_string()).unwrap(), ids[0]);
+
+        let err = resolve_in(dir.path(), "3f2a9c1e").unwrap_err();
+        assert!(err.to_string().contains("ambiguous prefix"));
+        assert!(matches!(
+            resolve_in(dir.path(), "ffff"),
+            Err(StorageError::ScanNotFound(_))
+        ));
+        assert!(matches!(
+            resolve_in(dir.path(), "8ac1304b-0000-4000-8000-000000000004"),
+            Err(StorageError::ScanNotFound(_))
+        ));
+        assert!(matches!(
+            resolve_in(dir.path(), "../etc"),
+            Err(StorageError::InvalidScanId(_))
+        ));
+    }
+
+    #[test]
+    fn test_find_corrupt() {
+        let dir = tempfile::tempdir().unwrap();
+        let record = ScanRecord::new("127.0.0.1", "127.0.0.1", ScanType::Connect);
+        let good = dir.path().join(format!("{}.json", record.id));
+        write_atomic(&good, serde_json::to_string(&record).unwrap()).unwrap();
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let truncated = dir.path().join("8ac1304b-0000-4000-8000-000000000003.json");
+        fs::write(&truncated, "{\"id\": ").unwrap();
+        let leftover = dir.path().join("8ac1304b-0000-4000-8000-000000000004.tmp");
+        fs::File::create(&leftover)
+            .unwrap()
+            .set_modified(std::time::SystemTime::now() - 2 * STALE_TEMP_AGE)
+            .unwrap();
+        // Possibly a save still in progress
+        let in_flight = dir.path().join("8ac1304b-0000-4000-8000-000000000005.tmp");
+        fs::write(&in_flight, "").unwrap();
+        fs::write(dir.path().join("notes.txt"), "not a record").unwrap();
+
+        let corrupt = find_corrupt::<ScanRecord>(dir.path()).unwrap();
+        let paths: Vec<_> = corrupt.iter().map(|file| &file.path).collect();
+        assert_eq!(paths, vec![&truncated, &leftover]);
+        assert!(corrupt[1].reason.contains("interrupted save"));
+    }
+
+    #[test]
+    fn test_concurrent_atomic_writes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("8ac1304b-0000-4000-8000-000000000006.json");
+        let contents: Vec<String> = (0..8).map(|i| format!("{{\"save\": {}}}", i)).collect();
+
+        std::thread::scope(|scope| {
+            for content in &contents {
+                let path = &path;
+                scope.spawn(move || write_atomic(path, content).unwrap());
+            }
+        });
+
+        assert!(contents.contains(&fs::read_to_string(&path).unwrap()));
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_mixed_storage_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut ids = Vec::new();
+        for (format, compressed) in [
+            (StorageFormat::Json, false),
+            (StorageFormat::Json, true),
+            (StorageFormat::Msgpack, true),
+        ] {
+            let record = ScanRecord::new("127.0.0.1", "127.0.0.1", ScanType::Connect);
+            let content = format.encode(&record).unwrap();
+            let content = if compressed {
+                gzip(&content).unwrap()
+            } else {
+                content
+            };
+            let file = record_file(dir.path(), &record.id, format, compressed);
+            write_atomic(&file, content).unwrap();
+            ids.push(record.id);
+        }
+
+        let mut listed = list_ids_in(dir.path()).unwrap();
+        listed.sort_by_key(|id| id.to_string());
+        ids.sort_by_key(|id| id.to_string());
+        assert_eq!(listed, ids);
+        for id in &ids {
+            assert_eq!(resolve_in(dir.path(), &id.to_string()).unwrap(), *id);
+        }
+        assert!(find_corrupt::<ScanRecord>(dir.path()).unwrap().is_empty());
+
+        // A file that claims gzip but is not
+        let name = "8ac1304b-0000-4000-8000-000000000003.json.gz";
+        let bogus = dir.path().join(name);
+        fs::write(&bogus, [0x1f, 0x8b, 0x00]).unwrap();
+        let corrupt = find_corrupt::<ScanRecord>(dir.path()).unwrap();
+        assert_eq!(corrupt.len(), 1);
+        assert_eq!(corrupt[0].path, bogus);
+    }
+
+    #[test]
+    fn test_storage_stats() {
+        let scan = |ip: &str, open: u16| {
+            let results = (1..=4)
+                .map(|port| {
+                    let status = if port <= open {
+                        PortStatus::Open
+                    } else {
+                        PortStatus::Closed
+                    };
+                    PortResult::new(Port::new(port).unwrap(), status, "unknown")
+                })
+                .collect();
+            ScanRecord::new(ip, ip, ScanType::Connect).finalize(results, 0)
+        };
+        let records = vec![
+            scan("10.0.0.1", 2),
+            scan("10.0.0.2", 0),
+            scan("10.0.0.1", 1),
+        ];
+
+        let stats = StorageStats::of_records(&records, 4096);
+        assert_eq!(stats.scan_count, 3);
+        assert_eq!(stats.host_count, 2);
+        assert_eq!(stats.total_ports_scanned, 12);
+        assert_eq!(stats.total_open_ports, 3);
+        assert_eq!(stats.newest_scan, Some(records[0].started_at));
+        assert_eq!(stats.oldest_scan, Some(records[2].started_at));
+
+        let empty = StorageStats::of_records(&[], 0);
+        assert_eq!(empty.scan_count, 0);
+        assert_eq!(empty.oldest_scan, None);
+    }
+
+    #[test]
+    fn test_host_down_record() {
+        let record = ScanRecord::host_down("10.0.0.0/24", "10.0.0.7", ScanType::Connect);
+        assert!(record.is_down());
+        assert!(record.results.is_empty());
+        assert!(record.summary().ends_with("host down"));
+
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""host_state":"down""#));
+        assert!(json.contains(r#""host_down":true"#));
+        let parsed: ScanRecord = serde_json::from_str(&json).unwrap();
+        assert!(parsed.is_down());
+
+        let packed = StorageFormat::Msgpack.encode(&record).unwrap();
+        let parsed: ScanRecord = StorageFormat::Msgpack.decode(&packed).unwrap();
+        assert!(parsed.is_down());
+    }
+
+    #[test]
+    fn test_legacy_host_down_record() {
+        let mut value =
+            serde_json::to_value(ScanRecord::new("test", "127.0.0.1", ScanType::Connect)).unwrap();
+        value["host_down"] = serde_json::Value::Bool(true);
+        let parsed: ScanRecord = serde_json::from_value(value.clone()).unwrap();
+        assert_eq!(parsed.host_state, HostState::Down);
+
+        value["host_down"] = serde_json::Value::Bool(false);
+        let parsed: ScanRecord = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.host_state, HostState::Unknown);
+
+        // Hosts that were up or never probed carry no host_down
+        let mut record = ScanRecord::new("test", "127.0.0.1", ScanType::Connect);
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(!json.contains("host_state"));
+        assert!(!json.contains("host_down"));
+        record.host_state = HostState::Up;
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""host_state":"up""#));
+        assert!(!json.contains("host_down"));
+    }
+
+    #[test]
+    fn test_merge_host_state() {
+        use HostState::{Down, Unknown, Up};
+
+        let record = |state| {
+            let mut record = ScanRecord::new("test", "10.0.0.1", ScanType::Connect);
+            record.host_state = state;
+            record
+        };
+        let merged = |a, b| record(a).merge(record(b)).unwrap().host_state;
+        assert_eq!(merged(Down, Up), Up);
+        assert_eq!(merged(Down, Down), Down);
+        assert_eq!(merged(Down, Unknown), Unknown);
+    }
+
+    #[test]
+    fn test_last_hop() {
+        let mut record = ScanRecord::new("test", "10.0.0.1", ScanType::Syn);
+        assert_eq!(record.last_hop(), None);
+        assert!(!serde_json::to_string(&record).unwrap().contains("route"));
+
+        let gateway: IpAddr = "192.168.1.1".parse().unwrap();
+        record.route = vec![gateway, "10.0.0.1".parse().unwrap()];
+        assert_eq!(record.last_hop(), None);
+
+        record.route.pop();
+        assert_eq!(record.last_hop(), Some(gateway));
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains(r#""route":["192.168.1.1"]"#));
+    }
+
+    #[test]
+    fn test_scan_record_without_port_spec() {
+        let mut value = serde_json::to_value(ScanRecord::new(
+            "test",
+            "127.0.0.1",
+            ScanType::Connect,
+        ))
+        .unwrap();
+        value.as_object_mut().unwrap().remove("port_spec");
+
+        let parsed: ScanRecord = serde_json::from_value(value.clone()).unwrap();
+        assert!(parsed.port_spec.is_empty());
+
+        value["ports_spec"] = "22,80".into();
+        let parsed: ScanRecord = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.port_spec, "22,80");
     }
 }
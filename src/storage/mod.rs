@@ -1,7 +1,14 @@
 //! Scan result persistence.
 //!
-//! Provides JSON-based storage for scan results with query capabilities.
+//! Provides JSON-based storage for scan results with query capabilities,
+//! and an optional MessagePack encoding for compact archives.
 
+mod diff;
+mod format;
 mod json_store;
+mod session;
 
-pub use json_store::{ScanRecord, ScanStore};
+pub use diff::{BannerChange, HostChange, HostDiff, ScanDiff, SessionDiff, StatusChange};
+pub use json_store::{CorruptFile, ScanRecord, ScanSettings, ScanStore, StorageStats};
+pub use format::StorageFormat;
+pub use session::ScanSession;
@@ -0,0 +1,136 @@
+//! Multi-host scan sessions.
+//!
+//! A range scan produces one [`ScanRecord`] per host. A [`ScanSession`]
+//! groups those records under the original target so the scan can be
+//! saved, listed, and exported as one unit. Each record still stands on its
+//! own and carries the session's ID in [`ScanRecord::session_id`].
+
+use crate::scanner::traits::ScanType;
+use crate::storage::ScanRecord;
+use crate::types::ScanId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// The records of one scan across several hosts, with totals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanSession {
+    /// Unique identifier for this session.
+    pub id: ScanId,
+    /// When the session was started.
+    pub started_at: DateTime<Utc>,
+    /// When the last host finished.
+    pub completed_at: DateTime<Utc>,
+    /// Target specification as given (usually a CIDR or range).
+    pub target: String,
+    /// Type of scan performed.
+    pub scan_type: String,
+    /// Port specification that was scanned.
+    #[serde(default)]
+    pub port_spec: String,
+    /// Number of hosts that were port-scanned.
+    pub hosts_up: usize,
+    /// Number of hosts that failed host discovery.
+    pub hosts_down: usize,
+    /// Ports scanned, summed over all hosts.
+    pub ports_scanned: usize,
+    /// Open ports, summed over all hosts.
+    pub open_ports: usize,
+    /// Total session duration in milliseconds.
+    pub duration_ms: u64,
+    /// Per-host records, in scan order.
+    #[serde(default)]
+    pub records: Vec<ScanRecord>,
+}
+
+impl ScanSession {
+    /// Create a new, empty session.
+    pub fn new(target: impl Into<String>, scan_type: ScanType) -> Self {
+        Self {
+            id: ScanId::new(),
+            started_at: Utc::now(),
+            completed_at: Utc::now(),
+            target: target.into(),
+            scan_type: scan_type.to_string(),
+            port_spec: String::new(),
+            hosts_up: 0,
+            hosts_down: 0,
+            ports_scanned: 0,
+            open_ports: 0,
+            duration_ms: 0,
+            records: Vec::new(),
+        }
+    }
+
+    /// Finalize the session with its host records and compute the totals.
+    pub fn finalize(mut self, records: Vec<ScanRecord>) -> Self {
+        self.completed_at = Utc::now();
+        self.duration_ms = (self.completed_at - self.started_at)
+            .num_milliseconds()
+            .max(0) as u64;
+        self.records = records;
+        self.update_totals();
+        self
+    }
+
+    /// Recompute the totals from the records, e.g. after filtering them.
+    pub fn update_totals(&mut self) {
+        self.hosts_down = self.records.iter().filter(|r| r.is_down()).count();
+        self.hosts_up = self.records.len() - self.hosts_down;
+        self.ports_scanned = self.records.iter().map(|r| r.ports_scanned).sum();
+        self.open_ports = self.records.iter().map(|r| r.open_ports).sum();
+    }
+
+    /// Number of hosts in the session, up or down.
+    pub fn hosts(&self) -> usize {
+        self.hosts_up + self.hosts_down
+    }
+
+    /// Get a short summary of the session.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} - {} hosts ({} up, {} down), {} open ports [{:.2}s]",
+            self.target,
+            self.hosts(),
+            self.hosts_up,
+            self.hosts_down,
+            self.open_ports,
+            self.duration_ms as f64 / 1000.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::scanner::traits::{PortResult, PortStatus};
+    use crate::types::Port;
+
+    #[test]
+    fn test_session_totals() {
+        let result = |port, status| PortResult::new(Port::new(port).unwrap(), status, "svc");
+        let host = |ip: &str, results| {
+            ScanRecord::new("10.0.0.0/30", ip, ScanType::Connect).finalize(results, 0)
+        };
+        let records = vec![
+            host(
+                "10.0.0.1",
+                vec![
+                    result(22, PortStatus::Open),
+                    result(80, PortStatus::Filtered),
+                ],
+            ),
+            host("10.0.0.2", vec![result(443, PortStatus::Open)]),
+            ScanRecord::host_down("10.0.0.0/30", "10.0.0.3", ScanType::Connect),
+        ];
+
+        let session = ScanSession::new("10.0.0.0/30", ScanType::Connect).finalize(records);
+        assert_eq!(session.hosts(), 3);
+        assert_eq!(session.hosts_up, 2);
+        assert_eq!(session.hosts_down, 1);
+        assert_eq!(session.ports_scanned, 3);
+        assert_eq!(session.open_ports, 2);
+        assert!(session
+            .summary()
+            .starts_with("10.0.0.0/30 - 3 hosts (2 up, 1 down)"));
+    }
+}
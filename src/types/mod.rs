@@ -7,6 +7,6 @@ mod port;
 mod scan_id;
 mod target;
 
-pub use port::{Port, PortError, PortRange, PortSpec};
+pub use port::{Port, PortError, PortRange, PortRangeIter, PortSpec};
 pub use scan_id::{ScanId, ScanIdError};
-pub use target::{ScanTarget, TargetError, TargetSpec};
+pub use target::{host_port, shuffle_targets, ResolveOptions, ScanTarget, TargetError, TargetSpec};
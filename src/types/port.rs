@@ -24,7 +24,7 @@ impl Port {
     /// Create a new Port from a u16, returning None if invalid.
     #[inline]
     pub const fn new(port: u16) -> Option<Self> {
-        if port >= Self::MIN && port <= Self::MAX {
+        if port >= Self::MIN {
             Some(Self(port))
         } else {
             None
@@ -93,6 +93,20 @@ pub enum PortError {
 }
 
 /// A range of ports (inclusive).
+///
+/// Ranges iterate over their ports in order:
+///
+/// ```
+/// use scuttle::types::{Port, PortRange};
+///
+/// let range: PortRange = "8000-8002".parse().unwrap();
+/// let mut seen = Vec::new();
+/// for port in range {
+///     seen.push(port.as_u16());
+/// }
+/// assert_eq!(seen, [8000, 8001, 8002]);
+/// assert_eq!(range.into_iter().last(), Port::new(8002));
+/// ```
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PortRange {
     start: Port,
@@ -127,11 +141,89 @@ impl PortRange {
         false // A valid PortRange always has at least one port
     }
 
+    /// Get the first port in the range.
+    pub const fn start(&self) -> Port {
+        self.start
+    }
+
+    /// Get the last port in the range (inclusive).
+    pub const fn end(&self) -> Port {
+        self.end
+    }
+
+    /// Check if a port falls within this range.
+    pub const fn contains(&self, port: Port) -> bool {
+        port.0 >= self.start.0 && port.0 <= self.end.0
+    }
+
     /// Iterate over all ports in this range.
-    pub fn iter(&self) -> impl Iterator<Item = Port> {
-        let start = self.start.0;
-        let end = self.end.0;
-        (start..=end).map(Port::new_unchecked)
+    pub fn iter(&self) -> PortRangeIter {
+        PortRangeIter(self.start.0..=self.end.0)
+    }
+}
+
+impl IntoIterator for PortRange {
+    type Item = Port;
+    type IntoIter = PortRangeIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for &PortRange {
+    type Item = Port;
+    type IntoIter = PortRangeIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the ports of a [`PortRange`], in ascending order.
+#[derive(Debug, Clone)]
+pub struct PortRangeIter(std::ops::RangeInclusive<u16>);
+
+impl Iterator for PortRangeIter {
+    type Item = Port;
+
+    fn next(&mut self) -> Option<Port> {
+        self.0.next().map(Port::new_unchecked)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for PortRangeIter {
+    fn next_back(&mut self) -> Option<Port> {
+        self.0.next_back().map(Port::new_unchecked)
+    }
+}
+
+impl ExactSizeIterator for PortRangeIter {}
+
+impl std::iter::FusedIterator for PortRangeIter {}
+
+impl FromStr for PortRange {
+    type Err = PortError;
+
+    /// Parse `start-end`, or a single port as a one-port range.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parse = |part: &str| {
+            let part = part.trim();
+            let port: u16 = part
+                .parse()
+                .map_err(|_| PortError::InvalidFormat(part.to_string()))?;
+            Port::new(port).ok_or(PortError::OutOfRange(port))
+        };
+
+        match s.trim().split_once('-') {
+            Some((start, end)) if !end.contains('-') => Self::new(parse(start)?, parse(end)?),
+            Some(_) => Err(PortError::InvalidFormat(s.trim().to_string())),
+            None => parse(s).map(Self::single),
+        }
     }
 }
 
@@ -163,7 +255,18 @@ impl PortSpec {
         Self { ranges: Vec::new() }
     }
 
+    /// Build a normalized specification from individual ports.
+    ///
+    /// Consecutive ports are coalesced, so ports 1 through 100 become the
+    /// single range `1-100`.
+    pub fn from_ports(ports: &[Port]) -> Self {
+        ports.iter().copied().collect()
+    }
+
     /// Add a port range to the specification.
+    ///
+    /// Ranges are stored as given; call [`normalize`](Self::normalize) to
+    /// merge overlaps.
     pub fn add_range(&mut self, range: PortRange) {
         self.ranges.push(range);
     }
@@ -173,17 +276,39 @@ impl PortSpec {
         self.ranges.push(PortRange::single(port));
     }
 
+    /// Add all ranges from another specification.
+    pub fn merge(&mut self, other: &PortSpec) {
+        self.ranges.extend_from_slice(&other.ranges);
+        self.normalize();
+    }
+
+    /// Merge overlapping and adjacent ranges into a minimal, sorted set.
+    pub fn normalize(&mut self) {
+        self.ranges = coalesce(&self.ranges);
+    }
+
     /// Get all ports as a sorted, deduplicated vector.
     pub fn to_ports(&self) -> Vec<Port> {
-        let mut ports: Vec<Port> = self.ranges.iter().flat_map(|r| r.iter()).collect();
-        ports.sort_unstable();
-        ports.dedup();
-        ports
+        coalesce(&self.ranges).into_iter().flatten().collect()
     }
 
     /// Get the total number of unique ports.
+    ///
+    /// Sums range lengths directly; only a specification with overlapping
+    /// ranges (built through `add_range`/`add_port`) is coalesced first.
     pub fn count(&self) -> usize {
-        self.to_ports().len()
+        if self.is_normalized() {
+            self.ranges.iter().map(PortRange::len).sum()
+        } else {
+            coalesce(&self.ranges).iter().map(PortRange::len).sum()
+        }
+    }
+
+    /// Whether the ranges are sorted and neither overlap nor touch.
+    fn is_normalized(&self) -> bool {
+        self.ranges
+            .windows(2)
+            .all(|w| u32::from(w[0].end.0) + 1 < u32::from(w[1].start.0))
     }
 
     /// Check if empty.
@@ -191,6 +316,16 @@ impl PortSpec {
         self.ranges.is_empty()
     }
 
+    /// Check if a port is covered by any range in the specification.
+    pub fn contains(&self, port: Port) -> bool {
+        self.ranges.iter().any(|r| r.contains(port))
+    }
+
+    /// Get the ranges in this specification.
+    pub fn ranges(&self) -> &[PortRange] {
+        &self.ranges
+    }
+
     /// Common scan profiles.
     pub fn top_100() -> Self {
         // Top 100 most common ports
@@ -203,13 +338,7 @@ impl PortSpec {
             6646, 7070, 8000, 8008, 8009, 8080, 8081, 8443, 8888, 9100, 9999, 10000, 32768, 49152,
             49153, 49154, 49155, 49156, 49157,
         ];
-        let mut spec = Self::new();
-        for &p in &ports {
-            if let Some(port) = Port::new(p) {
-                spec.add_port(port);
-            }
-        }
-        spec
+        ports.into_iter().filter_map(Port::new).collect()
     }
 
     /// Full port range (1-65535).
@@ -236,43 +365,76 @@ impl FromStr for PortSpec {
         let mut spec = Self::new();
 
         for part in s.split(',') {
-            let part = part.trim();
-            if part.contains('-') {
-                let bounds: Vec<&str> = part.split('-').collect();
-                if bounds.len() != 2 {
-                    return Err(PortError::InvalidFormat(part.to_string()));
-                }
-
-                let start: u16 = bounds[0]
-                    .trim()
-                    .parse()
-                    .map_err(|_| PortError::InvalidFormat(bounds[0].to_string()))?;
-                let end: u16 = bounds[1]
-                    .trim()
-                    .parse()
-                    .map_err(|_| PortError::InvalidFormat(bounds[1].to_string()))?;
-
-                let start_port = Port::new(start).ok_or(PortError::OutOfRange(start))?;
-                let end_port = Port::new(end).ok_or(PortError::OutOfRange(end))?;
-                let range = PortRange::new(start_port, end_port)?;
-                spec.add_range(range);
-            } else {
-                let port: u16 = part
-                    .parse()
-                    .map_err(|_| PortError::InvalidFormat(part.to_string()))?;
-                let port = Port::new(port).ok_or(PortError::OutOfRange(port))?;
-                spec.add_port(port);
-            }
+            spec.add_range(part.parse()?);
         }
 
         if spec.is_empty() {
             return Err(PortError::Empty);
         }
 
+        spec.normalize();
         Ok(spec)
     }
 }
 
+impl IntoIterator for &PortSpec {
+    type Item = Port;
+    type IntoIter = std::vec::IntoIter<Port>;
+
+    /// Iterate over all ports, sorted and deduplicated.
+    fn into_iter(self) -> Self::IntoIter {
+        self.to_ports().into_iter()
+    }
+}
+
+impl Extend<Port> for PortSpec {
+    fn extend<I: IntoIterator<Item = Port>>(&mut self, iter: I) {
+        for port in iter {
+            self.add_port(port);
+        }
+    }
+}
+
+impl FromIterator<Port> for PortSpec {
+    fn from_iter<I: IntoIterator<Item = Port>>(iter: I) -> Self {
+        let mut spec = Self::new();
+        spec.extend(iter);
+        spec.normalize();
+        spec
+    }
+}
+
+impl From<Vec<Port>> for PortSpec {
+    fn from(ports: Vec<Port>) -> Self {
+        ports.into_iter().collect()
+    }
+}
+
+impl From<PortRange> for PortSpec {
+    fn from(range: PortRange) -> Self {
+        Self {
+            ranges: vec![range],
+        }
+    }
+}
+
+/// Sort ranges and merge any that overlap or touch.
+fn coalesce(ranges: &[PortRange]) -> Vec<PortRange> {
+    let mut sorted = ranges.to_vec();
+    sorted.sort_unstable_by_key(|r| (r.start, r.end));
+
+    let mut merged: Vec<PortRange> = Vec::with_capacity(sorted.len());
+    for range in sorted {
+        match merged.last_mut() {
+            Some(last) if u32::from(range.start.0) <= u32::from(last.end.0) + 1 => {
+                last.end = last.end.max(range.end);
+            }
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
+
 impl fmt::Display for PortSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let parts: Vec<String> = self.ranges.iter().map(|r| r.to_string()).collect();
@@ -311,6 +473,125 @@ mod tests {
         assert_eq!(range.len(), 100);
     }
 
+    #[test]
+    fn test_port_range_into_iter() {
+        let range: PortRange = "65530-65535".parse().unwrap();
+        let owned: Vec<u16> = range.into_iter().map(Port::as_u16).collect();
+        assert_eq!(owned, [65530, 65531, 65532, 65533, 65534, 65535]);
+
+        let mut borrowed = Vec::new();
+        for port in &range {
+            borrowed.push(port.as_u16());
+        }
+        assert_eq!(borrowed, owned);
+
+        let mut iter = range.iter();
+        assert_eq!(iter.len(), range.len());
+        assert_eq!(iter.next_back(), Port::new(65535));
+        let single = PortRange::single(Port::new(22).unwrap());
+        assert_eq!(single.into_iter().count(), 1);
+    }
+
+    #[test]
+    fn test_port_range_contains() {
+        let range = PortRange::new(Port::new(100).unwrap(), Port::new(200).unwrap()).unwrap();
+        assert!(range.contains(Port::new(100).unwrap()));
+        assert!(range.contains(Port::new(150).unwrap()));
+        assert!(range.contains(Port::new(200).unwrap()));
+        assert!(!range.contains(Port::new(99).unwrap()));
+        assert!(!range.contains(Port::new(201).unwrap()));
+
+        let single = PortRange::single(Port::new(80).unwrap());
+        assert!(single.contains(Port::new(80).unwrap()));
+        assert!(!single.contains(Port::new(81).unwrap()));
+    }
+
+    #[test]
+    fn test_port_range_parsing() {
+        let range: PortRange = "40000-40999".parse().unwrap();
+        assert_eq!(range.start().as_u16(), 40000);
+        assert_eq!(range.len(), 1000);
+        assert_eq!(" 53 ".parse::<PortRange>().unwrap().to_string(), "53");
+
+        assert!(matches!(
+            "2000-1000".parse::<PortRange>(),
+            Err(PortError::InvalidRange(2000, 1000))
+        ));
+        assert!(matches!(
+            "0-10".parse::<PortRange>(),
+            Err(PortError::OutOfRange(0))
+        ));
+        assert!("1-2-3".parse::<PortRange>().is_err());
+        assert!("".parse::<PortRange>().is_err());
+    }
+
+    #[test]
+    fn test_port_spec_contains() {
+        let spec: PortSpec = "22,80,8000-8010".parse().unwrap();
+        assert!(spec.contains(Port::new(22).unwrap()));
+        assert!(spec.contains(Port::new(8000).unwrap()));
+        assert!(spec.contains(Port::new(8010).unwrap()));
+        assert!(!spec.contains(Port::new(23).unwrap()));
+        assert!(!spec.contains(Port::new(7999).unwrap()));
+        assert!(!spec.contains(Port::new(8011).unwrap()));
+        assert!(!PortSpec::new().contains(Port::new(80).unwrap()));
+    }
+
+    #[test]
+    fn test_port_spec_iteration() {
+        let spec: PortSpec = "443,80,80,22".parse().unwrap();
+        let ports: Vec<u16> = (&spec).into_iter().map(Port::as_u16).collect();
+        assert_eq!(ports, vec![22, 80, 443]);
+
+        let mut total = 0;
+        for port in &spec {
+            assert!(spec.contains(port));
+            total += 1;
+        }
+        assert_eq!(total, 3);
+    }
+
+    #[test]
+    fn test_port_spec_from_iterator() {
+        let spec: PortSpec = [22, 80, 443]
+            .into_iter()
+            .filter_map(Port::new)
+            .collect();
+        assert_eq!(spec.count(), 3);
+        assert!(spec.contains(Port::new(80).unwrap()));
+
+        let empty: PortSpec = std::iter::empty().collect();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn test_port_spec_from_ports() {
+        let ports: Vec<Port> = (1..=100).filter_map(Port::new).collect();
+        let spec = PortSpec::from_ports(&ports);
+        assert_eq!(spec.ranges().len(), 1);
+        assert_eq!(spec.to_string(), "1-100");
+
+        let scattered: Vec<Port> = [443, 22, 80, 81, 82, 22]
+            .into_iter()
+            .filter_map(Port::new)
+            .collect();
+        let spec = PortSpec::from(scattered);
+        assert_eq!(spec.to_string(), "22,80-82,443");
+        let reparsed: PortSpec = spec.to_string().parse().unwrap();
+        assert_eq!(reparsed.to_ports(), spec.to_ports());
+
+        let range = PortRange::new(Port::new(8000).unwrap(), Port::new(8080).unwrap()).unwrap();
+        assert_eq!(PortSpec::from(range).to_string(), "8000-8080");
+    }
+
+    #[test]
+    fn test_port_spec_merge() {
+        let mut spec: PortSpec = "22,80".parse().unwrap();
+        spec.merge(&"80,1000-1002".parse().unwrap());
+        assert_eq!(spec.count(), 5);
+        assert!(spec.contains(Port::new(1001).unwrap()));
+    }
+
     #[test]
     fn test_port_spec_parsing() {
         let spec: PortSpec = "80".parse().unwrap();
@@ -326,6 +607,38 @@ mod tests {
         assert_eq!(spec.count(), 14);
     }
 
+    #[test]
+    fn test_port_spec_normalize() {
+        let spec: PortSpec = "1-10,5-20".parse().unwrap();
+        assert_eq!(spec.to_string(), "1-20");
+        assert_eq!(spec.count(), 20);
+
+        // Adjacent ranges and single ports merge too
+        let spec: PortSpec = "21,1-10,11-20,443".parse().unwrap();
+        assert_eq!(spec.to_string(), "1-21,443");
+        assert_eq!(spec.ranges().len(), 2);
+    }
+
+    #[test]
+    fn test_port_spec_count_unnormalized() {
+        let mut spec = PortSpec::new();
+        spec.add_range(PortRange::new(Port::new(1).unwrap(), Port::new(10).unwrap()).unwrap());
+        spec.add_range(PortRange::new(Port::new(5).unwrap(), Port::new(20).unwrap()).unwrap());
+        assert_eq!(spec.count(), 20);
+        assert_eq!(spec.ranges().len(), 2);
+
+        spec.normalize();
+        assert_eq!(spec.ranges().len(), 1);
+    }
+
+    #[test]
+    fn test_port_spec_count_full() {
+        let spec = PortSpec::full();
+        assert!(spec.is_normalized());
+        assert_eq!(spec.count(), 65535);
+        assert!(PortSpec::top_100().is_normalized());
+    }
+
     #[test]
     fn test_port_spec_dedup() {
         let spec: PortSpec = "80,80,443,80".parse().unwrap();
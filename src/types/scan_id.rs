@@ -10,7 +10,8 @@ use uuid::Uuid;
 
 /// A unique identifier for a scan result.
 ///
-/// Uses UUID v4 internally for globally unique identifiers.
+/// Uses UUID v4 internally for globally unique identifiers, or UUID v5 when
+/// derived from the scan inputs with [`ScanId::from_inputs`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ScanId(Uuid);
@@ -21,6 +22,17 @@ impl ScanId {
         Self(Uuid::new_v4())
     }
 
+    /// Derive a scan ID from the scan inputs.
+    ///
+    /// The same inputs always give the same ID, so a pipeline can compute
+    /// it before the scan runs. `seed` tells apart repeated scans of the same
+    /// target, e.g. a date or a pipeline run number.
+    pub fn from_inputs(target: &str, port_spec: &str, scan_type: &str, seed: &str) -> Self {
+        let namespace = Uuid::new_v5(&Uuid::NAMESPACE_URL, b"https://github.com/HueCodes/Scuttle");
+        let name = [target, port_spec, scan_type, seed].join("\n");
+        Self(Uuid::new_v5(&namespace, name.as_bytes()))
+    }
+
     /// Create a ScanId from raw bytes.
     pub fn from_bytes(bytes: [u8; 16]) -> Self {
         Self(Uuid::from_bytes(bytes))
@@ -53,9 +65,8 @@ impl FromStr for ScanId {
     type Err = ScanIdError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        // Support both full UUIDs and short forms
-        if s.len() == 8 {
-            // Short form - this is a prefix search, but for parsing we need full UUID
+        // A prefix can only be resolved against stored scans
+        if s.len() < 36 && !s.is_empty() && s.chars().all(|c| c.is_ascii_hexdigit() || c == '-') {
             return Err(ScanIdError::ShortFormNotSupported);
         }
 
@@ -69,7 +80,7 @@ impl FromStr for ScanId {
 pub enum ScanIdError {
     #[error("invalid scan ID format: {0}")]
     InvalidFormat(String),
-    #[error("short form IDs require database lookup")]
+    #[error("short form IDs must be resolved against stored scans (ScanStore::resolve)")]
     ShortFormNotSupported,
 }
 
@@ -84,6 +95,25 @@ mod tests {
         assert_ne!(id1, id2);
     }
 
+    #[test]
+    fn test_scan_id_from_inputs() {
+        let id = ScanId::from_inputs("10.0.0.1", "1-1000", "connect", "2024-06-01");
+        assert_eq!(
+            id,
+            ScanId::from_inputs("10.0.0.1", "1-1000", "connect", "2024-06-01")
+        );
+        assert_eq!(id.0.get_version_num(), 5);
+
+        for (target, ports, scan_type, seed) in [
+            ("10.0.0.2", "1-1000", "connect", "2024-06-01"),
+            ("10.0.0.1", "1-1024", "connect", "2024-06-01"),
+            ("10.0.0.1", "1-1000", "syn", "2024-06-01"),
+            ("10.0.0.1", "1-1000", "connect", "2024-06-02"),
+        ] {
+            assert_ne!(id, ScanId::from_inputs(target, ports, scan_type, seed));
+        }
+    }
+
     #[test]
     fn test_scan_id_display() {
         let id = ScanId::new();
@@ -3,7 +3,7 @@
 //! Provides flexible target parsing supporting:
 //! - Single IP addresses (IPv4 and IPv6)
 //! - CIDR notation (192.168.1.0/24)
-//! - Hostnames (example.com)
+//! - Hostnames (example.com), including internationalized names (münchen.de)
 //! - Multiple targets
 
 use ipnetwork::IpNetwork;
@@ -11,7 +11,10 @@ use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::net::IpAddr;
 use std::str::FromStr;
-use trust_dns_resolver::config::{ResolverConfig, ResolverOpts};
+use std::time::Duration;
+use trust_dns_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use trust_dns_resolver::error::{ResolveError, ResolveErrorKind};
+use trust_dns_resolver::proto::op::ResponseCode;
 use trust_dns_resolver::TokioAsyncResolver;
 
 /// A single scan target that has been resolved to an IP address.
@@ -41,6 +44,16 @@ impl ScanTarget {
     pub fn is_ipv4(&self) -> bool {
         self.ip.is_ipv4()
     }
+
+    /// Check if this target is the machine running the scan: a loopback or
+    /// unspecified address, or one assigned to a local interface.
+    pub fn is_local(&self) -> bool {
+        self.ip.is_loopback()
+            || self.ip.is_unspecified()
+            || pnet::datalink::interfaces()
+                .iter()
+                .any(|iface| iface.ips.iter().any(|net| net.ip() == self.ip))
+    }
 }
 
 impl fmt::Display for ScanTarget {
@@ -66,6 +79,72 @@ pub enum TargetError {
     InvalidCidr(String),
     #[error("CIDR range too large: {0} addresses (max: {1})")]
     CidrTooLarge(u128, u128),
+    #[error("invalid internationalized hostname: {0}")]
+    InvalidIdn(String),
+    #[error("cannot resolve hostname '{0}': DNS lookups are disabled")]
+    DnsDisabled(String),
+}
+
+/// Options controlling how [`TargetSpec::resolve_with`] resolves hostnames.
+#[derive(Debug, Clone, Copy)]
+pub struct ResolveOptions {
+    /// Retries for transient DNS failures.
+    pub dns_retries: u32,
+    /// Whether DNS queries may be sent; when off, hostnames fail to resolve.
+    pub allow_dns: bool,
+    /// Keep every address a hostname resolves to instead of only the first.
+    pub all_ips: bool,
+    /// Name server to query instead of the system configuration.
+    pub dns_server: Option<IpAddr>,
+}
+
+impl Default for ResolveOptions {
+    fn default() -> Self {
+        Self {
+            dns_retries: TargetSpec::DEFAULT_DNS_RETRIES,
+            allow_dns: true,
+            all_ips: false,
+            dns_server: None,
+        }
+    }
+}
+
+impl ResolveOptions {
+    /// Set the number of retries for transient DNS failures.
+    pub fn with_dns_retries(mut self, retries: u32) -> Self {
+        self.dns_retries = retries;
+        self
+    }
+
+    /// Refuse to send any DNS query, so only IP and CIDR targets resolve.
+    pub fn without_dns(mut self) -> Self {
+        self.allow_dns = false;
+        self
+    }
+
+    /// Keep every address of a hostname, not only the first.
+    pub fn with_all_ips(mut self) -> Self {
+        self.all_ips = true;
+        self
+    }
+
+    /// Send queries to `server` (port 53) instead of the system resolvers.
+    pub fn with_dns_server(mut self, server: IpAddr) -> Self {
+        self.dns_server = Some(server);
+        self
+    }
+
+    /// Resolver configuration for these options.
+    pub(crate) fn resolver_config(&self) -> ResolverConfig {
+        match self.dns_server {
+            Some(server) => ResolverConfig::from_parts(
+                None,
+                Vec::new(),
+                NameServerConfigGroup::from_ips_clear(&[server], 53, true),
+            ),
+            None => ResolverConfig::default(),
+        }
+    }
 }
 
 /// A target specification that may contain multiple targets.
@@ -74,6 +153,7 @@ pub enum TargetError {
 /// - Single IP: "192.168.1.1"
 /// - CIDR: "192.168.1.0/24"
 /// - Hostname: "example.com"
+/// - Internationalized hostname: "münchen.de"
 /// - IPv6: "::1", "2001:db8::/32"
 #[derive(Debug, Clone)]
 pub enum TargetSpec {
@@ -81,7 +161,9 @@ pub enum TargetSpec {
     Single(IpAddr),
     /// A CIDR network range.
     Cidr(IpNetwork),
-    /// A hostname to be resolved.
+    /// A hostname to be resolved, kept as the user typed it.
+    ///
+    /// Unicode names are converted to punycode only at resolution time.
     Hostname(String),
 }
 
@@ -89,6 +171,9 @@ impl TargetSpec {
     /// Maximum number of hosts allowed in a CIDR range.
     pub const MAX_CIDR_HOSTS: u128 = 65536; // /16 for IPv4
 
+    /// Default number of retries for transient DNS failures.
+    pub const DEFAULT_DNS_RETRIES: u32 = 2;
+
     /// Parse a target specification from a string.
     pub fn parse(s: &str) -> Result<Self, TargetError> {
         let s = s.trim();
@@ -118,8 +203,8 @@ impl TargetSpec {
             return Ok(Self::Cidr(network));
         }
 
-        // Assume it's a hostname
-        if is_valid_hostname(s) {
+        // Assume it's a hostname, validating its ASCII (punycode) form
+        if is_valid_hostname(&to_ascii_hostname(s)?) {
             return Ok(Self::Hostname(s.to_string()));
         }
 
@@ -129,8 +214,31 @@ impl TargetSpec {
     /// Resolve this target specification to a list of scan targets.
     ///
     /// For CIDR ranges, this expands to all host addresses.
-    /// For hostnames, this performs DNS resolution.
+    /// For hostnames, this performs DNS resolution on the punycode form while
+    /// keeping the original (possibly Unicode) name for display.
     pub async fn resolve(&self) -> Result<Vec<ScanTarget>, TargetError> {
+        self.resolve_with_retries(Self::DEFAULT_DNS_RETRIES).await
+    }
+
+    /// Resolve this target specification, retrying transient DNS failures.
+    ///
+    /// Timeouts and server failures are retried up to `retries` times with
+    /// jittered exponential backoff. Permanent failures such as NXDOMAIN are
+    /// returned immediately.
+    pub async fn resolve_with_retries(
+        &self,
+        retries: u32,
+    ) -> Result<Vec<ScanTarget>, TargetError> {
+        self.resolve_with(&ResolveOptions::default().with_dns_retries(retries))
+            .await
+    }
+
+    /// Resolve this target specification with the given options.
+    pub async fn resolve_with(
+        &self,
+        options: &ResolveOptions,
+    ) -> Result<Vec<ScanTarget>, TargetError> {
+        let retries = options.dns_retries;
         match self {
             Self::Single(ip) => Ok(vec![ScanTarget::new(ip.to_string(), *ip)]),
 
@@ -154,33 +262,71 @@ impl TargetSpec {
                 Ok(targets)
             }
 
-            Self::Hostname(hostname) => {
-                let resolver = TokioAsyncResolver::tokio(
-                    ResolverConfig::default(),
-                    ResolverOpts::default(),
-                );
-
-                let response = resolver.lookup_ip(hostname.as_str()).await.map_err(|e| {
-                    TargetError::DnsResolutionFailed(hostname.clone(), e.to_string())
-                })?;
+            Self::Hostname(hostname) if !options.allow_dns => {
+                Err(TargetError::DnsDisabled(hostname.clone()))
+            }
 
-                let ips: Vec<IpAddr> = response.iter().collect();
+            Self::Hostname(hostname) => {
+                let resolver =
+                    TokioAsyncResolver::tokio(options.resolver_config(), ResolverOpts::default());
+
+                let ascii = to_ascii_hostname(hostname)?;
+                let mut attempt = 0;
+                let response = loop {
+                    match resolver.lookup_ip(ascii.as_str()).await {
+                        Ok(response) => break response,
+                        Err(e) if attempt < retries && is_transient_dns_error(&e) => {
+                            attempt += 1;
+                            tracing::debug!(
+                                "DNS lookup for {} failed ({}), retry {}/{}",
+                                hostname,
+                                e,
+                                attempt,
+                                retries
+                            );
+                            tokio::time::sleep(dns_backoff(attempt)).await;
+                        }
+                        Err(e) => {
+                            return Err(TargetError::DnsResolutionFailed(
+                                hostname.clone(),
+                                e.to_string(),
+                            ))
+                        }
+                    }
+                };
+
+                let mut ips: Vec<IpAddr> = Vec::new();
+                for ip in response.iter() {
+                    if !ips.contains(&ip) {
+                        ips.push(ip);
+                    }
+                }
                 if ips.is_empty() {
                     return Err(TargetError::NoAddressesFound(hostname.clone()));
                 }
 
-                // Return only the first IP (most common use case)
-                // Users can specify --all-ips flag if they want all resolved IPs
-                Ok(vec![ScanTarget::new(hostname.clone(), ips[0])])
+                // Return only the first IP (most common use case) unless
+                // every address was asked for
+                if !options.all_ips {
+                    ips.truncate(1);
+                }
+                Ok(ips
+                    .into_iter()
+                    .map(|ip| ScanTarget::new(hostname.clone(), ip))
+                    .collect())
             }
         }
     }
 
     /// Get an estimate of how many hosts this target represents.
+    ///
+    /// Matches what [`resolve`](Self::resolve) yields for IP and CIDR targets,
+    /// so IPv4 network and broadcast addresses are not counted.
     pub fn estimated_host_count(&self) -> u128 {
         match self {
             Self::Single(_) => 1,
             Self::Cidr(network) => match network {
+                IpNetwork::V4(net) if net.prefix() < 31 => net.size() as u128 - 2,
                 IpNetwork::V4(net) => net.size() as u128,
                 IpNetwork::V6(net) => {
                     let prefix = net.prefix() as u32;
@@ -210,7 +356,61 @@ impl fmt::Display for TargetSpec {
     }
 }
 
-/// Check if a string is a valid hostname.
+/// Shuffle resolved targets in place so a range is not swept in address order.
+///
+/// Resolution already holds every target in memory, so an in-place
+/// Fisher-Yates shuffle costs no extra allocation at any range size.
+pub fn shuffle_targets(targets: &mut [ScanTarget]) {
+    use rand::seq::SliceRandom;
+    targets.shuffle(&mut rand::thread_rng());
+}
+
+/// Format a host and port as `host:port`, bracketing IPv6 addresses
+/// (`[::1]:80`) so the port cannot be mistaken for part of the address.
+pub fn host_port(host: &str, port: u16) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// Check whether a DNS failure is worth retrying.
+///
+/// Timeouts, I/O problems, and SERVFAIL are usually transient; NXDOMAIN and
+/// empty answers are authoritative and will not change on retry.
+fn is_transient_dns_error(error: &ResolveError) -> bool {
+    match error.kind() {
+        ResolveErrorKind::Timeout
+        | ResolveErrorKind::Io(_)
+        | ResolveErrorKind::Proto(_)
+        | ResolveErrorKind::NoConnections => true,
+        ResolveErrorKind::NoRecordsFound { response_code, .. } => {
+            *response_code == ResponseCode::ServFail
+        }
+        _ => false,
+    }
+}
+
+/// Backoff before DNS retry `attempt` (1-based): 100ms, 200ms, 400ms, ... plus jitter.
+fn dns_backoff(attempt: u32) -> Duration {
+    let base = 100u64 << attempt.saturating_sub(1).min(6);
+    let jitter = rand::random::<u64>() % 50;
+    Duration::from_millis(base + jitter)
+}
+
+/// Convert a hostname to its ASCII form, applying IDNA (punycode) encoding.
+///
+/// ASCII hostnames pass through unchanged apart from case normalization.
+fn to_ascii_hostname(s: &str) -> Result<String, TargetError> {
+    if s.is_ascii() {
+        return Ok(s.to_ascii_lowercase());
+    }
+
+    idna::domain_to_ascii(s).map_err(|_| TargetError::InvalidIdn(s.to_string()))
+}
+
+/// Check if a string is a valid (ASCII) hostname.
 fn is_valid_hostname(s: &str) -> bool {
     if s.is_empty() || s.len() > 253 {
         return false;
@@ -223,14 +423,14 @@ fn is_valid_hostname(s: &str) -> bool {
             return false;
         }
         // Must start and end with alphanumeric
-        if !label.chars().next().map_or(false, |c| c.is_alphanumeric()) {
+        if !label.chars().next().is_some_and(|c| c.is_ascii_alphanumeric()) {
             return false;
         }
-        if !label.chars().last().map_or(false, |c| c.is_alphanumeric()) {
+        if !label.chars().last().is_some_and(|c| c.is_ascii_alphanumeric()) {
             return false;
         }
         // Can only contain alphanumeric and hyphens
-        if !label.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
             return false;
         }
     }
@@ -248,6 +448,47 @@ mod tests {
         assert!(matches!(spec, TargetSpec::Single(IpAddr::V4(_))));
     }
 
+    #[tokio::test]
+    async fn test_shuffle_targets() {
+        let original = TargetSpec::parse("10.0.0.0/24")
+            .unwrap()
+            .resolve()
+            .await
+            .unwrap();
+        let mut shuffled = original.clone();
+        shuffle_targets(&mut shuffled);
+
+        let set = |targets: &[ScanTarget]| {
+            targets
+                .iter()
+                .cloned()
+                .collect::<std::collections::HashSet<_>>()
+        };
+        assert_eq!(shuffled.len(), original.len());
+        assert_eq!(set(&shuffled), set(&original));
+    }
+
+    #[test]
+    fn test_is_local() {
+        for ip in ["127.0.0.1", "127.0.1.1", "::1", "0.0.0.0"] {
+            assert!(
+                ScanTarget::new(ip, ip.parse().unwrap()).is_local(),
+                "{}",
+                ip
+            );
+        }
+        // Documentation addresses are never assigned to this machine
+        let remote: IpAddr = "198.51.100.7".parse().unwrap();
+        assert!(!ScanTarget::new("remote", remote).is_local());
+
+        // Every address of every interface, not only loopback
+        for iface in pnet::datalink::interfaces() {
+            for net in iface.ips {
+                assert!(ScanTarget::new(&iface.name, net.ip()).is_local());
+            }
+        }
+    }
+
     #[test]
     fn test_parse_ipv6() {
         let spec = TargetSpec::parse("::1").unwrap();
@@ -264,6 +505,65 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_estimated_host_count_matches_resolve() {
+        for target in ["10.0.0.0/24", "10.0.0.0/31", "10.0.0.1/32", "10.0.0.1", "fd00::/120"] {
+            let spec = TargetSpec::parse(target).unwrap();
+            let resolved = spec.resolve().await.unwrap().len() as u128;
+            assert_eq!(spec.estimated_host_count(), resolved, "{}", target);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_without_dns() {
+        let options = ResolveOptions::default().without_dns();
+
+        let hosts = TargetSpec::parse("10.0.0.0/30").unwrap();
+        assert_eq!(hosts.resolve_with(&options).await.unwrap().len(), 2);
+        let host = TargetSpec::parse("::1").unwrap();
+        assert_eq!(host.resolve_with(&options).await.unwrap().len(), 1);
+
+        let name = TargetSpec::parse("example.com").unwrap();
+        assert!(matches!(
+            name.resolve_with(&options).await,
+            Err(TargetError::DnsDisabled(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_options_builders() {
+        let options = ResolveOptions::default();
+        assert!(!options.all_ips);
+        assert_eq!(options.dns_server, None);
+
+        let server: IpAddr = "192.0.2.53".parse().unwrap();
+        let options = options.with_all_ips().with_dns_server(server);
+        assert!(options.all_ips);
+        assert_eq!(options.dns_server, Some(server));
+
+        let config = options.resolver_config();
+        let name_servers = config.name_servers();
+        assert!(!name_servers.is_empty());
+        assert!(name_servers
+            .iter()
+            .all(|ns| ns.socket_addr == (server, 53).into()));
+    }
+
+    #[tokio::test]
+    async fn test_resolve_localhost() {
+        let spec = TargetSpec::parse("localhost").unwrap();
+
+        let targets = spec.resolve_with(&ResolveOptions::default()).await.unwrap();
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].original, "localhost");
+        assert!(targets[0].ip.is_loopback());
+
+        let options = ResolveOptions::default().with_all_ips();
+        let targets = spec.resolve_with(&options).await.unwrap();
+        assert!(!targets.is_empty());
+        assert!(targets.iter().all(|t| t.ip.is_loopback()));
+    }
+
     #[test]
     fn test_parse_hostname() {
         let spec = TargetSpec::parse("example.com").unwrap();
@@ -284,5 +584,44 @@ mod tests {
         assert!(is_valid_hostname("my-server"));
         assert!(!is_valid_hostname(""));
         assert!(!is_valid_hostname("-invalid.com"));
+        assert!(!is_valid_hostname("münchen.de"));
+    }
+
+    #[test]
+    fn test_transient_dns_errors() {
+        assert!(is_transient_dns_error(&ResolveErrorKind::Timeout.into()));
+        assert!(is_transient_dns_error(&ResolveErrorKind::NoConnections.into()));
+        assert!(!is_transient_dns_error(&ResolveErrorKind::Message("bad").into()));
+    }
+
+    #[test]
+    fn test_host_port() {
+        assert_eq!(host_port("192.0.2.1", 80), "192.0.2.1:80");
+        assert_eq!(host_port("::1", 80), "[::1]:80");
+        assert_eq!(host_port("2001:db8::1", 443), "[2001:db8::1]:443");
+        assert_eq!(host_port("example.com", 22), "example.com:22");
+    }
+
+    #[test]
+    fn test_dns_backoff_grows() {
+        assert!(dns_backoff(1) >= Duration::from_millis(100));
+        assert!(dns_backoff(1) < Duration::from_millis(150));
+        assert!(dns_backoff(3) >= Duration::from_millis(400));
+        assert!(dns_backoff(3) < Duration::from_millis(450));
+    }
+
+    #[test]
+    fn test_parse_idn_hostname() {
+        let spec = TargetSpec::parse("münchen.de").unwrap();
+        match spec {
+            TargetSpec::Hostname(name) => assert_eq!(name, "münchen.de"),
+            _ => panic!("Expected hostname"),
+        }
+    }
+
+    #[test]
+    fn test_to_ascii_hostname() {
+        assert_eq!(to_ascii_hostname("münchen.de").unwrap(), "xn--mnchen-3ya.de");
+        assert_eq!(to_ascii_hostname("Example.COM").unwrap(), "example.com");
     }
 }
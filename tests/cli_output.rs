@@ -0,0 +1,392 @@
+//! End-to-end tests of the scuttle binary's output.
+
+use std::net::TcpListener;
+use std::process::{Command, Output};
+
+#[test]
+fn test_range_scan_json_is_one_array() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let home = tempfile::tempdir().unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_scuttle"))
+        .args(["scan", "127.0.0.0/30", "-p", &port, "-o", "json"])
+        .args(["--skip-host-discovery", "--no-save", "--timeout", "500"])
+        .env("XDG_DATA_HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .env("XDG_CACHE_HOME", home.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let hosts = json.as_array().expect("top-level array");
+    assert_eq!(hosts.len(), 2);
+    assert_eq!(hosts[0]["ip_address"], "127.0.0.1");
+    assert_eq!(hosts[0]["open_ports"], 1);
+    assert_eq!(hosts[1]["ip_address"], "127.0.0.2");
+}
+
+/// Run `scuttle scan` with a throwaway home and no saved history.
+fn scan(args: &[&str]) -> Output {
+    let home = tempfile::tempdir().unwrap();
+    let output = Command::new(env!("CARGO_BIN_EXE_scuttle"))
+        .arg("scan")
+        .args(args)
+        .args(["--no-save", "--timeout", "500"])
+        .env("XDG_DATA_HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path())
+        .env("XDG_CACHE_HOME", home.path())
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    output
+}
+
+#[test]
+fn test_ipv6_output_is_well_formed() {
+    // Hosts without IPv6 have nothing to check
+    let Ok(listener) = TcpListener::bind("[::1]:0") else {
+        return;
+    };
+    let port = listener.local_addr().unwrap().port().to_string();
+
+    let output = scan(&["::1", "-p", &port, "--template", "{addr} {status}"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        format!("[::1]:{} open\n", port)
+    );
+
+    let output = scan(&["::1", "-p", &port]);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("  IP Address: ::1\n"), "{}", stdout);
+    // The port row lines up under the PORT and STATE headers
+    let row = format!("  {:>6}  {:^14}  ", port, "open");
+    assert!(
+        stdout.lines().any(|line| line.starts_with(&row)),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_quiet_machine_output_is_clean() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let range = [
+        "127.0.0.0/30",
+        "-p",
+        &port,
+        "-q",
+        "-v",
+        "--skip-host-discovery",
+    ];
+
+    // JSON Lines: every line is a record and nothing else
+    let output = scan(&[&range[..], &["-o", "jsonl"]].concat());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 2, "{}", stdout);
+    for line in stdout.lines() {
+        serde_json::from_str::<serde_json::Value>(line).unwrap();
+    }
+
+    // CSV: one table for the whole range, with a single header
+    let output = scan(&[&range[..], &["-o", "csv"]].concat());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2, "{}", stdout);
+    assert_eq!(lines[0], "host,port,status,service,banner,response_time_ms");
+    assert!(lines[1].starts_with(&format!("127.0.0.1,{},open,", port)));
+
+    // JSON for a single host parses with nothing trailing
+    let output = scan(&["127.0.0.1", "-p", &port, "-q", "-o", "json"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["open_ports"], 1);
+}
+
+#[test]
+fn test_range_csv_honors_sort() {
+    // A closed port numbered below the open one, so sorting changes the order
+    let (open, closed) = loop {
+        let open = TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed = TcpListener::bind("127.0.0.1:0").unwrap();
+        let closed_port = closed.local_addr().unwrap().port();
+        if closed_port < open.local_addr().unwrap().port() {
+            break (open, closed_port.to_string());
+        }
+    };
+    let open_port = open.local_addr().unwrap().port().to_string();
+    let ports = format!("{},{}", closed, open_port);
+
+    let output = scan(&[
+        "127.0.0.0/30",
+        "-p",
+        &ports,
+        "-q",
+        "--skip-host-discovery",
+        "--show-closed",
+        "-o",
+        "csv",
+        "--sort",
+        "status",
+    ]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let rows: Vec<&str> = stdout.lines().skip(1).collect();
+    assert_eq!(rows.len(), 4, "{}", stdout);
+    assert!(rows[0].starts_with(&format!("127.0.0.1,{},open,", open_port)));
+    assert!(rows[1].starts_with(&format!("127.0.0.1,{},closed,", closed)));
+    assert!(rows[2].starts_with("127.0.0.2,"));
+}
+
+#[test]
+fn test_compact_json_is_one_line() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+
+    let output = scan(&["127.0.0.1", "-p", &port, "-q", "-o", "json-compact"]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json["open_ports"], 1);
+
+    // A range is still one array, as with -o json
+    let output = scan(&[
+        "127.0.0.0/30",
+        "-p",
+        &port,
+        "-q",
+        "--skip-host-discovery",
+        "-o",
+        "json-compact",
+    ]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+    let json: serde_json::Value = serde_json::from_str(&stdout).unwrap();
+    assert_eq!(json.as_array().expect("top-level array").len(), 2);
+}
+
+#[test]
+fn test_exit_code_reflects_outcome() {
+    let closed = {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        listener.local_addr().unwrap().port().to_string()
+    };
+    let home = tempfile::tempdir().unwrap();
+    let exit_code = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_scuttle"))
+            .arg("scan")
+            .args(args)
+            .args(["--no-save", "-q"])
+            .env("XDG_DATA_HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path())
+            .env("XDG_CACHE_HOME", home.path())
+            .output()
+            .unwrap()
+            .status
+            .code()
+    };
+
+    assert_eq!(exit_code(&["127.0.0.1", "-p", &closed]), Some(3));
+    assert_eq!(exit_code(&["127.0.0.1", "-p", "0"]), Some(2));
+    assert_eq!(exit_code(&["127.0.0.1", "-p", &closed, "-c", "0"]), Some(2));
+    assert_eq!(
+        exit_code(&["127.0.0.1", "-p", &closed, "--sample", "100"]),
+        Some(6)
+    );
+
+    // A warning that loses no ports leaves the code alone
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let open = listener.local_addr().unwrap().port().to_string();
+    assert_eq!(
+        exit_code(&["127.0.0.1", "-p", &open, "-c", "5000"]),
+        Some(0)
+    );
+    assert_eq!(
+        exit_code(&["127.0.0.1", "-p", &closed, "-c", "5000"]),
+        Some(3)
+    );
+}
+
+#[test]
+fn test_scan_like_reuses_saved_settings() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let home = tempfile::tempdir().unwrap();
+    let scan = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_scuttle"))
+            .arg("scan")
+            .args(args)
+            .args(["-o", "json", "--timeout", "500"])
+            .env("XDG_DATA_HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path())
+            .env("XDG_CACHE_HOME", home.path())
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        serde_json::from_slice::<serde_json::Value>(&output.stdout).unwrap()
+    };
+
+    let ports = format!("9,{}", port);
+    let first = scan(&["127.0.0.1", "-p", &ports]);
+    let id = first["id"].as_str().unwrap();
+
+    let like = scan(&["--like", id, "--no-save"]);
+    assert_ne!(like["id"], first["id"]);
+    assert_eq!(like["ip_address"], "127.0.0.1");
+    assert_eq!(like["port_spec"], first["port_spec"]);
+    assert_eq!(like["scan_type"], first["scan_type"]);
+
+    // Flags on the command line win over the saved scan's
+    let like = scan(&["--like", id, "-p", &port, "--no-save"]);
+    assert_eq!(like["port_spec"], port.as_str());
+}
+
+#[test]
+fn test_msgpack_storage_and_export() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let home = tempfile::tempdir().unwrap();
+    let config_dir = home.path().join("scuttle");
+    std::fs::create_dir_all(&config_dir).unwrap();
+    std::fs::write(
+        config_dir.join("settings.json"),
+        r#"{"storage_format": "msgpack"}"#,
+    )
+    .unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_scuttle"))
+            .args(args)
+            .env("XDG_DATA_HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path())
+            .env("XDG_CACHE_HOME", home.path())
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output.stdout
+    };
+
+    let scanned = run(&["scan", "127.0.0.1", "-p", &port, "-o", "json", "-q"]);
+    let scanned: serde_json::Value = serde_json::from_slice(&scanned).unwrap();
+    let id = scanned["id"].as_str().unwrap();
+    let stored = home.path().join(format!("scuttle/scans/{}.msgpack", id));
+    assert!(stored.exists());
+
+    // Saved as MessagePack, exported as JSON
+    let exported = run(&["export", &id[..8], "-f", "json"]);
+    let exported: serde_json::Value = serde_json::from_slice(&exported).unwrap();
+    assert_eq!(exported["id"], scanned["id"]);
+    assert_eq!(exported["open_ports"], 1);
+
+    let archive = home.path().join("scan.msgpack");
+    run(&[
+        "export",
+        id,
+        "-f",
+        "msgpack",
+        "-o",
+        archive.to_str().unwrap(),
+    ]);
+    let archived: scuttle::storage::ScanRecord =
+        scuttle::storage::StorageFormat::load(&archive).unwrap();
+    assert_eq!(archived.id.to_string(), id);
+}
+
+#[test]
+fn test_history_stats_json() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    let home = tempfile::tempdir().unwrap();
+    let run = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_scuttle"))
+            .args(args)
+            .env("XDG_DATA_HOME", home.path())
+            .env("XDG_CONFIG_HOME", home.path())
+            .env("XDG_CACHE_HOME", home.path())
+            .output()
+            .unwrap();
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+        output.stdout
+    };
+
+    let ports = format!("9,{}", port);
+    run(&["scan", "127.0.0.1", "-p", &ports, "-q", "--show-closed"]);
+    run(&["scan", "127.0.0.1", "-p", &port, "-q"]);
+
+    let stats = run(&["history", "stats", "-o", "json"]);
+    let stats: serde_json::Value = serde_json::from_slice(&stats).unwrap();
+    assert_eq!(stats["scan_count"], 2);
+    assert_eq!(stats["host_count"], 1);
+    assert_eq!(stats["total_ports_scanned"], 3);
+    assert_eq!(stats["total_open_ports"], 2);
+    assert!(stats["total_size_bytes"].as_u64().unwrap() > 0);
+}
+
+#[test]
+fn test_local_host_is_flagged() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+
+    let output = scan(&["127.0.0.1", "-p", &port, "-o", "json"]);
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(json["local_host"], true);
+
+    let output = scan(&["127.0.0.1", "-p", &port]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(
+        stdout.contains("  Local Host: this machine\n"),
+        "{}",
+        stdout
+    );
+}
+
+#[test]
+fn test_resolve_json() {
+    let output = Command::new(env!("CARGO_BIN_EXE_scuttle"))
+        .args([
+            "resolve",
+            "127.0.0.1",
+            "10.0.0.0/30",
+            "localhost",
+            "-o",
+            "json",
+        ])
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    let json: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let targets = json.as_array().expect("top-level array");
+    assert_eq!(targets.len(), 4);
+    assert_eq!(targets[0]["original"], "127.0.0.1");
+    assert_eq!(targets[0]["ip"], "127.0.0.1");
+    assert_eq!(targets[1]["original"], "10.0.0.0/30");
+    assert_eq!(targets[1]["ip"], "10.0.0.1");
+    assert_eq!(targets[2]["ip"], "10.0.0.2");
+    assert_eq!(targets[3]["original"], "localhost");
+    let ip: std::net::IpAddr = targets[3]["ip"].as_str().unwrap().parse().unwrap();
+    assert!(ip.is_loopback());
+}
@@ -0,0 +1,88 @@
+//! End-to-end tests of the TCP connect path against local listeners.
+
+use scuttle::scanner::{create_scanner, run_scan, ScanConfig, ScanJobConfig, TcpConnectScanner};
+use scuttle::services::ServiceSource;
+use scuttle::{Port, PortStatus, ScanType, Scanner};
+use std::net::{IpAddr, Ipv4Addr};
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::net::TcpListener;
+
+const LOCALHOST: IpAddr = IpAddr::V4(Ipv4Addr::LOCALHOST);
+
+/// Bind a listener on an ephemeral localhost port.
+async fn listener() -> (TcpListener, Port) {
+    let listener = TcpListener::bind((LOCALHOST, 0)).await.unwrap();
+    let port = Port::new(listener.local_addr().unwrap().port()).unwrap();
+    (listener, port)
+}
+
+/// Accept one connection and send `greeting` on it.
+fn serve_greeting(listener: TcpListener, greeting: &'static [u8]) {
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        socket.write_all(greeting).await.unwrap();
+        // Keep the connection open until the scanner hangs up
+        let _ = socket.readable().await;
+    });
+}
+
+/// A port that was just in use and is now closed.
+async fn closed_port() -> Port {
+    let (listener, port) = listener().await;
+    drop(listener);
+    port
+}
+
+#[tokio::test]
+async fn test_open_port() {
+    let (_listener, port) = listener().await;
+    let scanner = TcpConnectScanner::new(LOCALHOST, Duration::from_secs(1), false);
+
+    let result = scanner.scan_port(port).await;
+    assert_eq!(result.status, PortStatus::Open);
+    assert!(result.response_time_ms.is_some());
+    assert_eq!(result.banner, None);
+}
+
+#[tokio::test]
+async fn test_banner_captured() {
+    let (listener, port) = listener().await;
+    serve_greeting(listener, b"SSH-2.0-OpenSSH_9.6\r\n");
+    let scanner = TcpConnectScanner::new(LOCALHOST, Duration::from_secs(1), true);
+
+    let result = scanner.scan_port(port).await;
+    assert_eq!(result.status, PortStatus::Open);
+    assert_eq!(result.banner.as_deref(), Some("SSH-2.0-OpenSSH_9.6"));
+    assert_eq!(result.service, "ssh");
+    assert_eq!(result.service_source, ServiceSource::VersionDetected);
+}
+
+#[tokio::test]
+async fn test_closed_port() {
+    let port = closed_port().await;
+    let scanner = TcpConnectScanner::new(LOCALHOST, Duration::from_millis(500), false);
+
+    let result = scanner.scan_port(port).await;
+    assert!(matches!(
+        result.status,
+        PortStatus::Closed | PortStatus::Filtered
+    ));
+}
+
+#[tokio::test]
+async fn test_run_scan_mixed_ports() {
+    let (_listener, open) = listener().await;
+    let closed = closed_port().await;
+
+    let config = ScanConfig::new(LOCALHOST).with_timeout(Duration::from_millis(500));
+    let scanner = create_scanner(ScanType::Connect, config).unwrap();
+    let job = ScanJobConfig::new(vec![open, closed]).with_closed();
+
+    let record = run_scan(scanner, job).await.unwrap();
+    assert_eq!(record.open_ports, 1);
+    assert_eq!(record.results.len(), 2);
+    let open_result = record.results.iter().find(|r| r.port == open).unwrap();
+    assert_eq!(open_result.status, PortStatus::Open);
+    assert_eq!(record.source_ip.as_deref(), Some("127.0.0.1"));
+}